@@ -0,0 +1,338 @@
+//! One-call analysis of a wallet for API consumers
+//! Bundles stats, score breakdown, tier, pattern flags and ML feature vectors
+
+use crate::{
+    buy_cadence, calculate_whale_score_with_config, clamped_early_count, concentration_ratio, detect_ladder,
+    process_interactions, timing_entropy, token_diversity, wallet_phase, whale_score_components_with, Phase,
+    ScoringConfig, TokenInteraction, TradeSide, WalletStats, WhaleTier, CADENCE_BUCKET_SECONDS,
+};
+
+/// Minimum rising buys for `PatternFlags::ladder`
+const LADDER_MIN_STEPS: usize = 3;
+
+/// Points earned by each whale score component and the final score
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoreBreakdown {
+    pub early_entry: f64,
+    pub buy_size: f64,
+    pub repetition: f64,
+    pub profit: f64,
+    /// `calculate_whale_score_with_config`, which may exceed the component
+    /// sum when the cold-start floor applies
+    pub total: u8,
+}
+
+impl ScoreBreakdown {
+    pub fn new(stats: &WalletStats, config: &ScoringConfig) -> Self {
+        let [early_entry, buy_size, repetition, profit] = if stats.interaction_count == 0 {
+            [0.0; 4]
+        } else {
+            whale_score_components_with(stats, config)
+        };
+
+        Self {
+            early_entry,
+            buy_size,
+            repetition,
+            profit,
+            total: calculate_whale_score_with_config(stats, config),
+        }
+    }
+}
+
+/// Behavioral patterns detected over the wallet's whole history
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PatternFlags {
+    /// At least three buys of increasing size (`detect_ladder`)
+    pub ladder: bool,
+    /// Net flow over all interactions is clearly buying (`wallet_phase`)
+    pub accumulating: bool,
+    /// Net flow over all interactions is clearly selling (`wallet_phase`)
+    pub distributing: bool,
+}
+
+/// Everything `full_analysis` knows about a wallet
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnalysisResult {
+    pub stats: WalletStats,
+    pub breakdown: ScoreBreakdown,
+    pub tier: WhaleTier,
+    pub patterns: PatternFlags,
+}
+
+/// Analyze one wallet's interactions in a single call
+///
+/// Stats come from `process_interactions`, the breakdown and tier from the
+/// scoring curves in `config`, and the pattern flags from the detectors in
+/// `patterns` run over the full history.
+pub fn full_analysis(interactions: &[TokenInteraction], config: &ScoringConfig) -> AnalysisResult {
+    let stats = process_interactions(interactions);
+    let breakdown = ScoreBreakdown::new(&stats, config);
+    let latest = interactions.iter().map(|i| i.block_time).max().unwrap_or(0);
+    let phase = wallet_phase(interactions, u64::MAX, latest);
+
+    AnalysisResult {
+        tier: WhaleTier::from_score(breakdown.total),
+        patterns: PatternFlags {
+            ladder: detect_ladder(interactions, LADDER_MIN_STEPS),
+            accumulating: phase == Phase::Accumulating,
+            distributing: phase == Phase::Distributing,
+        },
+        stats,
+        breakdown,
+    }
+}
+
+/// Number of slots in `wallet_feature_vector`
+pub const FEATURE_VECTOR_LEN: usize = 7;
+
+/// Mean gap between buys that `wallet_feature_vector` maps to 0.5
+const CADENCE_HALF_POINT_SECONDS: f64 = 3600.0;
+
+/// Fixed-length numeric fingerprint of a wallet for external ML
+///
+/// Every slot lies in `[0, 1]` and the order is stable:
+/// - 0: `total_volume_sol / 500`, capped at 1 (the scorer's volume ceiling)
+/// - 1: `average_entry_size / 50`, capped at 1 (the scorer's size ceiling)
+/// - 2: early entry ratio, with the early count clamped to the interaction count
+/// - 3: `winrate_proxy`
+/// - 4: cadence, `gap / (gap + 3600)` for the mean gap between buys in
+///   seconds (`buy_cadence`), so 0.5 is an hour; 1.0 with fewer than two buys
+/// - 5: token diversity (`token_diversity`)
+/// - 6: concentration ratio (`concentration_ratio`)
+///
+/// Slots 0-3 come from `stats`, 4-6 from `interactions`.
+pub fn wallet_feature_vector(stats: &WalletStats, interactions: &[TokenInteraction]) -> [f64; FEATURE_VECTOR_LEN] {
+    let unit = |value: f64| if value.is_nan() { 0.0 } else { value.clamp(0.0, 1.0) };
+    let early_ratio = if stats.interaction_count > 0 {
+        clamped_early_count(stats).0 as f64 / stats.interaction_count as f64
+    } else {
+        0.0
+    };
+    let cadence = buy_cadence(interactions).map_or(1.0, |gap| gap / (gap + CADENCE_HALF_POINT_SECONDS));
+
+    [
+        unit(stats.total_volume_sol / 500.0),
+        unit(stats.average_entry_size / 50.0),
+        unit(early_ratio),
+        unit(stats.winrate_proxy),
+        unit(cadence),
+        unit(token_diversity(interactions).1),
+        unit(concentration_ratio(interactions)),
+    ]
+}
+
+/// Fewer interactions than this make a wallet `Archetype::Dormant`
+const ARCHETYPE_MIN_INTERACTIONS: u64 = 3;
+
+/// Buys needed before regular timing can mark a wallet `Archetype::Bot`
+const BOT_MIN_BUYS: usize = 5;
+
+/// `timing_entropy` at or below which a wallet's cadence counts as automated
+const BOT_MAX_TIMING_ENTROPY: f64 = 0.25;
+
+/// Early entry ratio at or above which a wallet is an `Archetype::Sniper`
+const SNIPER_MIN_EARLY_RATIO: f64 = 0.5;
+
+/// Share of gross volume sold at or above which a wallet is an `Archetype::Swinger`
+const SWINGER_MIN_SELL_SHARE: f64 = 0.3;
+
+/// Distinct tokens and `token_diversity` needed for `Archetype::Diversified`
+const DIVERSIFIED_MIN_TOKENS: usize = 3;
+const DIVERSIFIED_MIN_DIVERSITY: f64 = 0.6;
+
+/// Single behavioral label for a wallet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Archetype {
+    /// Buys right at launch
+    Sniper,
+    /// Builds positions in a few tokens without selling much
+    Accumulator,
+    /// Trades in and out, selling a large share of what it buys
+    Swinger,
+    /// Buys on a clock-regular cadence
+    Bot,
+    /// Spreads volume evenly across many tokens
+    Diversified,
+    /// Too little activity to say more
+    Dormant,
+}
+
+/// Label a wallet with its dominant behavior
+///
+/// Rules are checked in order and the first match wins:
+/// 1. `Dormant`: fewer than 3 interactions
+/// 2. `Bot`: at least 5 buys whose gaps have `timing_entropy` of at most
+///    0.25 over `CADENCE_BUCKET_SECONDS` buckets
+/// 3. `Sniper`: at least half of the interactions are early entries
+///    (counted from `stats`)
+/// 4. `Swinger`: sells are at least 30% of gross volume
+/// 5. `Diversified`: at least 3 tokens with `token_diversity` of at least 0.6
+/// 6. `Accumulator`: everything else, i.e. a mostly-buying wallet
+///    concentrated in few tokens
+///
+/// Bot cadence comes first because a script that snipes is still a script.
+pub fn classify_archetype(stats: &WalletStats, interactions: &[TokenInteraction]) -> Archetype {
+    if stats.interaction_count < ARCHETYPE_MIN_INTERACTIONS {
+        return Archetype::Dormant;
+    }
+
+    let buys = interactions.iter().filter(|i| i.side == TradeSide::Buy).count();
+    if buys >= BOT_MIN_BUYS && timing_entropy(interactions, CADENCE_BUCKET_SECONDS) <= BOT_MAX_TIMING_ENTROPY {
+        return Archetype::Bot;
+    }
+
+    let early_ratio = clamped_early_count(stats).0 as f64 / stats.interaction_count as f64;
+    if early_ratio >= SNIPER_MIN_EARLY_RATIO {
+        return Archetype::Sniper;
+    }
+
+    let gross: f64 = interactions.iter().map(|i| i.sol_amount).sum();
+    let sold: f64 = interactions.iter().filter(|i| i.side == TradeSide::Sell).map(|i| i.sol_amount).sum();
+    if gross > 0.0 && sold / gross >= SWINGER_MIN_SELL_SHARE {
+        return Archetype::Swinger;
+    }
+
+    let (tokens, diversity) = token_diversity(interactions);
+    if tokens >= DIVERSIFIED_MIN_TOKENS && diversity >= DIVERSIFIED_MIN_DIVERSITY {
+        return Archetype::Diversified;
+    }
+
+    Archetype::Accumulator
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculate_whale_score, TradeSide};
+
+    fn ladder() -> Vec<TokenInteraction> {
+        [5.0, 10.0, 20.0, 40.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &sol_amount)| TokenInteraction {
+                wallet_address: "addr1".to_string(),
+                token_mint: "token1".to_string(),
+                block_time: 1000 + i as u64 * 10,
+                sol_amount,
+                is_early_entry: true,
+                side: TradeSide::Buy,
+                source: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_full_analysis() {
+        let interactions = ladder();
+        let result = full_analysis(&interactions, &ScoringConfig::default());
+
+        assert_eq!(result.stats.interaction_count, 4);
+        assert_eq!(result.breakdown.total, calculate_whale_score(&result.stats));
+        assert_eq!(result.tier, WhaleTier::from_score(result.breakdown.total));
+        assert_eq!(result.patterns, PatternFlags { ladder: true, accumulating: true, distributing: false });
+
+        let empty = full_analysis(&[], &ScoringConfig::default());
+        assert_eq!(empty.breakdown.total, 0);
+        assert_eq!(empty.tier, WhaleTier::Minnow);
+        assert_eq!(empty.patterns, PatternFlags::default());
+    }
+
+    #[test]
+    fn test_wallet_feature_vector() {
+        let interactions = ladder();
+        let stats = process_interactions(&interactions);
+        let features = wallet_feature_vector(&stats, &interactions);
+
+        assert_eq!(features.len(), FEATURE_VECTOR_LEN);
+        // 75 SOL over 4 buys, all early, all in one token, 10s apart
+        assert!((features[0] - 0.15).abs() < 1e-9);
+        assert!((features[1] - 0.375).abs() < 1e-9);
+        assert_eq!(features[2], 1.0);
+        assert!((features[4] - 10.0 / 3610.0).abs() < 1e-9);
+        assert_eq!(features[5], 0.0);
+        assert_eq!(features[6], 1.0);
+        assert!(features.iter().all(|f| (0.0..=1.0).contains(f)));
+
+        let empty = wallet_feature_vector(&WalletStats::empty("addr1"), &[]);
+        assert_eq!(empty, [0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_classify_archetype() {
+        // (token, seconds after 1000, sol, side, early)
+        let wallet = |trades: &[(&str, u64, f64, TradeSide, bool)]| -> Vec<TokenInteraction> {
+            trades
+                .iter()
+                .map(|&(mint, offset, sol_amount, side, is_early_entry)| TokenInteraction {
+                    wallet_address: "addr1".to_string(),
+                    token_mint: mint.to_string(),
+                    block_time: 1000 + offset,
+                    sol_amount,
+                    is_early_entry,
+                    side,
+                    source: None,
+                })
+                .collect()
+        };
+        let classify = |interactions: &[TokenInteraction]| {
+            classify_archetype(&process_interactions(interactions), interactions)
+        };
+        let (buy, sell) = (TradeSide::Buy, TradeSide::Sell);
+
+        let dormant = wallet(&[("t1", 0, 5.0, buy, true), ("t1", 500, 5.0, buy, false)]);
+        assert_eq!(classify(&dormant), Archetype::Dormant);
+
+        let bot = wallet(&(0..6).map(|i| ("t1", i * 60, 1.0, buy, true)).collect::<Vec<_>>());
+        assert_eq!(classify(&bot), Archetype::Bot);
+
+        let sniper = wallet(&[
+            ("t1", 0, 5.0, buy, true),
+            ("t2", 700, 5.0, buy, true),
+            ("t3", 5000, 2.0, buy, false),
+        ]);
+        assert_eq!(classify(&sniper), Archetype::Sniper);
+
+        let swinger = wallet(&[
+            ("t1", 0, 10.0, buy, false),
+            ("t1", 900, 8.0, sell, false),
+            ("t2", 4000, 10.0, buy, false),
+            ("t2", 9000, 12.0, sell, false),
+        ]);
+        assert_eq!(classify(&swinger), Archetype::Swinger);
+
+        let diversified = wallet(&[
+            ("t1", 0, 5.0, buy, false),
+            ("t2", 300, 5.0, buy, false),
+            ("t3", 2000, 5.0, buy, false),
+            ("t4", 9000, 5.0, buy, false),
+        ]);
+        assert_eq!(classify(&diversified), Archetype::Diversified);
+
+        let accumulator = wallet(&[
+            ("t1", 0, 5.0, buy, false),
+            ("t1", 400, 10.0, buy, false),
+            ("t1", 3000, 20.0, buy, false),
+            ("t2", 8000, 1.0, buy, false),
+        ]);
+        assert_eq!(classify(&accumulator), Archetype::Accumulator);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_full_analysis_serializes_every_section() {
+        let result = full_analysis(&ladder(), &ScoringConfig::default());
+        let json: serde_json::Value = serde_json::to_value(&result).unwrap();
+
+        for section in ["stats", "breakdown", "tier", "patterns"] {
+            assert!(json.get(section).is_some(), "missing {}", section);
+        }
+        assert_eq!(json["stats"]["address"], "addr1");
+        assert_eq!(json["breakdown"]["total"], result.breakdown.total);
+        assert_eq!(json["patterns"]["ladder"], true);
+    }
+}
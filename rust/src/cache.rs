@@ -0,0 +1,186 @@
+//! Memoization of whale scores for unchanged wallets
+//! Bounded LRU cache keyed by a content hash of `WalletStats`
+
+use crate::{calculate_whale_score, WalletStats};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+
+/// 64-bit hash over every `WalletStats` field (floats by their bit pattern)
+pub(crate) fn stats_content_hash(stats: &WalletStats) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    stats.address.hash(&mut hasher);
+    stats.total_volume_sol.to_bits().hash(&mut hasher);
+    stats.interaction_count.hash(&mut hasher);
+    stats.average_entry_size.to_bits().hash(&mut hasher);
+    stats.early_entry_count.hash(&mut hasher);
+    stats.winrate_proxy.to_bits().hash(&mut hasher);
+    stats.avg_realized_multiple.map(f64::to_bits).hash(&mut hasher);
+    stats.net_volume_sol.to_bits().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Field-by-field equality matching `stats_content_hash` (floats by bit pattern)
+fn same_stats(a: &WalletStats, b: &WalletStats) -> bool {
+    a.address == b.address
+        && a.total_volume_sol.to_bits() == b.total_volume_sol.to_bits()
+        && a.interaction_count == b.interaction_count
+        && a.average_entry_size.to_bits() == b.average_entry_size.to_bits()
+        && a.early_entry_count == b.early_entry_count
+        && a.winrate_proxy.to_bits() == b.winrate_proxy.to_bits()
+        && a.avg_realized_multiple.map(f64::to_bits) == b.avg_realized_multiple.map(f64::to_bits)
+        && a.net_volume_sol.to_bits() == b.net_volume_sol.to_bits()
+}
+
+/// LRU cache of `calculate_whale_score` results
+///
+/// Entries are keyed by a 64-bit content hash and keep a copy of the stats
+/// they were computed from, which is compared on every hit: a hash collision
+/// between two different stats counts as a miss (the newer stats replace the
+/// older entry) and never returns another wallet's score.
+#[derive(Debug, Clone)]
+pub struct ScoreCache {
+    capacity: usize,
+    tick: u64,
+    /// content hash -> (stats, score, last-used tick)
+    entries: HashMap<u64, (WalletStats, u8, u64)>,
+    /// last-used tick -> content hash, oldest first
+    recency: BTreeMap<u64, u64>,
+}
+
+impl ScoreCache {
+    /// Create a cache holding at most `capacity` scores (0 disables caching)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+        }
+    }
+
+    /// Return the cached score for identical stats, computing it on a miss
+    pub fn get_or_compute(&mut self, stats: &WalletStats) -> u8 {
+        let key = stats_content_hash(stats);
+        self.tick += 1;
+
+        if let Some((cached, score, last_used)) = self.entries.get_mut(&key) {
+            if same_stats(cached, stats) {
+                self.recency.remove(last_used);
+                *last_used = self.tick;
+                self.recency.insert(self.tick, key);
+                return *score;
+            }
+        }
+
+        let score = calculate_whale_score(stats);
+        if self.capacity == 0 {
+            return score;
+        }
+
+        // A colliding entry is replaced in place; otherwise make room
+        if let Some((_, _, last_used)) = self.entries.remove(&key) {
+            self.recency.remove(&last_used);
+        } else if self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (stats.clone(), score, self.tick));
+        self.recency.insert(self.tick, key);
+        score
+    }
+
+    /// Whether identical stats are currently cached
+    pub fn contains(&self, stats: &WalletStats) -> bool {
+        self.entries
+            .get(&stats_content_hash(stats))
+            .is_some_and(|(cached, _, _)| same_stats(cached, stats))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(address: &str, early: u64) -> WalletStats {
+        WalletStats {
+            address: address.to_string(),
+            total_volume_sol: 100.0,
+            interaction_count: 10,
+            average_entry_size: 10.0,
+            early_entry_count: early,
+            winrate_proxy: 0.5,
+            avg_realized_multiple: None,
+            net_volume_sol: 100.0,
+        }
+    }
+
+    #[test]
+    fn test_cache_hit_and_miss() {
+        let mut cache = ScoreCache::new(8);
+        let original = stats("addr1", 2);
+
+        let score = cache.get_or_compute(&original);
+        assert_eq!(score, calculate_whale_score(&original));
+        assert!(cache.contains(&original.clone()));
+        assert_eq!(cache.get_or_compute(&original.clone()), score);
+        assert_eq!(cache.len(), 1);
+
+        let changed = WalletStats { winrate_proxy: 0.6, ..original.clone() };
+        assert!(!cache.contains(&changed));
+        assert_eq!(cache.get_or_compute(&changed), calculate_whale_score(&changed));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let mut cache = ScoreCache::new(2);
+        let (a, b, c) = (stats("a", 1), stats("b", 2), stats("c", 3));
+
+        cache.get_or_compute(&a);
+        cache.get_or_compute(&b);
+        // Touch `a` so `b` becomes the eviction candidate
+        cache.get_or_compute(&a);
+        cache.get_or_compute(&c);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains(&a));
+        assert!(!cache.contains(&b));
+        assert!(cache.contains(&c));
+
+        let mut disabled = ScoreCache::new(0);
+        assert_eq!(disabled.get_or_compute(&a), calculate_whale_score(&a));
+        assert!(disabled.is_empty());
+    }
+
+    #[test]
+    fn test_cache_hash_collision_is_a_miss() {
+        let mut cache = ScoreCache::new(8);
+        let (whale, minnow) = (stats("whale", 9), stats("minnow", 0));
+        cache.get_or_compute(&whale);
+
+        // Plant `whale`'s entry under `minnow`'s hash to simulate a collision
+        let (_, score, tick) = cache.entries.remove(&stats_content_hash(&whale)).unwrap();
+        cache.entries.insert(stats_content_hash(&minnow), (whale.clone(), score, tick));
+        cache.recency.insert(tick, stats_content_hash(&minnow));
+
+        assert!(!cache.contains(&minnow));
+        assert_eq!(cache.get_or_compute(&minnow), calculate_whale_score(&minnow));
+        assert_ne!(calculate_whale_score(&minnow), calculate_whale_score(&whale));
+        assert!(cache.contains(&minnow));
+        assert_eq!(cache.len(), 1);
+    }
+}
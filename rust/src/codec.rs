@@ -0,0 +1,69 @@
+//! Compact binary encoding of computed stats
+//! Lets large stats maps be cached and reloaded without recomputation
+
+use crate::WalletStats;
+use std::collections::HashMap;
+
+/// Encode a stats map with bincode
+pub fn serialize_stats_map(stats_map: &HashMap<String, WalletStats>) -> Vec<u8> {
+    // Strings, numbers and options always encode; only I/O writers can fail
+    bincode::serialize(stats_map).expect("stats map encoding is infallible")
+}
+
+/// Decode a stats map produced by `serialize_stats_map`
+pub fn deserialize_stats_map(bytes: &[u8]) -> Result<HashMap<String, WalletStats>, bincode::Error> {
+    bincode::deserialize(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculate_batch_stats, process_interactions, TokenInteraction, TradeSide};
+
+    #[test]
+    fn test_stats_map_round_trip() {
+        let interactions = vec![
+            TokenInteraction {
+                wallet_address: "addr1".to_string(),
+                token_mint: "token1".to_string(),
+                block_time: 1000,
+                sol_amount: 10.5,
+                is_early_entry: true,
+                side: TradeSide::Buy,
+                source: None,
+            },
+            TokenInteraction {
+                wallet_address: "addr2".to_string(),
+                token_mint: "token2".to_string(),
+                block_time: 2000,
+                sol_amount: 0.25,
+                is_early_entry: false,
+                side: TradeSide::Buy,
+                source: None,
+            },
+        ];
+        let mut stats_map = calculate_batch_stats(&interactions);
+        stats_map.insert("empty".to_string(), process_interactions(&[]));
+        stats_map.get_mut("addr1").unwrap().avg_realized_multiple = Some(3.0);
+
+        let bytes = serialize_stats_map(&stats_map);
+        let decoded = deserialize_stats_map(&bytes).unwrap();
+
+        assert_eq!(decoded.len(), 3);
+        for (address, stats) in &stats_map {
+            let restored = &decoded[address];
+            assert_eq!(restored.address, stats.address);
+            assert_eq!(restored.total_volume_sol, stats.total_volume_sol);
+            assert_eq!(restored.interaction_count, stats.interaction_count);
+            assert_eq!(restored.average_entry_size, stats.average_entry_size);
+            assert_eq!(restored.early_entry_count, stats.early_entry_count);
+            assert_eq!(restored.winrate_proxy, stats.winrate_proxy);
+            assert_eq!(restored.avg_realized_multiple, stats.avg_realized_multiple);
+        }
+    }
+
+    #[test]
+    fn test_deserialize_rejects_garbage() {
+        assert!(deserialize_stats_map(&[0xff, 0xff, 0xff]).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+//! Pluggable whale score components
+//! Weighted composition of built-in and user-defined scoring signals
+
+use crate::scoring::COMPONENT_CEILINGS;
+use crate::{whale_score_components, WalletStats};
+
+/// One signal contributing to a composite score
+///
+/// `score` returns the fraction (0-1) of the component's weight the wallet
+/// earns; values outside that range are clamped by `CompositeScorer`.
+pub trait ScoreComponent {
+    fn name(&self) -> &str;
+    fn score(&self, stats: &WalletStats) -> f64;
+}
+
+/// Fraction of a built-in component's ceiling earned by `stats`
+fn builtin_fraction(stats: &WalletStats, index: usize) -> f64 {
+    if stats.interaction_count == 0 {
+        return 0.0;
+    }
+    whale_score_components(stats)[index] / COMPONENT_CEILINGS[index]
+}
+
+/// Early entry ratio and count (40 points in the standard scorer)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EarlyEntryComponent;
+
+/// Average entry size and total volume (30 points in the standard scorer)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BuySizeComponent;
+
+/// Number of interactions (20 points in the standard scorer)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RepetitionComponent;
+
+/// Realized multiple or winrate proxy (10 points in the standard scorer)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProfitComponent;
+
+impl ScoreComponent for EarlyEntryComponent {
+    fn name(&self) -> &str {
+        "early_entry"
+    }
+
+    fn score(&self, stats: &WalletStats) -> f64 {
+        builtin_fraction(stats, 0)
+    }
+}
+
+impl ScoreComponent for BuySizeComponent {
+    fn name(&self) -> &str {
+        "buy_size"
+    }
+
+    fn score(&self, stats: &WalletStats) -> f64 {
+        builtin_fraction(stats, 1)
+    }
+}
+
+impl ScoreComponent for RepetitionComponent {
+    fn name(&self) -> &str {
+        "repetition"
+    }
+
+    fn score(&self, stats: &WalletStats) -> f64 {
+        builtin_fraction(stats, 2)
+    }
+}
+
+impl ScoreComponent for ProfitComponent {
+    fn name(&self) -> &str {
+        "profit"
+    }
+
+    fn score(&self, stats: &WalletStats) -> f64 {
+        builtin_fraction(stats, 3)
+    }
+}
+
+/// Weighted sum of score components, capped at 100
+///
+/// `CompositeScorer::default()` holds the four built-in components with
+/// weights 40/30/20/10, reproducing `calculate_whale_score`.
+pub struct CompositeScorer {
+    components: Vec<(Box<dyn ScoreComponent>, f64)>,
+}
+
+impl Default for CompositeScorer {
+    fn default() -> Self {
+        Self::empty()
+            .with_component(EarlyEntryComponent, 40.0)
+            .with_component(BuySizeComponent, 30.0)
+            .with_component(RepetitionComponent, 20.0)
+            .with_component(ProfitComponent, 10.0)
+    }
+}
+
+impl CompositeScorer {
+    /// A scorer with no components (always scores 0)
+    pub fn empty() -> Self {
+        Self { components: Vec::new() }
+    }
+
+    /// Add a component worth up to `weight` points (negative weights count as 0)
+    pub fn with_component(mut self, component: impl ScoreComponent + 'static, weight: f64) -> Self {
+        self.add_component(Box::new(component), weight);
+        self
+    }
+
+    /// Add a boxed component worth up to `weight` points
+    pub fn add_component(&mut self, component: Box<dyn ScoreComponent>, weight: f64) {
+        self.components.push((component, weight.max(0.0)));
+    }
+
+    /// Component names and weights, in registration order
+    pub fn components(&self) -> Vec<(&str, f64)> {
+        self.components.iter().map(|(c, w)| (c.name(), *w)).collect()
+    }
+
+    /// Points each component contributes for `stats`, in registration order
+    pub fn contributions(&self, stats: &WalletStats) -> Vec<(&str, f64)> {
+        self.components
+            .iter()
+            .map(|(c, w)| {
+                let fraction = c.score(stats);
+                let fraction = if fraction.is_nan() { 0.0 } else { fraction.clamp(0.0, 1.0) };
+                (c.name(), fraction * w)
+            })
+            .collect()
+    }
+
+    /// Sum of all contributions, capped at 100
+    pub fn score(&self, stats: &WalletStats) -> u8 {
+        let total: f64 = self.contributions(stats).iter().map(|(_, points)| points).sum();
+        total.min(100.0) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculate_whale_score;
+
+    fn stats(interaction_count: u64, total_volume_sol: f64) -> WalletStats {
+        WalletStats {
+            address: "addr1".to_string(),
+            total_volume_sol,
+            interaction_count,
+            average_entry_size: total_volume_sol / interaction_count.max(1) as f64,
+            early_entry_count: interaction_count / 2,
+            winrate_proxy: 0.6,
+            avg_realized_multiple: None,
+            net_volume_sol: total_volume_sol,
+        }
+    }
+
+    struct VolumeAbove(f64);
+
+    impl ScoreComponent for VolumeAbove {
+        fn name(&self) -> &str {
+            "volume_above"
+        }
+
+        fn score(&self, stats: &WalletStats) -> f64 {
+            if stats.total_volume_sol >= self.0 { 1.0 } else { 0.0 }
+        }
+    }
+
+    struct Activity;
+
+    impl ScoreComponent for Activity {
+        fn name(&self) -> &str {
+            "activity"
+        }
+
+        fn score(&self, stats: &WalletStats) -> f64 {
+            // Deliberately unbounded; the scorer clamps it
+            stats.interaction_count as f64 / 10.0
+        }
+    }
+
+    #[test]
+    fn test_default_composite_matches_whale_score() {
+        let scorer = CompositeScorer::default();
+        for s in [stats(0, 0.0), stats(4, 12.0), stats(20, 300.0), stats(80, 4000.0)] {
+            assert_eq!(scorer.score(&s), calculate_whale_score(&s));
+        }
+        let names: Vec<&str> = scorer.components().iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, ["early_entry", "buy_size", "repetition", "profit"]);
+    }
+
+    #[test]
+    fn test_custom_components() {
+        let scorer = CompositeScorer::empty()
+            .with_component(VolumeAbove(100.0), 70.0)
+            .with_component(Activity, 50.0);
+
+        assert_eq!(scorer.score(&stats(5, 50.0)), 25);
+        assert_eq!(scorer.score(&stats(5, 150.0)), 95);
+        // Activity clamps to its full 50 points; the total caps at 100
+        assert_eq!(scorer.score(&stats(40, 150.0)), 100);
+        assert_eq!(
+            scorer.contributions(&stats(5, 150.0)),
+            vec![("volume_above", 70.0), ("activity", 25.0)]
+        );
+    }
+}
@@ -0,0 +1,765 @@
+//! Relationships between wallets
+//! Signals that two or more wallets may be acting together
+
+use crate::scoring::canonicalize_clusters;
+use crate::timing::saturating_elapsed;
+use crate::{TokenInteraction, TradeSide};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Distinct token mints a wallet interacted with
+pub(crate) fn token_set(interactions: &[TokenInteraction]) -> HashSet<&str> {
+    interactions.iter().map(|i| i.token_mint.as_str()).collect()
+}
+
+/// Jaccard index of the two wallets' `token_mint` sets
+///
+/// 1.0 means both wallets traded exactly the same tokens, 0.0 means no token
+/// in common. Returns 0.0 when both sets are empty.
+pub fn token_overlap(a: &[TokenInteraction], b: &[TokenInteraction]) -> f64 {
+    let tokens_a = token_set(a);
+    let tokens_b = token_set(b);
+
+    let union = tokens_a.union(&tokens_b).count();
+    if union == 0 {
+        return 0.0;
+    }
+
+    tokens_a.intersection(&tokens_b).count() as f64 / union as f64
+}
+
+/// Seeded 64-bit hash of a token mint with a fixed, specified algorithm
+///
+/// FNV-1a over the seed's little-endian bytes and the mint's UTF-8 bytes,
+/// followed by the SplitMix64 finalizer so nearby seeds give unrelated
+/// hashes. Unlike `DefaultHasher`, the output never changes between Rust
+/// releases, which keeps MinHash signatures reproducible.
+fn seeded_token_hash(seed: u64, token: &str) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = FNV_OFFSET;
+    for &byte in seed.to_le_bytes().iter().chain(token.as_bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
+/// MinHash signature of a token set: the minimum of `len` seeded hashes
+fn minhash_signature(tokens: &HashSet<&str>, len: usize) -> Vec<u64> {
+    (0..len as u64)
+        .map(|seed| {
+            tokens
+                .iter()
+                .map(|token| seeded_token_hash(seed, token))
+                .min()
+                .unwrap_or(u64::MAX)
+        })
+        .collect()
+}
+
+/// Union-find root of `i`, compressing the path on the way
+pub(crate) fn find_root(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+/// Approximate groups of wallets with overlapping token sets, without
+/// comparing every pair
+///
+/// Each wallet's `token_mint` set gets a MinHash signature of
+/// `bands * rows_per_band` values, and wallets whose signatures agree on
+/// every row of any band become candidates; candidates are merged
+/// transitively into groups. Two wallets with Jaccard overlap `s` share a
+/// band with probability `1 - (1 - s^rows_per_band)^bands`, so more rows make
+/// the filter stricter and more bands make it more forgiving. This is
+/// O(wallets * tokens * signature length) instead of the O(wallets²) of
+/// calling `token_overlap` on every pair; confirm candidates with
+/// `token_overlap` if false positives matter. Only groups of two or more
+/// wallets are returned, in canonical order (sorted members, groups sorted
+/// by first member). Signatures use a fixed in-crate hash (not
+/// `DefaultHasher`), so results are reproducible across runs and Rust
+/// versions.
+pub fn minhash_candidate_groups(
+    interactions: &[TokenInteraction],
+    bands: usize,
+    rows_per_band: usize,
+) -> Vec<Vec<String>> {
+    let mut tokens_by_wallet: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for interaction in interactions {
+        tokens_by_wallet
+            .entry(interaction.wallet_address.as_str())
+            .or_default()
+            .insert(interaction.token_mint.as_str());
+    }
+    let mut wallets: Vec<&str> = tokens_by_wallet.keys().copied().collect();
+    wallets.sort_unstable();
+    if bands == 0 || rows_per_band == 0 {
+        return Vec::new();
+    }
+
+    let mut parents: Vec<usize> = (0..wallets.len()).collect();
+    let mut buckets: HashMap<(usize, &[u64]), usize> = HashMap::new();
+    let signatures: Vec<Vec<u64>> = wallets
+        .iter()
+        .map(|wallet| minhash_signature(&tokens_by_wallet[wallet], bands * rows_per_band))
+        .collect();
+
+    for (i, signature) in signatures.iter().enumerate() {
+        for (band, rows) in signature.chunks(rows_per_band).enumerate() {
+            match buckets.get(&(band, rows)) {
+                Some(&first) => {
+                    let (a, b) = (find_root(&mut parents, first), find_root(&mut parents, i));
+                    parents[b] = a;
+                }
+                None => {
+                    buckets.insert((band, rows), i);
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<String>> = HashMap::new();
+    for (i, wallet) in wallets.iter().enumerate() {
+        let root = find_root(&mut parents, i);
+        groups.entry(root).or_default().push(wallet.to_string());
+    }
+    let mut groups: Vec<Vec<String>> = groups.into_values().filter(|group| group.len() > 1).collect();
+    canonicalize_clusters(&mut groups);
+    groups
+}
+
+/// Fraction of `follower`'s buys that trail a `leader` buy of the same token
+///
+/// A follower buy counts when the leader bought the same token at most
+/// `max_lag_seconds` earlier (the same block included). Unlike
+/// `timing_correlation` this is directed: a high value flags the follower as
+/// a likely copy-trader of the leader, not the other way around. Sells are
+/// ignored; returns 0.0 when the follower has no buys.
+pub fn detect_copy_trading(leader: &[TokenInteraction], follower: &[TokenInteraction], max_lag_seconds: u64) -> f64 {
+    let mut leader_buys: HashMap<&str, Vec<u64>> = HashMap::new();
+    for interaction in leader.iter().filter(|i| i.side == TradeSide::Buy) {
+        leader_buys.entry(interaction.token_mint.as_str()).or_default().push(interaction.block_time);
+    }
+
+    let follower_buys: Vec<&TokenInteraction> = follower.iter().filter(|i| i.side == TradeSide::Buy).collect();
+    if follower_buys.is_empty() {
+        return 0.0;
+    }
+
+    let copied = follower_buys
+        .iter()
+        .filter(|buy| {
+            leader_buys.get(buy.token_mint.as_str()).is_some_and(|times| {
+                times
+                    .iter()
+                    .any(|&t| t <= buy.block_time && saturating_elapsed(buy.block_time, t) <= max_lag_seconds)
+            })
+        })
+        .count();
+    copied as f64 / follower_buys.len() as f64
+}
+
+/// Wallets in a cohort that copy-trade `leader`
+///
+/// Runs `detect_copy_trading` from the leader's interactions against every
+/// other wallet in `all_wallets` and keeps those whose match ratio is at
+/// least `min_match_ratio`. The leader itself is never reported as its own
+/// follower. Returns sorted addresses; empty when `leader` isn't in the
+/// map.
+pub fn estimate_followers(
+    leader: &str,
+    all_wallets: &HashMap<String, Vec<TokenInteraction>>,
+    max_lag: u64,
+    min_match_ratio: f64,
+) -> Vec<String> {
+    let leader_interactions = match all_wallets.get(leader) {
+        Some(interactions) => interactions,
+        None => return Vec::new(),
+    };
+
+    let mut followers: Vec<String> = all_wallets
+        .iter()
+        .filter(|(address, _)| address.as_str() != leader)
+        .filter(|(_, interactions)| detect_copy_trading(leader_interactions, interactions, max_lag) >= min_match_ratio)
+        .map(|(address, _)| address.clone())
+        .collect();
+    followers.sort();
+    followers
+}
+
+/// How much of a token's buying came from one cluster
+///
+/// Returns the number of `cluster` members that bought the token and their
+/// combined share (0-1) of its total buy volume, linking a cluster to a
+/// specific pump. Sells are ignored; the share is 0.0 when the token has no
+/// buy volume.
+pub fn cluster_token_participation(cluster: &[String], interactions_for_token: &[TokenInteraction]) -> (usize, f64) {
+    let members: HashSet<&str> = cluster.iter().map(String::as_str).collect();
+    let mut buyers: HashSet<&str> = HashSet::new();
+    let mut cluster_volume = 0.0;
+    let mut total_volume = 0.0;
+    for interaction in interactions_for_token.iter().filter(|i| i.side == TradeSide::Buy) {
+        total_volume += interaction.sol_amount;
+        if members.contains(interaction.wallet_address.as_str()) {
+            buyers.insert(interaction.wallet_address.as_str());
+            cluster_volume += interaction.sol_amount;
+        }
+    }
+
+    let share = if total_volume > 0.0 { cluster_volume / total_volume } else { 0.0 };
+    (buyers.len(), share)
+}
+
+/// Pearson correlation of two wallets' activity over time
+///
+/// Both wallets' interactions are counted in shared buckets of
+/// `bucket_seconds` (at least 1) spanning their combined time range. 1.0 means
+/// the wallets were busy in exactly the same buckets, -1.0 that one was active
+/// whenever the other was idle. Returns 0.0 if either series has zero variance,
+/// including when either wallet has no interactions.
+///
+/// Only occupied buckets are stored; the empty ones enter the sums
+/// analytically, so a span of years at one-second buckets costs no more
+/// memory than the interactions themselves.
+pub fn timing_correlation(a: &[TokenInteraction], b: &[TokenInteraction], bucket_seconds: u64) -> f64 {
+    let times = a.iter().chain(b).map(|i| i.block_time);
+    let (start, end) = match (times.clone().min(), times.max()) {
+        (Some(start), Some(end)) => (start, end),
+        _ => return 0.0,
+    };
+
+    let bucket_seconds = bucket_seconds.max(1);
+    let buckets = (saturating_elapsed(end, start) / bucket_seconds) as f64 + 1.0;
+    // bucket index -> (count in a, count in b)
+    let mut counts: HashMap<u64, (f64, f64)> = HashMap::new();
+    for interaction in a {
+        counts.entry(saturating_elapsed(interaction.block_time, start) / bucket_seconds).or_default().0 += 1.0;
+    }
+    for interaction in b {
+        counts.entry(saturating_elapsed(interaction.block_time, start) / bucket_seconds).or_default().1 += 1.0;
+    }
+
+    // Buckets absent from `counts` are (0, 0) and add nothing to these sums
+    let (mut sum_x, mut sum_y, mut sum_xx, mut sum_yy, mut sum_xy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for &(x, y) in counts.values() {
+        sum_x += x;
+        sum_y += y;
+        sum_xx += x * x;
+        sum_yy += y * y;
+        sum_xy += x * y;
+    }
+    let covariance = sum_xy - sum_x * sum_y / buckets;
+    let variance_x = sum_xx - sum_x * sum_x / buckets;
+    let variance_y = sum_yy - sum_y * sum_y / buckets;
+
+    // Relative epsilon: the subtractions above leave rounding noise, not variance
+    if variance_x <= 1e-12 * sum_xx || variance_y <= 1e-12 * sum_yy {
+        return 0.0;
+    }
+    (covariance / (variance_x * variance_y).sqrt()).clamp(-1.0, 1.0)
+}
+
+/// Pearson correlation of two equal-length series (0.0 if either has zero variance)
+pub(crate) fn pearson(xs: &[f64], ys: &[f64]) -> f64 {
+    let n = xs.len().min(ys.len());
+    if n == 0 {
+        return 0.0;
+    }
+
+    let mean_x = xs[..n].iter().sum::<f64>() / n as f64;
+    let mean_y = ys[..n].iter().sum::<f64>() / n as f64;
+    let mut covariance = 0.0;
+    let mut variance_x = 0.0;
+    let mut variance_y = 0.0;
+    for (x, y) in xs.iter().zip(ys) {
+        covariance += (x - mean_x) * (y - mean_y);
+        variance_x += (x - mean_x).powi(2);
+        variance_y += (y - mean_y).powi(2);
+    }
+
+    if variance_x == 0.0 || variance_y == 0.0 {
+        return 0.0;
+    }
+    covariance / (variance_x * variance_y).sqrt()
+}
+
+/// A burst of several wallets trading the same token on the same side
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoordinatedEvent {
+    pub token_mint: String,
+    pub side: TradeSide,
+    /// `block_time` of the first trade in the burst
+    pub start_time: u64,
+    /// `block_time` of the last trade in the burst
+    pub end_time: u64,
+    /// Distinct participating wallets, sorted
+    pub wallets: Vec<String>,
+    pub total_sol: f64,
+}
+
+/// Bursts where at least `min_wallets` distinct wallets sell one token within
+/// `block_window` seconds
+///
+/// A dump by many wallets at once is an early warning for a rug. Per token,
+/// sells are scanned in time order: each burst starts at the earliest sell not
+/// yet covered and takes every sell up to `block_window` seconds after it, so
+/// bursts never overlap. Buys are ignored. Events are ordered by start time,
+/// then token mint.
+pub fn detect_coordinated_exits(
+    interactions: &[TokenInteraction],
+    block_window: u64,
+    min_wallets: usize,
+) -> Vec<CoordinatedEvent> {
+    detect_coordinated(interactions, TradeSide::Sell, block_window, min_wallets)
+}
+
+/// How often each pair of wallets bought the same token close together
+///
+/// Every pair of buys of the same token by two different wallets at most
+/// `block_window` seconds apart adds one to that wallet pair's count, so the
+/// counts are edge weights for a coordination graph. Wallets are identified
+/// by their key in `wallets`, and each pair appears once, keyed in sorted
+/// order `(smaller, larger)`. Pairs that never co-occur are absent.
+pub fn co_occurrence_matrix(
+    wallets: &HashMap<String, Vec<TokenInteraction>>,
+    block_window: u64,
+) -> HashMap<(String, String), usize> {
+    co_occurrence_counts(wallets.iter().map(|(wallet, interactions)| (wallet.as_str(), interactions.as_slice())), block_window)
+        .into_iter()
+        .map(|((a, b), count)| ((a.to_string(), b.to_string()), count))
+        .collect()
+}
+
+/// `co_occurrence_matrix` over borrowed `(wallet, interactions)` pairs
+fn co_occurrence_counts<'a>(
+    wallets: impl IntoIterator<Item = (&'a str, &'a [TokenInteraction])>,
+    block_window: u64,
+) -> HashMap<(&'a str, &'a str), usize> {
+    let mut by_token: HashMap<&str, Vec<(u64, &str)>> = HashMap::new();
+    for (wallet, interactions) in wallets {
+        for interaction in interactions.iter().filter(|i| i.side == TradeSide::Buy) {
+            by_token.entry(interaction.token_mint.as_str()).or_default().push((interaction.block_time, wallet));
+        }
+    }
+
+    let mut counts: HashMap<(&str, &str), usize> = HashMap::new();
+    for buys in by_token.values_mut() {
+        buys.sort_unstable();
+        for (i, &(time, wallet)) in buys.iter().enumerate() {
+            for &(_, other) in buys[i + 1..].iter().take_while(|(t, _)| saturating_elapsed(*t, time) <= block_window) {
+                if other != wallet {
+                    let pair = if wallet < other { (wallet, other) } else { (other, wallet) };
+                    *counts.entry(pair).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+    counts
+}
+
+/// Co-occurrences at which a wallet pair counts as half coupled in `effective_independence`
+pub const INDEPENDENCE_HALF_COUNT: f64 = 5.0;
+
+/// Number of genuinely independent actors among a cluster's members
+///
+/// Each pair of distinct members is coupled by `t = c / (c + h)`, where `c`
+/// is the pair's count in `co_occurrence` (as built by
+/// `co_occurrence_matrix`, keyed by the sorted pair) and `h` is
+/// `INDEPENDENCE_HALF_COUNT`. With `n` members the effective count is
+///
+/// `n^2 / (n + sum of t over ordered pairs)`
+///
+/// a participation ratio: `n` when no pair co-occurs, approaching 1 as
+/// every pair co-occurs constantly (ten sybils run by one actor count as
+/// about one). Duplicate addresses count once; an empty cluster is 0.0.
+pub fn effective_independence(cluster: &[String], co_occurrence: &HashMap<(String, String), usize>) -> f64 {
+    let members: BTreeSet<&str> = cluster.iter().map(String::as_str).collect();
+    let members: Vec<&str> = members.into_iter().collect();
+    let n = members.len() as f64;
+    if members.is_empty() {
+        return 0.0;
+    }
+
+    let mut coupling = 0.0;
+    for (i, a) in members.iter().enumerate() {
+        for b in &members[i + 1..] {
+            // `members` is sorted, so (a, b) is already the normalized key
+            let count = co_occurrence.get(&(a.to_string(), b.to_string())).copied().unwrap_or(0) as f64;
+            coupling += 2.0 * count / (count + INDEPENDENCE_HALF_COUNT);
+        }
+    }
+    n * n / (n + coupling)
+}
+
+/// Points each signal contributes to `coordination_likelihood`:
+/// co-occurrence, timing correlation, token overlap
+const COORDINATION_WEIGHTS: [f64; 3] = [50.0, 30.0, 20.0];
+
+/// 0-100 likelihood that two wallets are run by the same coordinated group
+///
+/// Combines three signals, each scaled to 0-1:
+///
+/// - co-occurrence (50 points): the pair's `co_occurrence_matrix` count at
+///   `block_window`, saturated as `c / (c + INDEPENDENCE_HALF_COUNT)` like in
+///   `effective_independence` (5 same-token buys close together earn half)
+/// - timing correlation (30 points): `timing_correlation` at
+///   `bucket_seconds`, with negative correlation counted as 0
+/// - token overlap (20 points): `token_overlap`
+///
+/// Co-occurrence weighs most because same-token buys seconds apart are the
+/// hardest to produce by chance; overlap alone is common among wallets that
+/// simply trade the same trending tokens.
+pub fn coordination_likelihood(a: &[TokenInteraction], b: &[TokenInteraction], block_window: u64, bucket_seconds: u64) -> u8 {
+    let count = co_occurrence_counts([("a", a), ("b", b)], block_window).get(&("a", "b")).copied().unwrap_or(0) as f64;
+    let co_occurrence = count / (count + INDEPENDENCE_HALF_COUNT);
+    let signals = [co_occurrence, timing_correlation(a, b, bucket_seconds).max(0.0), token_overlap(a, b)];
+    let points: f64 = signals.iter().zip(COORDINATION_WEIGHTS).map(|(signal, weight)| signal * weight).sum();
+    points.round().min(100.0) as u8
+}
+
+fn detect_coordinated(
+    interactions: &[TokenInteraction],
+    side: TradeSide,
+    block_window: u64,
+    min_wallets: usize,
+) -> Vec<CoordinatedEvent> {
+    let mut by_token: HashMap<&str, Vec<&TokenInteraction>> = HashMap::new();
+    for interaction in interactions.iter().filter(|i| i.side == side) {
+        by_token.entry(interaction.token_mint.as_str()).or_default().push(interaction);
+    }
+
+    let mut events = Vec::new();
+    for (mint, mut trades) in by_token {
+        trades.sort_by_key(|i| i.block_time);
+
+        let mut start = 0;
+        while start < trades.len() {
+            let window_end = trades[start].block_time.saturating_add(block_window);
+            let end = start + trades[start..].iter().take_while(|i| i.block_time <= window_end).count();
+            let burst = &trades[start..end];
+            let wallets: BTreeSet<&str> = burst.iter().map(|i| i.wallet_address.as_str()).collect();
+
+            if wallets.len() >= min_wallets.max(1) {
+                events.push(CoordinatedEvent {
+                    token_mint: mint.to_string(),
+                    side,
+                    start_time: burst[0].block_time,
+                    end_time: burst[burst.len() - 1].block_time,
+                    wallets: wallets.into_iter().map(str::to_string).collect(),
+                    total_sol: burst.iter().map(|i| i.sol_amount).sum(),
+                });
+                start = end;
+            } else {
+                start += 1;
+            }
+        }
+    }
+
+    events.sort_by(|a, b| a.start_time.cmp(&b.start_time).then_with(|| a.token_mint.cmp(&b.token_mint)));
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interactions(wallet: &str, mints: &[&str]) -> Vec<TokenInteraction> {
+        mints
+            .iter()
+            .map(|mint| TokenInteraction {
+                wallet_address: wallet.to_string(),
+                token_mint: mint.to_string(),
+                block_time: 1000,
+                sol_amount: 1.0,
+                is_early_entry: false,
+                side: TradeSide::Buy,
+                source: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_token_overlap() {
+        let a = interactions("a", &["t1", "t2", "t2"]);
+        let same = interactions("b", &["t2", "t1"]);
+        let disjoint = interactions("c", &["t3", "t4"]);
+        let partial = interactions("d", &["t2", "t3"]);
+
+        assert!((token_overlap(&a, &same) - 1.0).abs() < 1e-9);
+        assert_eq!(token_overlap(&a, &disjoint), 0.0);
+        // {t1, t2} vs {t2, t3}: 1 shared of 3 total
+        assert!((token_overlap(&a, &partial) - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    fn at_times(wallet: &str, times: &[u64]) -> Vec<TokenInteraction> {
+        times
+            .iter()
+            .map(|&block_time| TokenInteraction {
+                block_time,
+                ..interactions(wallet, &["t1"]).remove(0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_timing_correlation() {
+        // 60s buckets starting at t=1000
+        let a = at_times("a", &[1000, 1010, 1130, 1140]);
+        let b = at_times("b", &[1005, 1125, 1135, 1150]);
+        let alternating = at_times("c", &[1000, 1130]);
+        let opposite = at_times("d", &[1070, 1200]);
+
+        assert!(timing_correlation(&a, &b, 60) > 0.7);
+        assert!((timing_correlation(&a, &a, 60) - 1.0).abs() < 1e-9);
+        assert!((timing_correlation(&alternating, &opposite, 60) + 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_timing_correlation_zero_variance() {
+        let a = at_times("a", &[1000, 1010]);
+
+        assert_eq!(timing_correlation(&a, &[], 60), 0.0);
+        assert_eq!(timing_correlation(&[], &[], 60), 0.0);
+        // Everything lands in one bucket: no variance on either side
+        assert_eq!(timing_correlation(&a, &at_times("b", &[1005]), 60), 0.0);
+    }
+
+    #[test]
+    fn test_timing_correlation_wide_span() {
+        // ~1.7e9 one-second buckets; dense counting would need gigabytes
+        let a = at_times("a", &[0, 1_700_000_000]);
+        let b = at_times("b", &[1, 1_700_000_000]);
+
+        let correlation = timing_correlation(&a, &b, 1);
+        assert!(correlation > 0.45 && correlation < 0.55);
+        assert!((timing_correlation(&a, &a, 1) - 1.0).abs() < 1e-9);
+        assert!(coordination_likelihood(&a, &b, 30, 1) > 0);
+    }
+
+    #[test]
+    fn test_minhash_candidate_groups() {
+        let mints: Vec<String> = (0..20).map(|i| format!("t{}", i)).collect();
+        let mints: Vec<&str> = mints.iter().map(String::as_str).collect();
+        let mut feed = interactions("a", &mints);
+        // b trades everything a does plus one more; c and d share nothing with them
+        feed.extend(interactions("b", &mints));
+        feed.extend(interactions("b", &["t99"]));
+        feed.extend(interactions("c", &["x1", "x2", "x3"]));
+        feed.extend(interactions("d", &["y1", "y2"]));
+
+        let groups = minhash_candidate_groups(&feed, 16, 4);
+        assert_eq!(groups, vec![vec!["a".to_string(), "b".to_string()]]);
+        assert!(minhash_candidate_groups(&feed, 0, 4).is_empty());
+        assert!(minhash_candidate_groups(&[], 16, 4).is_empty());
+    }
+
+    #[test]
+    fn test_seeded_token_hash_is_pinned() {
+        // Golden values: a change here silently reshuffles every MinHash group
+        assert_eq!(seeded_token_hash(0, ""), 0x813f_0174_a236_7c13);
+        assert_eq!(seeded_token_hash(3, "t1"), 0xe9b8_1a0f_7f8f_7d71);
+        assert_ne!(seeded_token_hash(0, "t1"), seeded_token_hash(1, "t1"));
+    }
+
+    #[test]
+    fn test_detect_copy_trading() {
+        let buy = |wallet: &str, mint: &str, block_time: u64| TokenInteraction {
+            block_time,
+            ..interactions(wallet, &[mint]).remove(0)
+        };
+        let leader = vec![buy("lead", "t1", 1000), buy("lead", "t2", 2000), buy("lead", "t3", 3000), buy("lead", "t4", 4000)];
+        let follower = vec![buy("copy", "t1", 1003), buy("copy", "t2", 2010), buy("copy", "t3", 3000), buy("copy", "t4", 4500)];
+        let independent = vec![buy("solo", "t1", 900), buy("solo", "t5", 2005), buy("solo", "t3", 7000)];
+
+        assert!((detect_copy_trading(&leader, &follower, 30) - 0.75).abs() < 1e-9);
+        // Reversed, only the same-block t3 buy counts
+        assert!((detect_copy_trading(&follower, &leader, 30) - 0.25).abs() < 1e-9);
+        assert_eq!(detect_copy_trading(&leader, &independent, 30), 0.0);
+        assert_eq!(detect_copy_trading(&leader, &[], 30), 0.0);
+    }
+
+    #[test]
+    fn test_estimate_followers() {
+        let buy = |wallet: &str, mint: &str, block_time: u64| TokenInteraction {
+            block_time,
+            ..interactions(wallet, &[mint]).remove(0)
+        };
+        let all_wallets = crate::group_by_wallet(&[
+            buy("lead", "t1", 1000),
+            buy("lead", "t2", 2000),
+            buy("lead", "t3", 3000),
+            buy("copy", "t1", 1005),
+            buy("copy", "t2", 2020),
+            buy("copy", "t3", 3010),
+            buy("solo", "t1", 800),
+            buy("solo", "t9", 2000),
+        ]);
+
+        assert_eq!(estimate_followers("lead", &all_wallets, 30, 0.5), vec!["copy".to_string()]);
+        // At a ratio of 0 everyone but the leader qualifies
+        assert_eq!(estimate_followers("lead", &all_wallets, 30, 0.0), vec!["copy".to_string(), "solo".to_string()]);
+        assert!(estimate_followers("missing", &all_wallets, 30, 0.5).is_empty());
+    }
+
+    #[test]
+    fn test_co_occurrence_matrix() {
+        let buy = |wallet: &str, mint: &str, block_time: u64| TokenInteraction {
+            block_time,
+            ..interactions(wallet, &[mint]).remove(0)
+        };
+        let wallets = crate::group_by_wallet(&[
+            buy("a", "t1", 1000),
+            buy("b", "t1", 1003),
+            buy("c", "t1", 1020),
+            buy("a", "t2", 2000),
+            buy("b", "t2", 2005),
+            buy("c", "t3", 2000),
+            TokenInteraction { side: TradeSide::Sell, ..buy("c", "t2", 2001) },
+        ]);
+
+        let matrix = co_occurrence_matrix(&wallets, 5);
+        assert_eq!(matrix.len(), 1);
+        assert_eq!(matrix[&("a".to_string(), "b".to_string())], 2);
+
+        // Widening the window pulls c's t1 buy in with both a and b
+        let wide = co_occurrence_matrix(&wallets, 30);
+        assert_eq!(wide[&("a".to_string(), "b".to_string())], 2);
+        assert_eq!(wide[&("a".to_string(), "c".to_string())], 1);
+        assert_eq!(wide[&("b".to_string(), "c".to_string())], 1);
+        assert!(!wide.contains_key(&("b".to_string(), "a".to_string())));
+    }
+
+    #[test]
+    fn test_cluster_token_participation() {
+        let buy = |wallet: &str, sol_amount: f64| TokenInteraction {
+            sol_amount,
+            ..interactions(wallet, &["pump"]).remove(0)
+        };
+        let cluster: Vec<String> = ["c1", "c2", "c3", "idle"].iter().map(|w| w.to_string()).collect();
+        let token = vec![
+            buy("c1", 40.0),
+            buy("c2", 30.0),
+            buy("c1", 10.0),
+            buy("c3", 10.0),
+            buy("retail1", 5.0),
+            buy("retail2", 5.0),
+            TokenInteraction { side: TradeSide::Sell, ..buy("c1", 50.0) },
+        ];
+
+        let (members, share) = cluster_token_participation(&cluster, &token);
+        assert_eq!(members, 3);
+        assert!((share - 0.9).abs() < 1e-9);
+        assert_eq!(cluster_token_participation(&[], &token), (0, 0.0));
+        assert_eq!(cluster_token_participation(&cluster, &[]), (0, 0.0));
+    }
+
+    #[test]
+    fn test_effective_independence() {
+        let sybils: Vec<String> = (0..10).map(|i| format!("s{}", i)).collect();
+        let mut co_occurrence: HashMap<(String, String), usize> = HashMap::new();
+        for (i, a) in sybils.iter().enumerate() {
+            for b in &sybils[i + 1..] {
+                co_occurrence.insert((a.clone(), b.clone()), 200);
+            }
+        }
+        let coordinated = effective_independence(&sybils, &co_occurrence);
+        assert!(coordinated > 1.0 && coordinated < 1.3, "{}", coordinated);
+
+        let independent: Vec<String> = (0..10).map(|i| format!("w{}", i)).collect();
+        assert_eq!(effective_independence(&independent, &co_occurrence), 10.0);
+
+        // One tightly linked pair among three wallets, via co_occurrence_matrix keys
+        let mut pair = HashMap::new();
+        pair.insert(("a".to_string(), "b".to_string()), 5);
+        let trio = vec!["b".to_string(), "a".to_string(), "c".to_string(), "a".to_string()];
+        assert!((effective_independence(&trio, &pair) - 9.0 / 4.0).abs() < 1e-9);
+        assert_eq!(effective_independence(&[], &pair), 0.0);
+    }
+
+    #[test]
+    fn test_coordination_likelihood() {
+        let buy = |wallet: &str, mint: &str, block_time: u64| TokenInteraction {
+            block_time,
+            ..interactions(wallet, &[mint]).remove(0)
+        };
+        let mints: Vec<String> = (0..20).map(|i| format!("t{}", i)).collect();
+        let leader: Vec<TokenInteraction> = mints.iter().zip(0..).map(|(m, i)| buy("a", m, 1000 + i * 600)).collect();
+        let shadow: Vec<TokenInteraction> = mints.iter().zip(0..).map(|(m, i)| buy("b", m, 1004 + i * 600)).collect();
+        let independent = vec![buy("c", "t1", 1300), buy("c", "x1", 2050), buy("c", "x2", 3500)];
+
+        // 20 co-occurrences earn 20 / 25 of the 50 points; timing and overlap are perfect
+        assert_eq!(coordination_likelihood(&leader, &shadow, 30, 60), 90);
+        assert_eq!(coordination_likelihood(&shadow, &leader, 30, 60), 90);
+        assert!(coordination_likelihood(&leader, &independent, 30, 60) < 15);
+        assert_eq!(coordination_likelihood(&leader, &[], 30, 60), 0);
+    }
+
+    #[test]
+    fn test_token_overlap_empty() {
+        let a = interactions("a", &["t1"]);
+
+        assert_eq!(token_overlap(&[], &[]), 0.0);
+        assert_eq!(token_overlap(&a, &[]), 0.0);
+    }
+
+    fn sell(wallet: &str, mint: &str, block_time: u64, sol_amount: f64) -> TokenInteraction {
+        TokenInteraction {
+            wallet_address: wallet.to_string(),
+            token_mint: mint.to_string(),
+            block_time,
+            sol_amount,
+            is_early_entry: false,
+            side: TradeSide::Sell,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_detect_coordinated_exits() {
+        let mut interactions = vec![
+            sell("c", "t1", 1004, 3.0),
+            sell("a", "t1", 1000, 5.0),
+            sell("b", "t1", 1002, 4.0),
+            sell("a", "t1", 1003, 1.0),
+            sell("d", "t1", 1500, 2.0),
+        ];
+        // Buys in the same window never count toward an exit
+        interactions.push(TokenInteraction { side: TradeSide::Buy, ..sell("e", "t1", 1001, 9.0) });
+
+        let events = detect_coordinated_exits(&interactions, 5, 3);
+        assert_eq!(
+            events,
+            vec![CoordinatedEvent {
+                token_mint: "t1".to_string(),
+                side: TradeSide::Sell,
+                start_time: 1000,
+                end_time: 1004,
+                wallets: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                total_sol: 13.0,
+            }]
+        );
+        assert!(detect_coordinated_exits(&interactions, 5, 4).is_empty());
+    }
+
+    #[test]
+    fn test_detect_coordinated_exits_ignores_scattered_sells() {
+        let interactions = vec![
+            sell("a", "t1", 1000, 5.0),
+            sell("b", "t1", 1100, 5.0),
+            sell("c", "t1", 1200, 5.0),
+            // Same moment, but different tokens
+            sell("d", "t2", 1000, 5.0),
+            sell("e", "t3", 1000, 5.0),
+        ];
+
+        assert!(detect_coordinated_exits(&interactions, 30, 2).is_empty());
+        assert!(detect_coordinated_exits(&[], 30, 2).is_empty());
+    }
+}
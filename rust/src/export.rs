@@ -0,0 +1,238 @@
+//! Text exports of scores for external tooling
+//! Monitoring and visualization formats
+
+use crate::{calculate_whale_score, hash_wallet_address_salted, whale_score_components, WalletStats, WhaleTier};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+
+/// Escape a Prometheus label value or DOT quoted ID (backslash, double quote and newline)
+fn escape_label_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Export whale scores in the Prometheus text exposition format
+///
+/// Emits one `whale_score{address="..."}` gauge sample per wallet, sorted by
+/// address, followed by a `whale_score_count` gauge with the number of wallets.
+pub fn export_prometheus(stats_map: &HashMap<String, WalletStats>) -> String {
+    let mut addresses: Vec<&String> = stats_map.keys().collect();
+    addresses.sort();
+
+    let mut out = String::new();
+    out.push_str("# HELP whale_score Whale score per wallet (0-100)\n");
+    out.push_str("# TYPE whale_score gauge\n");
+    for address in addresses {
+        let score = calculate_whale_score(&stats_map[address]);
+        let _ = writeln!(out, "whale_score{{address=\"{}\"}} {}", escape_label_value(address), score);
+    }
+    out.push_str("# HELP whale_score_count Number of scored wallets\n");
+    out.push_str("# TYPE whale_score_count gauge\n");
+    let _ = writeln!(out, "whale_score_count {}", stats_map.len());
+
+    out
+}
+
+/// Multi-line, human-readable summary of a wallet's score
+///
+/// Lists the address, score and tier, the points earned by each of the four
+/// components, and the stats they were computed from. Meant for logs and
+/// terminals; the layout is not stable and shouldn't be parsed.
+pub fn format_report(stats: &WalletStats) -> String {
+    let score = calculate_whale_score(stats);
+    let components = if stats.interaction_count == 0 { [0.0; 4] } else { whale_score_components(stats) };
+    let names = ["early_entry", "buy_size", "repetition", "profit"];
+    let ceilings = [40, 30, 20, 10];
+
+    let mut out = String::new();
+    let _ = writeln!(out, "wallet: {}", stats.address);
+    let _ = writeln!(out, "score: {}/100", score);
+    let _ = writeln!(out, "tier: {}", WhaleTier::from_score(score));
+    out.push_str("components:\n");
+    for ((name, points), ceiling) in names.iter().zip(components).zip(ceilings) {
+        let _ = writeln!(out, "  {:<12} {:>5.2} / {}", name, points, ceiling);
+    }
+    let _ = writeln!(out, "interactions: {} ({} early)", stats.interaction_count, stats.early_entry_count);
+    let _ = writeln!(
+        out,
+        "volume: {:.2} SOL total, {:.2} SOL net, {:.2} SOL avg entry",
+        stats.total_volume_sol, stats.net_volume_sol, stats.average_entry_size
+    );
+    let _ = writeln!(out, "winrate proxy: {:.2}", stats.winrate_proxy);
+    if let Some(multiple) = stats.avg_realized_multiple {
+        let _ = writeln!(out, "avg realized multiple: {:.2}x", multiple);
+    }
+
+    out
+}
+
+/// Copy of a stats map with every address replaced by its salted hash
+///
+/// Both the key and `WalletStats::address` become
+/// `hash_wallet_address_salted(address, salt)`; every numeric field is kept
+/// as is. The same salt gives the same pseudonyms across exports, so
+/// published datasets can be joined with each other but not with raw
+/// addresses.
+pub fn anonymize_stats_map(stats_map: &HashMap<String, WalletStats>, salt: &[u8]) -> HashMap<String, WalletStats> {
+    stats_map
+        .iter()
+        .map(|(address, stats)| {
+            let anonymized = WalletStats {
+                address: hash_wallet_address_salted(&stats.address, salt),
+                ..stats.clone()
+            };
+            (hash_wallet_address_salted(address, salt), anonymized)
+        })
+        .collect()
+}
+
+/// Export a co-occurrence graph as an undirected GraphViz DOT document
+///
+/// Each pair with a count of at least `min_weight` becomes an edge carrying
+/// the count as `weight` and `label`; only wallets with a kept edge appear as
+/// nodes. Nodes and edges are sorted, so identical input gives identical
+/// output. Render with e.g. `dot -Tsvg`.
+pub fn export_graph_dot(co_occurrence: &HashMap<(String, String), usize>, min_weight: usize) -> String {
+    let mut edges: Vec<(&String, &String, usize)> = co_occurrence
+        .iter()
+        .filter(|(_, &count)| count >= min_weight)
+        .map(|((a, b), &count)| if a <= b { (a, b, count) } else { (b, a, count) })
+        .collect();
+    edges.sort();
+    let nodes: BTreeSet<&String> = edges.iter().flat_map(|&(a, b, _)| [a, b]).collect();
+
+    let mut out = String::from("graph coordination {\n");
+    for node in nodes {
+        let _ = writeln!(out, "  \"{}\";", escape_label_value(node));
+    }
+    for (a, b, count) in edges {
+        let _ = writeln!(
+            out,
+            "  \"{}\" -- \"{}\" [weight={}, label=\"{}\"];",
+            escape_label_value(a),
+            escape_label_value(b),
+            count,
+            count
+        );
+    }
+    out.push_str("}\n");
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(address: &str, interaction_count: u64) -> WalletStats {
+        WalletStats {
+            address: address.to_string(),
+            total_volume_sol: 0.0,
+            interaction_count,
+            average_entry_size: 0.0,
+            early_entry_count: 0,
+            winrate_proxy: 0.0,
+            avg_realized_multiple: None,
+            net_volume_sol: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_export_prometheus() {
+        let mut stats_map = HashMap::new();
+        stats_map.insert("plain".to_string(), stats("plain", 25));
+        stats_map.insert("we\"ird\\addr\n".to_string(), stats("weird", 0));
+
+        let output = export_prometheus(&stats_map);
+        let expected = "# HELP whale_score Whale score per wallet (0-100)\n\
+                        # TYPE whale_score gauge\n\
+                        whale_score{address=\"plain\"} 10\n\
+                        whale_score{address=\"we\\\"ird\\\\addr\\n\"} 0\n\
+                        # HELP whale_score_count Number of scored wallets\n\
+                        # TYPE whale_score_count gauge\n\
+                        whale_score_count 2\n";
+
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_export_graph_dot() {
+        let co_occurrence: HashMap<(String, String), usize> = [
+            (("b".to_string(), "a".to_string()), 4),
+            (("a".to_string(), "q\"uote".to_string()), 2),
+            (("c".to_string(), "d".to_string()), 1),
+        ]
+        .into_iter()
+        .collect();
+
+        let output = export_graph_dot(&co_occurrence, 2);
+        let expected = "graph coordination {\n  \"a\";\n  \"b\";\n  \"q\\\"uote\";\n  \
+                        \"a\" -- \"b\" [weight=4, label=\"4\"];\n  \
+                        \"a\" -- \"q\\\"uote\" [weight=2, label=\"2\"];\n}\n";
+        assert_eq!(output, expected);
+        assert!(!output.contains("\"c\""));
+        assert_eq!(export_graph_dot(&HashMap::new(), 0), "graph coordination {\n}\n");
+    }
+
+    #[test]
+    fn test_anonymize_stats_map() {
+        let mut stats_map = HashMap::new();
+        stats_map.insert("alice".to_string(), stats("alice", 25));
+        stats_map.insert("bob".to_string(), stats("bob", 3));
+
+        let anonymized = anonymize_stats_map(&stats_map, b"secret");
+        let again = anonymize_stats_map(&stats_map, b"secret");
+        let resalted = anonymize_stats_map(&stats_map, b"other");
+
+        let alice = hash_wallet_address_salted("alice", b"secret");
+        assert_eq!(anonymized.len(), 2);
+        assert!(!anonymized.contains_key("alice"));
+        assert_eq!(anonymized[&alice].address, alice);
+        assert_eq!(anonymized[&alice].interaction_count, 25);
+        assert_eq!(
+            calculate_whale_score(&anonymized[&alice]),
+            calculate_whale_score(&stats_map["alice"])
+        );
+        let mut keys: Vec<&String> = anonymized.keys().collect();
+        let mut again_keys: Vec<&String> = again.keys().collect();
+        keys.sort();
+        again_keys.sort();
+        assert_eq!(keys, again_keys);
+        assert!(!resalted.contains_key(&alice));
+        assert_ne!(alice, crate::hash_wallet_address("alice"));
+    }
+
+    #[test]
+    fn test_format_report() {
+        let stats = WalletStats {
+            address: "addr1".to_string(),
+            total_volume_sol: 2500.0,
+            interaction_count: 50,
+            average_entry_size: 50.0,
+            early_entry_count: 10,
+            winrate_proxy: 0.3,
+            avg_realized_multiple: None,
+            net_volume_sol: 2500.0,
+        };
+
+        let report = format_report(&stats);
+        assert!(report.contains("wallet: addr1"));
+        assert!(report.contains("score: 77/100"));
+        assert!(report.contains("tier: Mega"));
+        assert!(report.contains("repetition   20.00 / 20"));
+        assert!(report.contains("interactions: 50 (10 early)"));
+        assert!(!report.contains("realized multiple"));
+
+        let empty = format_report(&WalletStats::empty("addr2"));
+        assert!(empty.contains("score: 0/100"));
+        assert!(empty.contains("tier: Minnow"));
+    }
+}
@@ -0,0 +1,320 @@
+//! Lenient ingestion of messy upstream feeds
+//! Coerces loosely-typed JSON records into `TokenInteraction`
+
+use crate::{TokenInteraction, TradeSide};
+use serde::Deserialize;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+
+/// A JSON scalar accepted wherever a strict bool/number is expected
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum LenientValue {
+    Bool(bool),
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl LenientValue {
+    fn describe(&self) -> String {
+        match self {
+            LenientValue::Bool(b) => b.to_string(),
+            LenientValue::Unsigned(n) => n.to_string(),
+            LenientValue::Signed(n) => n.to_string(),
+            LenientValue::Float(n) => n.to_string(),
+            LenientValue::Text(s) => format!("{:?}", s),
+        }
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        match self {
+            LenientValue::Unsigned(n) => Some(*n),
+            LenientValue::Signed(n) => u64::try_from(*n).ok(),
+            LenientValue::Float(n) if n.fract() == 0.0 && *n >= 0.0 && *n <= u64::MAX as f64 => {
+                Some(*n as u64)
+            }
+            LenientValue::Text(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        let value = match self {
+            LenientValue::Unsigned(n) => *n as f64,
+            LenientValue::Signed(n) => *n as f64,
+            LenientValue::Float(n) => *n,
+            LenientValue::Text(s) => parse_grouped_amount(s.trim())?,
+            LenientValue::Bool(_) => return None,
+        };
+        value.is_finite().then_some(value)
+    }
+
+    fn to_bool(&self) -> Option<bool> {
+        match self {
+            LenientValue::Bool(b) => Some(*b),
+            LenientValue::Unsigned(0) | LenientValue::Signed(0) => Some(false),
+            LenientValue::Unsigned(1) | LenientValue::Signed(1) => Some(true),
+            LenientValue::Text(s) => match s.trim().to_ascii_lowercase().as_str() {
+                "true" | "1" => Some(true),
+                "false" | "0" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Parse a number, allowing commas only as thousands separators
+///
+/// `"1,250.5"` parses as 1250.5, but a comma anywhere else (`"1,5"`,
+/// `"1,2,3"`, `"12,34.5"`) rejects the whole value rather than guessing.
+fn parse_grouped_amount(text: &str) -> Option<f64> {
+    if !text.contains(',') {
+        return text.parse().ok();
+    }
+
+    let (integer, fraction) = match text.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (text, None),
+    };
+    let all_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    let mut groups = integer.split(',');
+    let leading = groups.next()?;
+    let grouped = all_digits(leading) && leading.len() <= 3 && groups.all(|group| group.len() == 3 && all_digits(group));
+    if !grouped || !fraction.is_none_or(all_digits) {
+        return None;
+    }
+    text.replace(',', "").parse().ok()
+}
+
+/// Loosely-typed `TokenInteraction` as sent by some upstream feeds
+///
+/// Accepts `block_time` as a number or numeric string, `sol_amount` as a
+/// number or a string with thousands separators (`"1,250.5"`), and
+/// `is_early_entry` as a bool, `0`/`1`, or `"true"`/`"false"`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TokenInteractionRaw {
+    pub wallet_address: String,
+    pub token_mint: String,
+    pub block_time: LenientValue,
+    pub sol_amount: LenientValue,
+    pub is_early_entry: LenientValue,
+    #[serde(default)]
+    pub side: TradeSide,
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// Error converting a `TokenInteractionRaw` that can't be coerced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InteractionParseError {
+    InvalidBlockTime(String),
+    InvalidSolAmount(String),
+    InvalidEarlyEntry(String),
+}
+
+impl fmt::Display for InteractionParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InteractionParseError::InvalidBlockTime(v) => write!(f, "invalid block_time: {}", v),
+            InteractionParseError::InvalidSolAmount(v) => write!(f, "invalid sol_amount: {}", v),
+            InteractionParseError::InvalidEarlyEntry(v) => write!(f, "invalid is_early_entry: {}", v),
+        }
+    }
+}
+
+impl std::error::Error for InteractionParseError {}
+
+impl TryFrom<TokenInteractionRaw> for TokenInteraction {
+    type Error = InteractionParseError;
+
+    fn try_from(raw: TokenInteractionRaw) -> Result<Self, Self::Error> {
+        let block_time = raw
+            .block_time
+            .to_u64()
+            .ok_or_else(|| InteractionParseError::InvalidBlockTime(raw.block_time.describe()))?;
+        let sol_amount = raw
+            .sol_amount
+            .to_f64()
+            .ok_or_else(|| InteractionParseError::InvalidSolAmount(raw.sol_amount.describe()))?;
+        let is_early_entry = raw
+            .is_early_entry
+            .to_bool()
+            .ok_or_else(|| InteractionParseError::InvalidEarlyEntry(raw.is_early_entry.describe()))?;
+
+        Ok(TokenInteraction {
+            wallet_address: raw.wallet_address,
+            token_mint: raw.token_mint,
+            block_time,
+            sol_amount,
+            is_early_entry,
+            side: raw.side,
+            source: raw.source,
+        })
+    }
+}
+
+/// A JSONL line that `load_interactions_lenient` could not turn into an interaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFailure {
+    /// 1-based line number
+    pub line: usize,
+    pub message: String,
+}
+
+/// Load JSONL interactions, skipping and reporting bad lines
+///
+/// Each non-blank line is parsed as a `TokenInteractionRaw` and coerced with
+/// `TryFrom`. Lines that fail either step are recorded as a `ParseFailure`
+/// and loading continues; a read error is recorded against the line it
+/// occurred on and ends the load. The call itself never fails.
+pub fn load_interactions_lenient<R: Read>(r: R) -> (Vec<TokenInteraction>, Vec<ParseFailure>) {
+    let mut interactions = Vec::new();
+    let mut failures = Vec::new();
+
+    for (index, line) in BufReader::new(r).lines().enumerate() {
+        let line_number = index + 1;
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                failures.push(ParseFailure { line: line_number, message: e.to_string() });
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let parsed = serde_json::from_str::<TokenInteractionRaw>(&line)
+            .map_err(|e| e.to_string())
+            .and_then(|raw| TokenInteraction::try_from(raw).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(interaction) => interactions.push(interaction),
+            Err(message) => failures.push(ParseFailure { line: line_number, message }),
+        }
+    }
+
+    (interactions, failures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Result<TokenInteraction, InteractionParseError> {
+        let raw: TokenInteractionRaw = serde_json::from_str(json).expect("raw record should deserialize");
+        TokenInteraction::try_from(raw)
+    }
+
+    #[test]
+    fn test_strict_record_passes_through() {
+        let interaction = parse(
+            r#"{"wallet_address":"a","token_mint":"t","block_time":1000,"sol_amount":2.5,"is_early_entry":true}"#,
+        )
+        .unwrap();
+
+        assert_eq!(interaction.block_time, 1000);
+        assert_eq!(interaction.sol_amount, 2.5);
+        assert!(interaction.is_early_entry);
+        assert_eq!(interaction.side, TradeSide::Buy);
+    }
+
+    #[test]
+    fn test_sell_side() {
+        let interaction = parse(
+            r#"{"wallet_address":"a","token_mint":"t","block_time":1000,"sol_amount":2.5,"is_early_entry":false,"side":"sell"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(interaction.side, TradeSide::Sell);
+    }
+
+    #[test]
+    fn test_string_block_time() {
+        let interaction = parse(
+            r#"{"wallet_address":"a","token_mint":"t","block_time":" 1700000000 ","sol_amount":1,"is_early_entry":false}"#,
+        )
+        .unwrap();
+
+        assert_eq!(interaction.block_time, 1_700_000_000);
+        assert_eq!(interaction.sol_amount, 1.0);
+    }
+
+    #[test]
+    fn test_numeric_early_flag() {
+        let early = parse(r#"{"wallet_address":"a","token_mint":"t","block_time":1,"sol_amount":1.0,"is_early_entry":1}"#)
+            .unwrap();
+        let late = parse(r#"{"wallet_address":"a","token_mint":"t","block_time":1,"sol_amount":1.0,"is_early_entry":0}"#)
+            .unwrap();
+
+        assert!(early.is_early_entry);
+        assert!(!late.is_early_entry);
+    }
+
+    #[test]
+    fn test_sol_amount_with_commas() {
+        let interaction = parse(
+            r#"{"wallet_address":"a","token_mint":"t","block_time":1,"sol_amount":"1,250.5","is_early_entry":"true"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(interaction.sol_amount, 1250.5);
+        assert!(interaction.is_early_entry);
+        assert_eq!(parse_grouped_amount("12,345,678"), Some(12_345_678.0));
+    }
+
+    #[test]
+    fn test_misplaced_commas_error() {
+        let decimal_comma = parse(
+            r#"{"wallet_address":"a","token_mint":"t","block_time":1,"sol_amount":"1,5","is_early_entry":true}"#,
+        );
+
+        assert_eq!(decimal_comma.unwrap_err(), InteractionParseError::InvalidSolAmount("\"1,5\"".to_string()));
+        for text in ["1,2,3", "12,34.5", ",100", "1,000,", "1000,000", "1,000.5,0"] {
+            assert_eq!(parse_grouped_amount(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_invalid_values_error() {
+        let bad_amount = parse(
+            r#"{"wallet_address":"a","token_mint":"t","block_time":1,"sol_amount":"lots","is_early_entry":true}"#,
+        );
+        let bad_time = parse(
+            r#"{"wallet_address":"a","token_mint":"t","block_time":-5,"sol_amount":1.0,"is_early_entry":true}"#,
+        );
+        let bad_flag = parse(
+            r#"{"wallet_address":"a","token_mint":"t","block_time":1,"sol_amount":1.0,"is_early_entry":2}"#,
+        );
+
+        assert_eq!(bad_amount.unwrap_err(), InteractionParseError::InvalidSolAmount("\"lots\"".to_string()));
+        assert!(matches!(bad_time, Err(InteractionParseError::InvalidBlockTime(_))));
+        assert!(matches!(bad_flag, Err(InteractionParseError::InvalidEarlyEntry(_))));
+    }
+
+    #[test]
+    fn test_load_interactions_lenient() {
+        let input = concat!(
+            r#"{"wallet_address":"a","token_mint":"t","block_time":1000,"sol_amount":2.5,"is_early_entry":true}"#,
+            "\n",
+            "not json\n",
+            "\n",
+            r#"{"wallet_address":"b","token_mint":"t","block_time":"1001","sol_amount":"3","is_early_entry":0}"#,
+            "\n",
+            r#"{"wallet_address":"c","token_mint":"t","block_time":1002,"sol_amount":"lots","is_early_entry":1}"#,
+            "\n",
+        );
+
+        let (interactions, failures) = load_interactions_lenient(input.as_bytes());
+
+        assert_eq!(interactions.len(), 2);
+        assert_eq!(interactions[1].wallet_address, "b");
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].line, 2);
+        assert_eq!(failures[1].line, 5);
+        assert_eq!(failures[1].message, "invalid sol_amount: \"lots\"");
+    }
+}
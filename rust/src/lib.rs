@@ -1,13 +1,52 @@
 mod scoring;
+mod sampling;
+mod metrics;
+mod timing;
+mod patterns;
+mod coordination;
+mod export;
+mod validation;
+mod portfolio;
+mod cache;
+mod stream;
+mod components;
+mod analysis;
+mod rules;
+#[cfg(feature = "serde")]
+mod ingest;
+#[cfg(feature = "bincode")]
+mod codec;
+#[cfg(feature = "testkit")]
+mod testkit;
 
 use sha2::{Sha256, Digest};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 pub use scoring::*;
+pub use sampling::*;
+pub use metrics::*;
+pub use timing::*;
+pub use patterns::*;
+pub use coordination::*;
+pub use export::*;
+pub use validation::*;
+pub use portfolio::*;
+pub use cache::*;
+pub use stream::*;
+pub use components::*;
+pub use analysis::*;
+pub use rules::*;
+#[cfg(feature = "serde")]
+pub use ingest::*;
+#[cfg(feature = "bincode")]
+pub use codec::*;
+#[cfg(feature = "testkit")]
+pub use testkit::*;
 
 /// Wallet scoring algorithm implementation in Rust
 /// Provides high-performance calculations for whale detection
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WalletStats {
     pub address: String,
     pub total_volume_sol: f64,
@@ -15,15 +54,102 @@ pub struct WalletStats {
     pub average_entry_size: f64,
     pub early_entry_count: u64,
     pub winrate_proxy: f64,
+    /// Geometric mean of realized per-token multiples (e.g. 3.0 for 3x), when known
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub avg_realized_multiple: Option<f64>,
+    /// Buy volume minus sell volume in SOL (equals `total_volume_sol` for buy-only data)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub net_volume_sol: f64,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+impl WalletStats {
+    /// Stats for a wallet with no interactions
+    pub fn empty(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            total_volume_sol: 0.0,
+            interaction_count: 0,
+            average_entry_size: 0.0,
+            early_entry_count: 0,
+            winrate_proxy: 0.0,
+            avg_realized_multiple: None,
+            net_volume_sol: 0.0,
+        }
+    }
+
+    /// Stats over both wallets' interactions, as if processed together
+    ///
+    /// Volumes and counts add up, `average_entry_size` and `winrate_proxy`
+    /// are rederived the way `process_interactions` derives them, and
+    /// `avg_realized_multiple` is the interaction-weighted geometric mean of
+    /// whichever sides know it. Merging the stats of two interaction lists
+    /// matches `process_interactions` over their concatenation. The address
+    /// is `self`'s unless that is empty.
+    pub fn merge(&self, other: &WalletStats) -> WalletStats {
+        if other.interaction_count == 0 {
+            return self.clone();
+        }
+        if self.interaction_count == 0 {
+            let address = if self.address.is_empty() { &other.address } else { &self.address };
+            return WalletStats { address: address.clone(), ..other.clone() };
+        }
+
+        let interaction_count = self.interaction_count + other.interaction_count;
+        let total_volume_sol = self.total_volume_sol + other.total_volume_sol;
+        let early_entry_count = self.early_entry_count + other.early_entry_count;
+        let avg_realized_multiple = match (self.avg_realized_multiple, other.avg_realized_multiple) {
+            (Some(a), Some(b)) if a > 0.0 && b > 0.0 => {
+                let (wa, wb) = (self.interaction_count as f64, other.interaction_count as f64);
+                Some(((a.ln() * wa + b.ln() * wb) / (wa + wb)).exp())
+            }
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (known, None) | (None, known) => known,
+        };
+
+        WalletStats {
+            address: if self.address.is_empty() { other.address.clone() } else { self.address.clone() },
+            total_volume_sol,
+            interaction_count,
+            average_entry_size: total_volume_sol / interaction_count as f64,
+            early_entry_count,
+            winrate_proxy: winrate_proxy(early_entry_count as f64, interaction_count as f64),
+            avg_realized_multiple,
+            net_volume_sol: self.net_volume_sol + other.net_volume_sol,
+        }
+    }
+}
+
+impl From<&[TokenInteraction]> for WalletStats {
+    /// Same as `process_interactions`
+    fn from(interactions: &[TokenInteraction]) -> Self {
+        process_interactions(interactions)
+    }
+}
+
+/// Direction of a trade
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum TradeSide {
+    #[default]
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TokenInteraction {
     pub wallet_address: String,
     pub token_mint: String,
     pub block_time: u64,
     pub sol_amount: f64,
     pub is_early_entry: bool,
+    /// Buy or sell; records without a side are buys
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub side: TradeSide,
+    /// Provider(s) the record came from, when known (see `merge_sources`)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub source: Option<String>,
 }
 
 /// Calculate whale score based on wallet statistics
@@ -32,40 +158,196 @@ pub struct TokenInteraction {
 /// - Early entry weight: 40 points
 /// - Buy size weight: 30 points  
 /// - Repetition count: 20 points
-/// - Profit proxy: 10 points
+/// - Profit proxy: 10 points (log-scaled realized multiple when available)
 pub fn calculate_whale_score(stats: &WalletStats) -> u8 {
+    whale_score_total(stats) as u8
+}
+
+/// Version of the scoring math, bumped whenever scores for the same input change
+const ALGORITHM_VERSION: &str = "1.0.0";
+
+/// Semantic version of the scoring algorithm
+///
+/// Persisted scores computed under a different version may no longer match
+/// what `calculate_whale_score` returns and should be recomputed.
+pub fn algorithm_version() -> &'static str {
+    ALGORITHM_VERSION
+}
+
+/// A whale score tagged with the algorithm version that produced it
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoredResult {
+    pub score: u8,
+    pub algorithm_version: String,
+}
+
+impl ScoredResult {
+    /// Score `stats` under the current algorithm version
+    pub fn new(stats: &WalletStats) -> Self {
+        Self {
+            score: calculate_whale_score(stats),
+            algorithm_version: ALGORITHM_VERSION.to_string(),
+        }
+    }
+
+    /// Whether the score was produced by the current algorithm version
+    pub fn is_current(&self) -> bool {
+        self.algorithm_version == ALGORITHM_VERSION
+    }
+}
+
+/// Calculate whale score and report whether the input was inconsistent
+///
+/// The flag is true when `early_entry_count > interaction_count`; the score
+/// is computed with the early count clamped to the interaction count, exactly
+/// as `calculate_whale_score` does.
+pub fn calculate_whale_score_flagged(stats: &WalletStats) -> (u8, bool) {
+    let (_, clamped) = clamped_early_count(stats);
+    (calculate_whale_score(stats), clamped)
+}
+
+/// `early_entry_count` capped at `interaction_count`, plus whether capping was needed
+///
+/// Buggy upstream data can report more early entries than interactions; the
+/// scorers clamp explicitly instead of relying on per-component ceilings.
+/// With the `tracing` feature a `debug!` event is emitted when this happens;
+/// every scorer calls this on every score, so it stays below warn level to
+/// keep a bad wallet scored in a loop from flooding the logs.
+pub(crate) fn clamped_early_count(stats: &WalletStats) -> (u64, bool) {
+    if stats.early_entry_count <= stats.interaction_count {
+        return (stats.early_entry_count, false);
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        wallet = %stats.address,
+        early_entry_count = stats.early_entry_count,
+        interaction_count = stats.interaction_count,
+        "early_entry_count exceeds interaction_count; clamping"
+    );
+
+    (stats.interaction_count, true)
+}
+
+/// Whale score before truncation to an integer (0.0-100.0)
+pub(crate) fn whale_score_total(stats: &WalletStats) -> f64 {
     if stats.interaction_count == 0 {
-        return 0;
+        return 0.0;
     }
 
-    let early_entry_ratio = if stats.interaction_count > 0 {
-        stats.early_entry_count as f64 / stats.interaction_count as f64
-    } else {
-        0.0
-    };
+    let total_score: f64 = whale_score_components(stats).iter().sum();
+    total_score.min(100.0)
+}
 
-    // Component 1: Early Entry Score (0-40 points)
-    let ratio_score = (early_entry_ratio * 20.0).min(20.0);
-    let count_score = (stats.early_entry_count as f64 * 2.0).min(20.0);
-    let early_entry_score = ratio_score + count_score;
+/// Compute the four raw whale score components
+///
+/// Returns `[early_entry, buy_size, repetition, profit]` points, each already
+/// clamped to its own ceiling (40, 30, 20 and 10 respectively).
+pub(crate) fn whale_score_components(stats: &WalletStats) -> [f64; 4] {
+    whale_score_components_with(stats, &ScoringConfig::default())
+}
 
-    // Component 2: Buy Size Score (0-30 points)
-    // Normalize average entry size (assuming 50+ SOL is maximum)
-    let avg_size_score = ((stats.average_entry_size / 50.0) * 20.0).min(20.0);
-    // Normalize total volume (assuming 500+ SOL is maximum)
-    let volume_score = ((stats.total_volume_sol / 500.0) * 10.0).min(10.0);
-    let buy_size_score = avg_size_score + volume_score;
+/// Compute the whale score components using the curves in `config`
+pub(crate) fn whale_score_components_with(stats: &WalletStats, config: &ScoringConfig) -> [f64; 4] {
+    let (early_entry_count, _) = clamped_early_count(stats);
+    let points = raw_subscores_with(stats, early_entry_count as f64, config).map(|subscore| subscore.points());
 
+    // Component 1: Early Entry Score (0-40 points, split between ratio and count)
+    let early_entry_score = points[0] + points[1];
+    // Component 2: Buy Size Score (0-30 points)
+    let buy_size_score = points[2] + points[3];
     // Component 3: Repetition Score (0-20 points)
-    // Linear scaling up to 50 interactions = 20 points
-    let repetition_score = ((stats.interaction_count as f64 / 50.0) * 20.0).min(20.0);
-
+    let repetition_score = points[4];
     // Component 4: Profit Score (0-10 points)
-    let profit_score = stats.winrate_proxy * 10.0;
+    let profit_score = points[5];
 
-    let total_score = early_entry_score + buy_size_score + repetition_score + profit_score;
-    
-    (total_score.min(100.0)) as u8
+    [early_entry_score, buy_size_score, repetition_score, profit_score]
+}
+
+/// One of the six sub-scores behind the four whale score components
+///
+/// `value` earns the full `weight` points once it reaches `ceiling`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct SubScore {
+    pub(crate) name: &'static str,
+    pub(crate) value: f64,
+    pub(crate) ceiling: f64,
+    pub(crate) weight: f64,
+}
+
+impl SubScore {
+    /// `(value / ceiling) * weight` before any clamping
+    pub(crate) fn raw_points(&self) -> f64 {
+        self.value / self.ceiling * self.weight
+    }
+
+    /// Points the scorer awards, clamped to `[0, weight]` by `normalize`
+    pub(crate) fn points(&self) -> f64 {
+        normalize(self.value, self.ceiling, self.weight)
+    }
+}
+
+/// The six sub-scores behind the four components, with unsaturated values
+///
+/// Nothing is clamped here: an early count above the interaction count, a
+/// winrate outside `[0, 1]`, a realized multiple above 10x or an average
+/// entry above 50 SOL all show up as `raw_points` beyond the weight.
+/// `early_entry_count` is passed separately so the scorer can sanitize it
+/// first with `clamped_early_count`.
+pub(crate) fn raw_subscores_with(stats: &WalletStats, early_entry_count: f64, config: &ScoringConfig) -> [SubScore; 6] {
+    let count = stats.interaction_count as f64;
+    let early_entry_ratio = if count > 0.0 { early_entry_count / count } else { 0.0 };
+    let subscore = |name, value, ceiling, weight| SubScore { name, value, ceiling, weight };
+
+    let ratio_points = config.early_ratio_points.clamp(0.0, 40.0);
+    let count_points = 40.0 - ratio_points;
+    let curve = config.volume_curve;
+    [
+        subscore("early_entry_ratio", early_entry_ratio, 1.0, ratio_points),
+        subscore("early_entry_count", early_entry_count, 10.0, count_points),
+        // Average entry size saturates at 50 SOL, total volume at 500 SOL
+        subscore("average_entry_size", curve.apply(stats.average_entry_size / 50.0), 1.0, 20.0),
+        subscore("total_volume", curve.apply(stats.total_volume_sol / 500.0), 1.0, 10.0),
+        // Linear scaling up to 50 interactions = 20 points
+        subscore("repetition", count, 50.0, 20.0),
+        subscore("profit", raw_profit_fraction(stats), 1.0, 10.0),
+    ]
+}
+
+/// Points earned by `value` out of `weight`, reaching `weight` at `ceiling`
+///
+/// Computes `(value / ceiling) * weight` clamped to `[0, weight]`. A NaN or
+/// negative value, a NaN, zero or negative ceiling, and a non-positive weight
+/// all earn 0.
+pub(crate) fn normalize(value: f64, ceiling: f64, weight: f64) -> f64 {
+    if value.is_nan() || ceiling.is_nan() || ceiling <= 0.0 || weight.is_nan() || weight <= 0.0 {
+        return 0.0;
+    }
+    ((value / ceiling) * weight).clamp(0.0, weight)
+}
+
+/// Realized multiple that earns the full profit component
+pub(crate) const FULL_PROFIT_MULTIPLE: f64 = 10.0;
+
+/// Fraction of the profit component a wallet earns
+///
+/// Uses the log-scaled realized multiple when available (1x earns nothing,
+/// 10x or better earns everything) and falls back to `winrate_proxy` otherwise.
+pub(crate) fn profit_fraction(stats: &WalletStats) -> f64 {
+    match stats.avg_realized_multiple {
+        Some(_) => raw_profit_fraction(stats).clamp(0.0, 1.0),
+        None => stats.winrate_proxy,
+    }
+}
+
+/// `profit_fraction` before the realized multiple is clamped to `[0, 1]`
+fn raw_profit_fraction(stats: &WalletStats) -> f64 {
+    match stats.avg_realized_multiple {
+        Some(multiple) if multiple > 0.0 => multiple.ln() / FULL_PROFIT_MULTIPLE.ln(),
+        Some(_) => 0.0,
+        None => stats.winrate_proxy,
+    }
 }
 
 /// Calculate insider confidence score
@@ -114,20 +396,112 @@ pub fn calculate_insider_confidence(
     (confidence.min(100.0)) as u8
 }
 
+/// Coarse size class of a wallet by whale score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WhaleTier {
+    /// Score below 25
+    Minnow,
+    /// Score 25-49
+    Dolphin,
+    /// Score 50-74
+    Whale,
+    /// Score 75 or above
+    Mega,
+}
+
+impl WhaleTier {
+    /// Every tier, smallest first
+    pub const ALL: [WhaleTier; 4] = [WhaleTier::Minnow, WhaleTier::Dolphin, WhaleTier::Whale, WhaleTier::Mega];
+
+    pub fn from_score(score: u8) -> Self {
+        match score {
+            0..=24 => WhaleTier::Minnow,
+            25..=49 => WhaleTier::Dolphin,
+            50..=74 => WhaleTier::Whale,
+            _ => WhaleTier::Mega,
+        }
+    }
+}
+
+impl std::fmt::Display for WhaleTier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            WhaleTier::Minnow => "Minnow",
+            WhaleTier::Dolphin => "Dolphin",
+            WhaleTier::Whale => "Whale",
+            WhaleTier::Mega => "Mega",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Whale score and insider confidence for one wallet, plus a blend of both
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CombinedSignal {
+    pub whale_score: u8,
+    pub insider_confidence: u8,
+    /// Geometric mean of the two (0-100), so a wallet must score high on both
+    pub blended: u8,
+}
+
+/// Compute `calculate_whale_score` and `calculate_insider_confidence` together
+///
+/// Insider confidence is computed from `stats` the same way
+/// `batch_insider_confidence` does.
+pub fn combined_signal(stats: &WalletStats, min_threshold: f64, min_repetitions: u64) -> CombinedSignal {
+    let whale_score = calculate_whale_score(stats);
+    let insider_confidence = stats_insider_confidence(stats, min_threshold, min_repetitions);
+    let blended = (whale_score as f64 * insider_confidence as f64).sqrt() as u8;
+
+    CombinedSignal {
+        whale_score,
+        insider_confidence,
+        blended,
+    }
+}
+
+/// `calculate_insider_confidence` with its inputs taken from `stats`
+///
+/// The early entry count is clamped to the interaction count, as the whale
+/// score does.
+fn stats_insider_confidence(stats: &WalletStats, min_threshold: f64, min_repetitions: u64) -> u8 {
+    let (early_entry_count, _) = clamped_early_count(stats);
+    calculate_insider_confidence(
+        early_entry_count,
+        stats.interaction_count,
+        stats.average_entry_size,
+        min_threshold,
+        min_repetitions,
+    )
+}
+
+/// Insider confidence for every wallet in a stats map
+///
+/// Derives the early entry count, interaction count and average buy size
+/// from each `WalletStats` and calls `calculate_insider_confidence`.
+pub fn batch_insider_confidence(
+    stats_map: &HashMap<String, WalletStats>,
+    min_threshold: f64,
+    min_repetitions: u64,
+) -> HashMap<String, u8> {
+    stats_map
+        .iter()
+        .map(|(address, stats)| (address.clone(), stats_insider_confidence(stats, min_threshold, min_repetitions)))
+        .collect()
+}
+
 /// Process batch of interactions and calculate aggregate statistics
+///
+/// Every interaction counts toward volume and counts regardless of side;
+/// `net_volume_sol` subtracts sells from buys.
 pub fn process_interactions(interactions: &[TokenInteraction]) -> WalletStats {
     if interactions.is_empty() {
-        return WalletStats {
-            address: String::new(),
-            total_volume_sol: 0.0,
-            interaction_count: 0,
-            average_entry_size: 0.0,
-            early_entry_count: 0,
-            winrate_proxy: 0.0,
-        };
+        return WalletStats::empty("");
     }
 
     let total_volume: f64 = interactions.iter().map(|i| i.sol_amount).sum();
+    let net_volume: f64 = interactions.iter().map(signed_amount).sum();
     let interaction_count = interactions.len() as u64;
     let average_entry_size = total_volume / interaction_count as f64;
     let early_entry_count = interactions.iter().filter(|i| i.is_early_entry).count() as u64;
@@ -135,7 +509,7 @@ pub fn process_interactions(interactions: &[TokenInteraction]) -> WalletStats {
     // Calculate winrate proxy based on early entries
     // In production, this would track actual profit/loss
     let winrate_proxy = if interaction_count > 0 {
-        (early_entry_count as f64 / interaction_count as f64 * 1.5).min(1.0)
+        winrate_proxy(early_entry_count as f64, interaction_count as f64)
     } else {
         0.3
     };
@@ -147,9 +521,187 @@ pub fn process_interactions(interactions: &[TokenInteraction]) -> WalletStats {
         average_entry_size,
         early_entry_count,
         winrate_proxy,
+        avg_realized_multiple: None,
+        net_volume_sol: net_volume,
     }
 }
 
+/// Winrate proxy from early entries: `1.5 * early / count`, capped at 1.0
+///
+/// The one definition behind every `WalletStats::winrate_proxy` this crate
+/// derives; counts may be fractional (decayed weights). `count` must be positive.
+pub(crate) fn winrate_proxy(early: f64, count: f64) -> f64 {
+    (early / count * 1.5).min(1.0)
+}
+
+/// Process interactions with exponentially decayed weights by age
+///
+/// An interaction `age = now - block_time` seconds old counts with weight
+/// `0.5^(age / half_life)` (`half_life` is at least 1 second; future
+/// timestamps count fully). Volumes are weighted sums, `interaction_count`
+/// and `early_entry_count` are the weighted counts rounded to the nearest
+/// integer, and `average_entry_size` is the weighted mean buy, so old
+/// activity shrinks the volume and repetition signals without changing the
+/// wallet's typical size. If every weight underflows to zero (ancient trades
+/// or a tiny `half_life`) the wallet has no effective activity and gets
+/// `WalletStats::empty` rather than NaN averages.
+pub fn process_interactions_decayed(interactions: &[TokenInteraction], now: u64, half_life: u64) -> WalletStats {
+    let first = match interactions.first() {
+        Some(first) => first,
+        None => return WalletStats::empty(""),
+    };
+
+    let mut weight_sum = 0.0;
+    let mut early_weight = 0.0;
+    let mut total_volume = 0.0;
+    let mut net_volume = 0.0;
+    for interaction in interactions {
+        let weight = decay_weight(interaction, now, half_life);
+        weight_sum += weight;
+        total_volume += weight * interaction.sol_amount;
+        net_volume += weight * signed_amount(interaction);
+        if interaction.is_early_entry {
+            early_weight += weight;
+        }
+    }
+    if weight_sum < f64::MIN_POSITIVE {
+        return WalletStats::empty(&first.wallet_address);
+    }
+
+    WalletStats {
+        address: first.wallet_address.clone(),
+        total_volume_sol: total_volume,
+        interaction_count: weight_sum.round() as u64,
+        average_entry_size: total_volume / weight_sum,
+        early_entry_count: early_weight.round() as u64,
+        winrate_proxy: winrate_proxy(early_weight, weight_sum),
+        avg_realized_multiple: None,
+        net_volume_sol: net_volume,
+    }
+}
+
+/// `0.5^(age / half_life)` for an interaction `now - block_time` seconds old
+///
+/// `half_life` is at least 1 second and future timestamps weigh 1.0.
+pub(crate) fn decay_weight(interaction: &TokenInteraction, now: u64, half_life: u64) -> f64 {
+    let age = timing::saturating_elapsed(now, interaction.block_time) as f64;
+    0.5f64.powf(age / half_life.max(1) as f64)
+}
+
+/// `sol_amount` signed by side: positive for buys, negative for sells
+pub(crate) fn signed_amount(interaction: &TokenInteraction) -> f64 {
+    match interaction.side {
+        TradeSide::Buy => interaction.sol_amount,
+        TradeSide::Sell => -interaction.sol_amount,
+    }
+}
+
+/// Process interactions while ignoring dust below `min_sol_amount`
+///
+/// Sub-threshold interactions are excluded from every aggregate, including
+/// `interaction_count` and `early_entry_count`. Because dust is dropped from
+/// both the numerator and the denominator, `average_entry_size` is the mean of
+/// the remaining buys rather than the raw average. The wallet address is kept
+/// even if every interaction is dust.
+pub fn process_interactions_min(interactions: &[TokenInteraction], min_sol_amount: f64) -> WalletStats {
+    let kept: Vec<TokenInteraction> = interactions
+        .iter()
+        .filter(|i| i.sol_amount >= min_sol_amount)
+        .cloned()
+        .collect();
+
+    let mut stats = process_interactions(&kept);
+    if kept.is_empty() {
+        if let Some(first) = interactions.first() {
+            stats.address = first.wallet_address.clone();
+        }
+    }
+    stats
+}
+
+/// Process interactions with `sol_amount` capped at an upper percentile
+///
+/// Amounts above the `percentile` (0-100, nearest-rank over this wallet's
+/// interactions) are lowered to that value before aggregating, so a single
+/// huge buy can't dominate `average_entry_size` or the volumes. Only amounts
+/// are capped: `interaction_count`, `early_entry_count` and sides are
+/// unchanged. A percentile of 100 (or NaN) leaves the input as-is.
+pub fn process_interactions_winsorized(interactions: &[TokenInteraction], percentile: f64) -> WalletStats {
+    if interactions.is_empty() || percentile.is_nan() {
+        return process_interactions(interactions);
+    }
+
+    let mut amounts: Vec<f64> = interactions.iter().map(|i| i.sol_amount).collect();
+    amounts.sort_by(f64::total_cmp);
+    let rank = (percentile.clamp(0.0, 100.0) / 100.0 * amounts.len() as f64).ceil() as usize;
+    let cap = amounts[rank.clamp(1, amounts.len()) - 1];
+
+    let capped: Vec<TokenInteraction> = interactions
+        .iter()
+        .map(|i| TokenInteraction {
+            sol_amount: i.sol_amount.min(cap),
+            ..i.clone()
+        })
+        .collect();
+    process_interactions(&capped)
+}
+
+/// Share of total `sol_amount` placed in interactions flagged `is_early_entry`
+///
+/// The volume-based counterpart of the early entry ratio: a wallet with few
+/// early entries can still have put most of its capital into them. Returns
+/// 0.0 for empty input or zero total volume.
+pub fn early_volume_ratio(interactions: &[TokenInteraction]) -> f64 {
+    let total: f64 = interactions.iter().map(|i| i.sol_amount).sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+
+    let early: f64 = interactions.iter().filter(|i| i.is_early_entry).map(|i| i.sol_amount).sum();
+    early / total
+}
+
+/// Count buys per size bucket
+///
+/// `buckets` are ascending upper edges in SOL: bucket `i` counts amounts in
+/// `(buckets[i - 1], buckets[i]]` (the first bucket has no lower bound). The
+/// returned vector has one extra trailing overflow bucket for amounts above
+/// the last edge, so its length is `buckets.len() + 1`.
+pub fn buy_size_histogram(interactions: &[TokenInteraction], buckets: &[f64]) -> Vec<u64> {
+    let mut counts = vec![0u64; buckets.len() + 1];
+
+    for interaction in interactions {
+        let index = buckets
+            .iter()
+            .position(|&edge| interaction.sol_amount <= edge)
+            .unwrap_or(buckets.len());
+        counts[index] += 1;
+    }
+
+    counts
+}
+
+/// Aggregate several addresses controlled by one actor into a single entity
+///
+/// Every interaction whose `wallet_address` is in `addresses` is processed
+/// together. The result is labeled `entity:<hash>`, where `<hash>` is
+/// `hash_wallet_address` of the sorted, comma-joined addresses, so the same
+/// set always yields the same label.
+pub fn process_entity(interactions: &[TokenInteraction], addresses: &HashSet<String>) -> WalletStats {
+    let members: Vec<TokenInteraction> = interactions
+        .iter()
+        .filter(|i| addresses.contains(&i.wallet_address))
+        .cloned()
+        .collect();
+
+    let mut sorted: Vec<&str> = addresses.iter().map(|a| a.as_str()).collect();
+    sorted.sort();
+
+    let mut stats = process_interactions(&members);
+    stats.address = format!("entity:{}", hash_wallet_address(&sorted.join(",")));
+    stats
+}
+
 /// Hash wallet address for efficient lookups
 pub fn hash_wallet_address(address: &str) -> String {
     let mut hasher = Sha256::new();
@@ -157,6 +709,18 @@ pub fn hash_wallet_address(address: &str) -> String {
     format!("{:x}", hasher.finalize())
 }
 
+/// Hash a wallet address together with a secret salt
+///
+/// SHA-256 of `salt` followed by the address bytes, hex-encoded. The same
+/// salt always maps an address to the same hash, but without the salt the
+/// hash can't be matched back by hashing candidate addresses.
+pub fn hash_wallet_address_salted(address: &str, salt: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    hasher.update(address.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
 /// Check if interaction qualifies as early entry
 /// 
 /// Returns true if the interaction happened within the specified time window
@@ -165,10 +729,36 @@ pub fn is_early_entry(block_time: u64, token_creation_time: u64, window_seconds:
     if block_time < token_creation_time {
         return false;
     }
-    let time_diff = block_time - token_creation_time;
+    let time_diff = timing::saturating_elapsed(block_time, token_creation_time);
     time_diff <= window_seconds
 }
 
+/// Timing of an interaction relative to its token's creation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryTiming {
+    /// Happened before the token was created (bad data or a pre-launch snipe)
+    PreCreation,
+    /// Happened within the early-entry window after creation
+    Early,
+    /// Happened after the early-entry window
+    Normal,
+}
+
+/// Classify an interaction's timing relative to token creation
+///
+/// Unlike `is_early_entry`, pre-creation timestamps are surfaced as
+/// `EntryTiming::PreCreation` instead of being folded into "not early".
+/// An interaction at exactly the creation time is `Early`.
+pub fn classify_entry_timing(block_time: u64, creation_time: u64, window: u64) -> EntryTiming {
+    if block_time < creation_time {
+        EntryTiming::PreCreation
+    } else if timing::saturating_elapsed(block_time, creation_time) <= window {
+        EntryTiming::Early
+    } else {
+        EntryTiming::Normal
+    }
+}
+
 /// Group interactions by wallet address
 pub fn group_by_wallet(interactions: &[TokenInteraction]) -> HashMap<String, Vec<TokenInteraction>> {
     let mut grouped: HashMap<String, Vec<TokenInteraction>> = HashMap::new();
@@ -176,28 +766,292 @@ pub fn group_by_wallet(interactions: &[TokenInteraction]) -> HashMap<String, Vec
     for interaction in interactions {
         grouped
             .entry(interaction.wallet_address.clone())
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(interaction.clone());
     }
     
     grouped
 }
 
+/// Group interactions by `(wallet_address, token_mint)` pair
+pub fn group_by_wallet_token(
+    interactions: &[TokenInteraction],
+) -> HashMap<(String, String), Vec<TokenInteraction>> {
+    let mut grouped: HashMap<(String, String), Vec<TokenInteraction>> = HashMap::new();
+
+    for interaction in interactions {
+        grouped
+            .entry((interaction.wallet_address.clone(), interaction.token_mint.clone()))
+            .or_default()
+            .push(interaction.clone());
+    }
+
+    grouped
+}
+
+/// Collapse duplicate feed records into one interaction each
+///
+/// Records with the same `wallet_address`, `token_mint`, `block_time` and
+/// `side` are merged: `sol_amount` is summed and `is_early_entry` is OR-ed.
+/// A buy and a sell in the same block stay separate. Merged records keep the
+/// position of the first one seen.
+pub fn dedup_interactions(interactions: &[TokenInteraction]) -> Vec<TokenInteraction> {
+    let mut positions: HashMap<(&str, &str, u64, TradeSide), usize> = HashMap::new();
+    let mut deduped: Vec<TokenInteraction> = Vec::with_capacity(interactions.len());
+
+    for interaction in interactions {
+        let key = (
+            interaction.wallet_address.as_str(),
+            interaction.token_mint.as_str(),
+            interaction.block_time,
+            interaction.side,
+        );
+        match positions.get(&key) {
+            Some(&index) => {
+                deduped[index].sol_amount += interaction.sol_amount;
+                deduped[index].is_early_entry |= interaction.is_early_entry;
+            }
+            None => {
+                positions.insert(key, deduped.len());
+                deduped.push(interaction.clone());
+            }
+        }
+    }
+
+    deduped
+}
+
+/// Merge split orders back into the positions they were executed for
+///
+/// Interactions are walked in `block_time` order. A wallet's buy of a token
+/// within `max_block_gap` seconds of its previous buy of that token, with no
+/// sell of the token in between, is folded into that earlier buy: the
+/// amounts are summed, `is_early_entry` is OR-ed and the first fragment's
+/// `block_time` and `source` are kept. Chains can span any number of
+/// fragments as long as each gap is short enough. Sells pass through
+/// untouched. The result is ordered by `block_time`.
+pub fn merge_split_orders(interactions: &[TokenInteraction], max_block_gap: u64) -> Vec<TokenInteraction> {
+    // (wallet, mint) -> (index of the open merged buy, time of its latest fragment)
+    let mut open: HashMap<(&str, &str), (usize, u64)> = HashMap::new();
+    let mut merged: Vec<TokenInteraction> = Vec::with_capacity(interactions.len());
+
+    for interaction in timing::sorted_by_time(interactions) {
+        let key = (interaction.wallet_address.as_str(), interaction.token_mint.as_str());
+        if interaction.side == TradeSide::Sell {
+            open.remove(&key);
+            merged.push(interaction.clone());
+            continue;
+        }
+
+        match open.get_mut(&key) {
+            Some((index, last)) if timing::saturating_elapsed(interaction.block_time, *last) <= max_block_gap => {
+                merged[*index].sol_amount += interaction.sol_amount;
+                merged[*index].is_early_entry |= interaction.is_early_entry;
+                *last = interaction.block_time;
+            }
+            _ => {
+                open.insert(key, (merged.len(), interaction.block_time));
+                merged.push(interaction.clone());
+            }
+        }
+    }
+
+    merged
+}
+
+/// Combine interaction lists from several providers, collapsing cross-source duplicates
+///
+/// Trades with the same wallet, token, `block_time`, side and `sol_amount`
+/// reported by different sources become one interaction (`is_early_entry`
+/// is OR-ed) in the position it was first seen. Repeats within one source
+/// are kept as separate trades, so a trade listed twice by one provider and
+/// once by another survives twice. Every output interaction's `source`
+/// lists the distinct tags that reported it, comma-separated in order of
+/// appearance; an interaction without a `source` is tagged with its source
+/// list's index (`"0"`, `"1"`, ...).
+pub fn merge_sources(sources: Vec<Vec<TokenInteraction>>) -> Vec<TokenInteraction> {
+    type TradeKey = (String, String, u64, TradeSide, u64);
+    let mut positions: HashMap<(TradeKey, usize), usize> = HashMap::new();
+    let mut merged: Vec<TokenInteraction> = Vec::new();
+
+    for (index, source) in sources.into_iter().enumerate() {
+        let mut occurrences: HashMap<TradeKey, usize> = HashMap::new();
+        for mut interaction in source {
+            let tag = interaction.source.take().unwrap_or_else(|| index.to_string());
+            let key = (
+                interaction.wallet_address.clone(),
+                interaction.token_mint.clone(),
+                interaction.block_time,
+                interaction.side,
+                interaction.sol_amount.to_bits(),
+            );
+            let occurrence = occurrences.entry(key.clone()).or_insert(0);
+            let nth = *occurrence;
+            *occurrence += 1;
+
+            match positions.get(&(key.clone(), nth)) {
+                Some(&position) => {
+                    let existing = &mut merged[position];
+                    existing.is_early_entry |= interaction.is_early_entry;
+                    let tags = existing.source.get_or_insert_with(String::new);
+                    if !tags.split(',').any(|t| t == tag) {
+                        tags.push(',');
+                        tags.push_str(&tag);
+                    }
+                }
+                None => {
+                    positions.insert((key, nth), merged.len());
+                    interaction.source = Some(tag);
+                    merged.push(interaction);
+                }
+            }
+        }
+    }
+
+    merged
+}
+
+/// Whether `filter_by_tokens` keeps or drops the listed tokens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterMode {
+    /// Keep only interactions with a listed token (whitelist)
+    Include,
+    /// Drop interactions with a listed token (blacklist)
+    Exclude,
+}
+
+/// Restrict interactions to, or away from, a set of token mints
+///
+/// Order is preserved. An empty set includes nothing and excludes nothing.
+pub fn filter_by_tokens(
+    interactions: &[TokenInteraction],
+    tokens: &HashSet<String>,
+    mode: FilterMode,
+) -> Vec<TokenInteraction> {
+    interactions
+        .iter()
+        .filter(|i| tokens.contains(&i.token_mint) == (mode == FilterMode::Include))
+        .cloned()
+        .collect()
+}
+
 /// Calculate statistics for multiple wallets in batch
+///
+/// With the `tracing` feature enabled the batch runs inside a
+/// `calculate_batch_stats` debug span (carrying the interaction count), emits
+/// a `debug!` event with the wallet count and elapsed time, and a `trace!`
+/// event per wallet score. Without the feature none of this is compiled in.
 pub fn calculate_batch_stats(
     interactions: &[TokenInteraction],
 ) -> HashMap<String, WalletStats> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("calculate_batch_stats", interactions = interactions.len()).entered();
+    #[cfg(feature = "tracing")]
+    let started = std::time::Instant::now();
+
     let grouped = group_by_wallet(interactions);
     let mut stats_map = HashMap::new();
 
     for (address, wallet_interactions) in grouped {
         let stats = process_interactions(&wallet_interactions);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(wallet = %address, score = calculate_whale_score(&stats), "wallet scored");
         stats_map.insert(address, stats);
     }
 
+    #[cfg(feature = "tracing")]
+    tracing::debug!(wallets = stats_map.len(), elapsed = ?started.elapsed(), "batch scored");
+
     stats_map
 }
 
+/// Calculate statistics for every wallet's activity in each token separately
+///
+/// Like `calculate_batch_stats`, but keyed by `(wallet_address, token_mint)`,
+/// so a wallet that whales on one token and dabbles in others gets a
+/// separate `WalletStats` per token.
+pub fn calculate_wallet_token_stats(
+    interactions: &[TokenInteraction],
+) -> HashMap<(String, String), WalletStats> {
+    group_by_wallet_token(interactions)
+        .into_iter()
+        .map(|(key, pair_interactions)| (key, process_interactions(&pair_interactions)))
+        .collect()
+}
+
+/// Score every wallet in a batch, preserving first-appearance order
+///
+/// Returns one `(address, whale_score)` entry per unique wallet, ordered by
+/// the position of that wallet's first interaction in the input.
+pub fn score_interactions_ordered(interactions: &[TokenInteraction]) -> Vec<(String, u8)> {
+    let mut order: Vec<String> = Vec::new();
+    let mut grouped: HashMap<String, Vec<TokenInteraction>> = HashMap::new();
+
+    for interaction in interactions {
+        let entry = grouped.entry(interaction.wallet_address.clone()).or_insert_with(|| {
+            order.push(interaction.wallet_address.clone());
+            Vec::new()
+        });
+        entry.push(interaction.clone());
+    }
+
+    order
+        .into_iter()
+        .map(|address| {
+            let stats = process_interactions(&grouped[&address]);
+            let score = calculate_whale_score(&stats);
+            (address, score)
+        })
+        .collect()
+}
+
+/// The `n` highest-scoring wallets in a batch
+///
+/// Runs `calculate_batch_stats` and scores each wallet; results are sorted by
+/// score descending, with ties broken by address ascending so the output is
+/// deterministic.
+pub fn top_whales(interactions: &[TokenInteraction], n: usize) -> Vec<(String, u8)> {
+    let mut scores: Vec<(String, u8)> = calculate_batch_stats(interactions)
+        .into_iter()
+        .map(|(address, stats)| {
+            let score = calculate_whale_score(&stats);
+            (address, score)
+        })
+        .collect();
+
+    scores.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    scores.truncate(n);
+    scores
+}
+
+/// The `k` highest-scoring wallets in a stats map, without sorting all of them
+///
+/// Keeps a bounded heap of the best `k` seen so far, so it runs in
+/// O(n log k) time and O(k) extra memory. Ordering matches `top_whales`:
+/// score descending, ties broken by address ascending.
+pub fn top_k_whales(stats_map: &HashMap<String, WalletStats>, k: usize) -> Vec<(String, u8)> {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    if k == 0 {
+        return Vec::new();
+    }
+
+    // Min-heap on (score, reversed address): the root is the worst kept wallet
+    let mut heap: BinaryHeap<Reverse<(u8, Reverse<&String>)>> = BinaryHeap::with_capacity(k + 1);
+    for (address, stats) in stats_map {
+        heap.push(Reverse((calculate_whale_score(stats), Reverse(address))));
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((score, Reverse(address)))| (address.clone(), score))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,6 +1065,8 @@ mod tests {
             average_entry_size: 10.0,
             early_entry_count: 5,
             winrate_proxy: 0.8,
+            avg_realized_multiple: None,
+            net_volume_sol: 100.0,
         };
 
         let score = calculate_whale_score(&stats);
@@ -218,6 +1074,79 @@ mod tests {
         assert!(score > 0);
     }
 
+    #[test]
+    fn test_combined_signal() {
+        let stats = WalletStats {
+            address: "addr1".to_string(),
+            total_volume_sol: 2500.0,
+            interaction_count: 50,
+            average_entry_size: 50.0,
+            early_entry_count: 10,
+            winrate_proxy: 0.3,
+            avg_realized_multiple: None,
+            net_volume_sol: 2500.0,
+        };
+
+        // Big, active wallet that rarely enters early enough to look like an insider
+        let signal = combined_signal(&stats, 500.0, 20);
+        assert_eq!(signal.whale_score, 77);
+        assert_eq!(signal.insider_confidence, 14);
+        assert_eq!(signal.blended, 32);
+
+        let both_high = combined_signal(&stats, 10.0, 5);
+        assert!(both_high.blended > signal.blended);
+        assert!(both_high.blended <= both_high.whale_score.max(both_high.insider_confidence));
+    }
+
+    #[test]
+    fn test_algorithm_version() {
+        assert!(!algorithm_version().is_empty());
+        assert_eq!(algorithm_version(), "1.0.0");
+
+        let stats = WalletStats::empty("addr1");
+        let result = ScoredResult::new(&stats);
+        assert_eq!(result.score, calculate_whale_score(&stats));
+        assert!(result.is_current());
+        let stale = ScoredResult { algorithm_version: "0.9.0".to_string(), ..result };
+        assert!(!stale.is_current());
+    }
+
+    #[test]
+    fn test_normalize_edge_cases() {
+        assert_eq!(normalize(25.0, 50.0, 20.0), 10.0);
+        assert_eq!(normalize(500.0, 50.0, 20.0), 20.0);
+        assert_eq!(normalize(f64::INFINITY, 50.0, 20.0), 20.0);
+        assert_eq!(normalize(-5.0, 50.0, 20.0), 0.0);
+        assert_eq!(normalize(f64::NAN, 50.0, 20.0), 0.0);
+        assert_eq!(normalize(5.0, 0.0, 20.0), 0.0);
+        assert_eq!(normalize(5.0, -1.0, 20.0), 0.0);
+        assert_eq!(normalize(5.0, f64::NAN, 20.0), 0.0);
+        assert_eq!(normalize(5.0, 50.0, -20.0), 0.0);
+    }
+
+    #[test]
+    fn test_batch_insider_confidence() {
+        let wallet = |early_entry_count: u64, average_entry_size: f64| WalletStats {
+            address: String::new(),
+            total_volume_sol: average_entry_size * 10.0,
+            interaction_count: 10,
+            average_entry_size,
+            early_entry_count,
+            winrate_proxy: 0.5,
+            avg_realized_multiple: None,
+            net_volume_sol: average_entry_size * 10.0,
+        };
+        let mut stats_map = HashMap::new();
+        stats_map.insert("insider".to_string(), wallet(8, 30.0));
+        stats_map.insert("tourist".to_string(), wallet(1, 2.0));
+
+        let confidence = batch_insider_confidence(&stats_map, 10.0, 3);
+        assert_eq!(confidence.len(), 2);
+        assert_eq!(confidence["insider"], calculate_insider_confidence(8, 10, 30.0, 10.0, 3));
+        assert_eq!(confidence["tourist"], calculate_insider_confidence(1, 10, 2.0, 10.0, 3));
+        assert!(confidence["insider"] > confidence["tourist"]);
+    }
+
     #[test]
     fn test_is_early_entry() {
         let creation_time = 1000;
@@ -237,6 +1166,8 @@ mod tests {
                 block_time: 1000,
                 sol_amount: 10.0,
                 is_early_entry: true,
+                side: TradeSide::Buy,
+                source: None,
             },
             TokenInteraction {
                 wallet_address: "addr1".to_string(),
@@ -244,6 +1175,8 @@ mod tests {
                 block_time: 2000,
                 sol_amount: 20.0,
                 is_early_entry: false,
+                side: TradeSide::Buy,
+                source: None,
             },
         ];
 
@@ -252,6 +1185,635 @@ mod tests {
         assert_eq!(stats.total_volume_sol, 30.0);
         assert_eq!(stats.average_entry_size, 15.0);
         assert_eq!(stats.early_entry_count, 1);
+        assert_eq!(stats.net_volume_sol, 30.0);
     }
-}
 
+    #[test]
+    fn test_process_interactions_net_volume() {
+        let trade = |sol_amount: f64, side: TradeSide| TokenInteraction {
+            wallet_address: "addr1".to_string(),
+            token_mint: "token1".to_string(),
+            block_time: 1000,
+            sol_amount,
+            is_early_entry: false,
+            side,
+            source: None,
+        };
+        let interactions = vec![
+            trade(10.0, TradeSide::Buy),
+            trade(25.0, TradeSide::Buy),
+            trade(15.0, TradeSide::Sell),
+        ];
+
+        let stats = process_interactions(&interactions);
+        assert_eq!(stats.net_volume_sol, 20.0);
+        assert_eq!(stats.total_volume_sol, 50.0);
+        assert_eq!(stats.interaction_count, 3);
+    }
+
+    #[test]
+    fn test_from_interactions_matches_process_interactions() {
+        let interactions = vec![
+            TokenInteraction {
+                wallet_address: "addr1".to_string(),
+                token_mint: "token1".to_string(),
+                block_time: 1000,
+                sol_amount: 12.5,
+                is_early_entry: true,
+                side: TradeSide::Buy,
+                source: None,
+            },
+            TokenInteraction {
+                wallet_address: "addr1".to_string(),
+                token_mint: "token2".to_string(),
+                block_time: 1100,
+                sol_amount: 4.0,
+                is_early_entry: false,
+                side: TradeSide::Sell,
+                source: None,
+            },
+        ];
+
+        let converted = WalletStats::from(interactions.as_slice());
+        assert_eq!(format!("{:?}", converted), format!("{:?}", process_interactions(&interactions)));
+
+        let empty = WalletStats::empty("addr2");
+        assert_eq!(empty.address, "addr2");
+        assert_eq!(empty.interaction_count, 0);
+        assert_eq!(calculate_whale_score(&empty), 0);
+    }
+
+    #[test]
+    fn test_filter_by_tokens() {
+        let interaction = |mint: &str, amount: f64| TokenInteraction {
+            wallet_address: "addr1".to_string(),
+            token_mint: mint.to_string(),
+            block_time: 1000,
+            sol_amount: amount,
+            is_early_entry: false,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        let interactions = vec![interaction("defi", 10.0), interaction("meme", 1.0), interaction("defi", 5.0)];
+        let sector: HashSet<String> = ["defi".to_string()].into_iter().collect();
+
+        let included = filter_by_tokens(&interactions, &sector, FilterMode::Include);
+        assert_eq!(included.len(), 2);
+        assert_eq!(process_interactions(&included).total_volume_sol, 15.0);
+        let excluded = filter_by_tokens(&interactions, &sector, FilterMode::Exclude);
+        assert_eq!(excluded.len(), 1);
+        assert_eq!(excluded[0].token_mint, "meme");
+
+        let none = HashSet::new();
+        assert!(filter_by_tokens(&interactions, &none, FilterMode::Include).is_empty());
+        assert_eq!(filter_by_tokens(&interactions, &none, FilterMode::Exclude).len(), 3);
+    }
+
+    #[test]
+    fn test_dedup_interactions() {
+        let interaction = |wallet: &str, block_time: u64, amount: f64, early: bool| TokenInteraction {
+            wallet_address: wallet.to_string(),
+            token_mint: "token1".to_string(),
+            block_time,
+            sol_amount: amount,
+            is_early_entry: early,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        let interactions = vec![
+            interaction("a", 1000, 2.0, false),
+            interaction("b", 1000, 1.0, false),
+            interaction("a", 1000, 3.5, true),
+            interaction("a", 1010, 4.0, false),
+            TokenInteraction { side: TradeSide::Sell, ..interaction("a", 1010, 1.0, false) },
+        ];
+
+        let deduped = dedup_interactions(&interactions);
+        assert_eq!(deduped.len(), 4);
+        assert_eq!(deduped[0].wallet_address, "a");
+        assert_eq!(deduped[0].sol_amount, 5.5);
+        assert!(deduped[0].is_early_entry);
+        assert_eq!(deduped[1].wallet_address, "b");
+        assert_eq!(deduped[3].side, TradeSide::Sell);
+    }
+
+    #[test]
+    fn test_wallet_stats_merge_matches_combined_processing() {
+        let trade = |amount: f64, early: bool, side: TradeSide| TokenInteraction {
+            wallet_address: "addr1".to_string(),
+            token_mint: "token1".to_string(),
+            block_time: 1000,
+            sol_amount: amount,
+            is_early_entry: early,
+            side,
+            source: None,
+        };
+        let first = vec![trade(10.0, true, TradeSide::Buy), trade(4.0, false, TradeSide::Sell)];
+        let second = vec![trade(1.0, false, TradeSide::Buy), trade(7.0, true, TradeSide::Buy), trade(2.0, false, TradeSide::Buy)];
+        let all: Vec<TokenInteraction> = first.iter().chain(&second).cloned().collect();
+
+        let merged = process_interactions(&first).merge(&process_interactions(&second));
+        let expected = process_interactions(&all);
+        assert_eq!(merged.address, expected.address);
+        assert_eq!(merged.total_volume_sol, expected.total_volume_sol);
+        assert_eq!(merged.net_volume_sol, expected.net_volume_sol);
+        assert_eq!(merged.interaction_count, expected.interaction_count);
+        assert_eq!(merged.early_entry_count, expected.early_entry_count);
+        assert!((merged.average_entry_size - expected.average_entry_size).abs() < 1e-9);
+        assert!((merged.winrate_proxy - expected.winrate_proxy).abs() < 1e-9);
+
+        let with_multiples = WalletStats { avg_realized_multiple: Some(2.0), ..process_interactions(&first) };
+        let other = WalletStats { avg_realized_multiple: Some(8.0), ..process_interactions(&first) };
+        assert!((with_multiples.merge(&other).avg_realized_multiple.unwrap() - 4.0).abs() < 1e-9);
+        assert_eq!(WalletStats::empty("").merge(&expected).address, "addr1");
+        assert_eq!(expected.merge(&WalletStats::empty("")).interaction_count, 5);
+    }
+
+    #[test]
+    fn test_merge_split_orders() {
+        let trade = |wallet: &str, mint: &str, block_time: u64, amount: f64| TokenInteraction {
+            wallet_address: wallet.to_string(),
+            token_mint: mint.to_string(),
+            block_time,
+            sol_amount: amount,
+            is_early_entry: false,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        // One 100 SOL order split across four blocks, 1-2 seconds apart
+        let split = vec![
+            trade("a", "t1", 1002, 25.0),
+            trade("a", "t1", 1000, 25.0),
+            TokenInteraction { is_early_entry: true, ..trade("a", "t1", 1003, 25.0) },
+            trade("a", "t1", 1005, 25.0),
+            trade("b", "t1", 1001, 3.0),
+        ];
+        let merged = merge_split_orders(&split, 2);
+        assert_eq!(merged.len(), 2);
+        assert_eq!((merged[0].wallet_address.as_str(), merged[0].block_time), ("a", 1000));
+        assert_eq!(merged[0].sol_amount, 100.0);
+        assert!(merged[0].is_early_entry);
+        assert_eq!(merged[1].wallet_address, "b");
+        assert_eq!(process_interactions(&merged[..1]).average_entry_size, 100.0);
+
+        // Far apart, a different token, or separated by a sell
+        let separate = vec![
+            trade("a", "t1", 1000, 5.0),
+            trade("a", "t1", 1600, 5.0),
+            trade("a", "t2", 1601, 5.0),
+            TokenInteraction { side: TradeSide::Sell, ..trade("a", "t1", 1602, 5.0) },
+            trade("a", "t1", 1603, 5.0),
+        ];
+        assert_eq!(merge_split_orders(&separate, 10).len(), 5);
+        assert!(merge_split_orders(&[], 10).is_empty());
+    }
+
+    #[test]
+    fn test_score_interactions_ordered() {
+        let interaction = |wallet: &str, amount: f64, early: bool| TokenInteraction {
+            wallet_address: wallet.to_string(),
+            token_mint: "token1".to_string(),
+            block_time: 1000,
+            sol_amount: amount,
+            is_early_entry: early,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        let interactions = vec![
+            interaction("zeta", 40.0, true),
+            interaction("alpha", 1.0, false),
+            interaction("zeta", 30.0, true),
+            interaction("mid", 5.0, true),
+            interaction("alpha", 2.0, false),
+        ];
+
+        let scores = score_interactions_ordered(&interactions);
+        let addresses: Vec<&str> = scores.iter().map(|(a, _)| a.as_str()).collect();
+        assert_eq!(addresses, vec!["zeta", "alpha", "mid"]);
+
+        let batch = calculate_batch_stats(&interactions);
+        for (address, score) in &scores {
+            assert_eq!(*score, calculate_whale_score(&batch[address]));
+        }
+    }
+
+    #[test]
+    fn test_calculate_wallet_token_stats() {
+        let interaction = |wallet: &str, mint: &str, amount: f64| TokenInteraction {
+            wallet_address: wallet.to_string(),
+            token_mint: mint.to_string(),
+            block_time: 1000,
+            sol_amount: amount,
+            is_early_entry: false,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        let interactions = vec![
+            interaction("a", "t1", 40.0),
+            interaction("a", "t2", 1.0),
+            interaction("a", "t1", 60.0),
+            interaction("b", "t1", 5.0),
+        ];
+
+        let key = |wallet: &str, mint: &str| (wallet.to_string(), mint.to_string());
+        let grouped = group_by_wallet_token(&interactions);
+        assert_eq!(grouped.len(), 3);
+        assert_eq!(grouped[&key("a", "t1")].len(), 2);
+
+        let stats = calculate_wallet_token_stats(&interactions);
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats[&key("a", "t1")].total_volume_sol, 100.0);
+        assert_eq!(stats[&key("a", "t1")].interaction_count, 2);
+        assert_eq!(stats[&key("a", "t2")].total_volume_sol, 1.0);
+        assert_eq!(stats[&key("b", "t1")].address, "b");
+    }
+
+    #[test]
+    fn test_top_k_whales_matches_full_sort() {
+        let stats = |address: &str, interaction_count: u64, volume: f64| WalletStats {
+            address: address.to_string(),
+            total_volume_sol: volume,
+            interaction_count,
+            average_entry_size: volume / interaction_count as f64,
+            early_entry_count: interaction_count / 2,
+            winrate_proxy: 0.5,
+            avg_realized_multiple: None,
+            net_volume_sol: volume,
+        };
+        let stats_map: HashMap<String, WalletStats> = [
+            stats("e", 10, 100.0),
+            stats("b", 40, 2000.0),
+            stats("d", 10, 100.0),
+            stats("a", 2, 1.0),
+            stats("c", 25, 600.0),
+            stats("f", 10, 100.0),
+        ]
+        .into_iter()
+        .map(|s| (s.address.clone(), s))
+        .collect();
+
+        let mut full: Vec<(String, u8)> = stats_map
+            .iter()
+            .map(|(address, s)| (address.clone(), calculate_whale_score(s)))
+            .collect();
+        full.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        for k in 0..=8 {
+            assert_eq!(top_k_whales(&stats_map, k), full[..k.min(full.len())].to_vec());
+        }
+        // "d", "e" and "f" tie; the cut keeps the smallest addresses
+        let top = top_k_whales(&stats_map, 3);
+        assert_eq!(top[2].0, "d");
+    }
+
+    #[test]
+    fn test_merge_sources_collapses_cross_source_duplicates() {
+        let trade = |wallet: &str, block_time: u64, amount: f64, source: Option<&str>| TokenInteraction {
+            wallet_address: wallet.to_string(),
+            token_mint: "token1".to_string(),
+            block_time,
+            sol_amount: amount,
+            is_early_entry: false,
+            side: TradeSide::Buy,
+            source: source.map(str::to_string),
+        };
+        let helius = vec![
+            trade("a", 1000, 5.0, Some("helius")),
+            trade("b", 1001, 2.0, Some("helius")),
+            trade("b", 1001, 2.0, Some("helius")),
+        ];
+        let triton = vec![
+            TokenInteraction { is_early_entry: true, ..trade("a", 1000, 5.0, Some("triton")) },
+            trade("b", 1001, 2.0, Some("triton")),
+            trade("c", 1002, 1.0, Some("triton")),
+            // Same block, different amount: a distinct trade
+            trade("a", 1000, 5.5, Some("triton")),
+        ];
+        let untagged = vec![trade("c", 1002, 1.0, None)];
+
+        let merged = merge_sources(vec![helius, triton, untagged]);
+        let summary: Vec<(&str, f64, &str)> = merged
+            .iter()
+            .map(|i| (i.wallet_address.as_str(), i.sol_amount, i.source.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a", 5.0, "helius,triton"),
+                ("b", 2.0, "helius,triton"),
+                ("b", 2.0, "helius"),
+                ("c", 1.0, "triton,2"),
+                ("a", 5.5, "triton"),
+            ]
+        );
+        assert!(merged[0].is_early_entry);
+        assert!(merge_sources(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_top_whales() {
+        let interaction = |wallet: &str, amount: f64, early: bool| TokenInteraction {
+            wallet_address: wallet.to_string(),
+            token_mint: "token1".to_string(),
+            block_time: 1000,
+            sol_amount: amount,
+            is_early_entry: early,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        let interactions = vec![
+            interaction("small", 1.0, false),
+            interaction("big", 50.0, true),
+            interaction("mid", 10.0, true),
+            interaction("big", 50.0, true),
+            interaction("mid_twin", 10.0, true),
+        ];
+
+        let top = top_whales(&interactions, 3);
+        let addresses: Vec<&str> = top.iter().map(|(a, _)| a.as_str()).collect();
+        assert_eq!(addresses, vec!["big", "mid", "mid_twin"]);
+        assert!(top[0].1 > top[1].1);
+        assert_eq!(top[1].1, top[2].1);
+
+        assert_eq!(top_whales(&interactions, 10).len(), 4);
+        assert!(top_whales(&interactions, 0).is_empty());
+    }
+
+    #[test]
+    fn test_classify_entry_timing() {
+        assert_eq!(classify_entry_timing(999, 1000, 60), EntryTiming::PreCreation);
+        assert_eq!(classify_entry_timing(1000, 1000, 60), EntryTiming::Early);
+        assert_eq!(classify_entry_timing(1060, 1000, 60), EntryTiming::Early);
+        assert_eq!(classify_entry_timing(1061, 1000, 60), EntryTiming::Normal);
+        assert_eq!(classify_entry_timing(1000, 1000, 0), EntryTiming::Early);
+    }
+
+    #[test]
+    fn test_profit_component_uses_realized_multiple() {
+        let base = WalletStats {
+            address: "test".to_string(),
+            total_volume_sol: 100.0,
+            interaction_count: 10,
+            average_entry_size: 10.0,
+            early_entry_count: 5,
+            winrate_proxy: 0.5,
+            avg_realized_multiple: None,
+            net_volume_sol: 100.0,
+        };
+        let flat = WalletStats { avg_realized_multiple: Some(1.0), ..base.clone() };
+        let tripled = WalletStats { avg_realized_multiple: Some(3.0), ..base.clone() };
+
+        let fallback_profit = whale_score_components(&base)[3];
+        let flat_profit = whale_score_components(&flat)[3];
+        let tripled_profit = whale_score_components(&tripled)[3];
+
+        assert!((fallback_profit - 5.0).abs() < 1e-9);
+        assert!(flat_profit.abs() < 1e-9);
+        assert!((tripled_profit - 10.0 * 3f64.ln() / 10f64.ln()).abs() < 1e-9);
+
+        assert!(calculate_whale_score(&tripled) > calculate_whale_score(&flat));
+        assert_eq!(calculate_whale_score(&base), 35);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_batch_stats_emits_span_and_events() {
+        use std::fmt::Write as _;
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+        use tracing_subscriber::Layer;
+
+        #[derive(Default)]
+        struct Fields(String);
+
+        impl Visit for Fields {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                let _ = write!(self.0, "{}={:?} ", field.name(), value);
+            }
+        }
+
+        /// Records `(level, span or event name, fields)` for every span and event
+        #[derive(Clone, Default)]
+        struct Capture(Arc<Mutex<Vec<(tracing::Level, String, String)>>>);
+
+        impl<S: tracing::Subscriber> Layer<S> for Capture {
+            fn on_new_span(&self, attrs: &tracing::span::Attributes<'_>, _: &tracing::span::Id, _: Context<'_, S>) {
+                let mut fields = Fields::default();
+                attrs.record(&mut fields);
+                let metadata = attrs.metadata();
+                self.0.lock().unwrap().push((*metadata.level(), metadata.name().to_string(), fields.0));
+            }
+
+            fn on_event(&self, event: &tracing::Event<'_>, _: Context<'_, S>) {
+                let mut fields = Fields::default();
+                event.record(&mut fields);
+                self.0.lock().unwrap().push((*event.metadata().level(), "event".to_string(), fields.0));
+            }
+        }
+
+        let interactions = vec![TokenInteraction {
+            wallet_address: "addr1".to_string(),
+            token_mint: "token1".to_string(),
+            block_time: 1000,
+            sol_amount: 10.0,
+            is_early_entry: true,
+            side: TradeSide::Buy,
+            source: None,
+        }];
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        tracing::subscriber::with_default(subscriber, || calculate_batch_stats(&interactions));
+
+        let records = capture.0.lock().unwrap();
+        assert!(records.iter().any(|(level, name, fields)| {
+            *level == tracing::Level::DEBUG && name == "calculate_batch_stats" && fields.contains("interactions=1")
+        }));
+        assert!(records.iter().any(|(level, _, fields)| {
+            *level == tracing::Level::DEBUG && fields.contains("batch scored") && fields.contains("wallets=1")
+        }));
+        assert!(records.iter().any(|(level, _, fields)| {
+            *level == tracing::Level::TRACE && fields.contains("wallet scored") && fields.contains("wallet=addr1")
+        }));
+    }
+
+    #[test]
+    fn test_process_interactions_decayed_ancient_trades() {
+        let ancient = vec![TokenInteraction {
+            wallet_address: "addr1".to_string(),
+            token_mint: "token1".to_string(),
+            block_time: 0,
+            sol_amount: 10.0,
+            is_early_entry: true,
+            side: TradeSide::Buy,
+            source: None,
+        }];
+
+        // 1.7e9 seconds at a one-second half-life: the weight underflows to 0
+        let stats = process_interactions_decayed(&ancient, 1_700_000_000, 1);
+        assert_eq!(stats.address, "addr1");
+        assert_eq!(stats.interaction_count, 0);
+        assert_eq!(stats.average_entry_size, 0.0);
+        assert_eq!(stats.winrate_proxy, 0.0);
+        assert!(validate_stats(&stats).is_ok());
+        assert_eq!(calculate_whale_score(&stats), 0);
+    }
+
+    #[test]
+    fn test_buy_size_histogram() {
+        let interactions: Vec<TokenInteraction> = [0.5, 1.0, 1.5, 5.0, 7.0, 10.0, 25.0, 100.0]
+            .iter()
+            .map(|&amount| TokenInteraction {
+                wallet_address: "addr1".to_string(),
+                token_mint: "token1".to_string(),
+                block_time: 1000,
+                sol_amount: amount,
+                is_early_entry: false,
+                side: TradeSide::Buy,
+                source: None,
+            })
+            .collect();
+
+        // Edges are inclusive upper bounds; 25 and 100 overflow
+        assert_eq!(buy_size_histogram(&interactions, &[1.0, 5.0, 10.0]), vec![2, 2, 2, 2]);
+        assert_eq!(buy_size_histogram(&interactions, &[]), vec![8]);
+        assert_eq!(buy_size_histogram(&[], &[1.0]), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_early_volume_ratio() {
+        let interaction = |amount: f64, early: bool| TokenInteraction {
+            wallet_address: "addr1".to_string(),
+            token_mint: "token1".to_string(),
+            block_time: 1000,
+            sol_amount: amount,
+            is_early_entry: early,
+            side: TradeSide::Buy,
+            source: None,
+        };
+
+        assert_eq!(early_volume_ratio(&[interaction(5.0, true), interaction(15.0, true)]), 1.0);
+        assert_eq!(early_volume_ratio(&[interaction(5.0, false), interaction(15.0, false)]), 0.0);
+        // One early entry out of four, but it carries 80% of the volume
+        let mixed = vec![interaction(80.0, true), interaction(5.0, false), interaction(10.0, false), interaction(5.0, false)];
+        assert!((early_volume_ratio(&mixed) - 0.8).abs() < 1e-9);
+        assert_eq!(early_volume_ratio(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_process_interactions_winsorized_caps_outlier() {
+        let interaction = |amount: f64, early: bool| TokenInteraction {
+            wallet_address: "addr1".to_string(),
+            token_mint: "token1".to_string(),
+            block_time: 1000,
+            sol_amount: amount,
+            is_early_entry: early,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        let mut interactions: Vec<TokenInteraction> = (1..=9).map(|i| interaction(i as f64, i % 2 == 0)).collect();
+        interactions.push(interaction(10_000.0, true));
+
+        let raw = process_interactions(&interactions);
+        let winsorized = process_interactions_winsorized(&interactions, 90.0);
+
+        assert!((raw.average_entry_size - 1004.5).abs() < 1e-9);
+        // The 10,000 SOL buy is capped at the 90th percentile amount, 9 SOL
+        assert!((winsorized.average_entry_size - 5.4).abs() < 1e-9);
+        assert_eq!(winsorized.interaction_count, raw.interaction_count);
+        assert_eq!(winsorized.early_entry_count, raw.early_entry_count);
+
+        let untouched = process_interactions_winsorized(&interactions, 100.0);
+        assert_eq!(untouched.average_entry_size, raw.average_entry_size);
+        assert_eq!(process_interactions_winsorized(&[], 90.0).interaction_count, 0);
+    }
+
+    #[test]
+    fn test_process_interactions_min_excludes_dust() {
+        let interaction = |amount: f64, early: bool| TokenInteraction {
+            wallet_address: "addr1".to_string(),
+            token_mint: "token1".to_string(),
+            block_time: 1000,
+            sol_amount: amount,
+            is_early_entry: early,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        let interactions = vec![
+            interaction(10.0, true),
+            interaction(0.001, true),
+            interaction(0.002, false),
+            interaction(20.0, false),
+        ];
+
+        let raw = process_interactions(&interactions);
+        let filtered = process_interactions_min(&interactions, 0.01);
+
+        assert_eq!(raw.interaction_count, 4);
+        assert_eq!(filtered.interaction_count, 2);
+        assert_eq!(filtered.early_entry_count, 1);
+        assert_eq!(filtered.average_entry_size, 15.0);
+        assert!(raw.average_entry_size < filtered.average_entry_size);
+
+        let all_dust = process_interactions_min(&interactions[1..3], 0.01);
+        assert_eq!(all_dust.interaction_count, 0);
+        assert_eq!(all_dust.address, "addr1");
+    }
+
+    #[test]
+    fn test_process_entity_combines_wallets() {
+        let interaction = |wallet: &str, amount: f64, early: bool| TokenInteraction {
+            wallet_address: wallet.to_string(),
+            token_mint: "token1".to_string(),
+            block_time: 1000,
+            sol_amount: amount,
+            is_early_entry: early,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        let interactions = vec![
+            interaction("a", 10.0, true),
+            interaction("b", 20.0, false),
+            interaction("outsider", 500.0, true),
+            interaction("b", 30.0, true),
+        ];
+        let addresses: HashSet<String> = ["a", "b"].iter().map(|s| s.to_string()).collect();
+
+        let entity = process_entity(&interactions, &addresses);
+        let batch = calculate_batch_stats(&interactions);
+        let (a, b) = (&batch["a"], &batch["b"]);
+
+        assert_eq!(entity.total_volume_sol, a.total_volume_sol + b.total_volume_sol);
+        assert_eq!(entity.interaction_count, a.interaction_count + b.interaction_count);
+        assert_eq!(entity.early_entry_count, a.early_entry_count + b.early_entry_count);
+        assert_eq!(entity.average_entry_size, 20.0);
+        assert!(entity.address.starts_with("entity:"));
+
+        let reordered: HashSet<String> = ["b", "a"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(process_entity(&interactions, &reordered).address, entity.address);
+    }
+
+    #[test]
+    fn test_inconsistent_early_count_is_clamped_and_flagged() {
+        let consistent = WalletStats {
+            address: "test".to_string(),
+            total_volume_sol: 50.0,
+            interaction_count: 5,
+            average_entry_size: 10.0,
+            early_entry_count: 5,
+            winrate_proxy: 0.5,
+            avg_realized_multiple: None,
+            net_volume_sol: 50.0,
+        };
+        let inconsistent = WalletStats { early_entry_count: 15, ..consistent.clone() };
+
+        assert_eq!(calculate_whale_score_flagged(&consistent), (calculate_whale_score(&consistent), false));
+        let (score, flagged) = calculate_whale_score_flagged(&inconsistent);
+        assert!(flagged);
+        assert_eq!(score, calculate_whale_score(&consistent));
+        assert_eq!(
+            DynamicScorer::default().calculate_score(&inconsistent),
+            DynamicScorer::default().calculate_score(&consistent)
+        );
+    }
+}
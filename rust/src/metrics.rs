@@ -0,0 +1,991 @@
+//! Market-structure metrics over whole cohorts of wallets
+//! Complements the per-wallet scoring with distribution-level views
+
+use crate::{calculate_whale_score, clamped_early_count, signed_amount, TokenInteraction, TradeSide, WalletStats, WhaleTier};
+use crate::timing::saturating_elapsed;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+/// Gini coefficient of `total_volume_sol` across wallets
+///
+/// 0.0 means every wallet bought the same volume, values approaching 1.0 mean
+/// a single wallet holds nearly all of it. Empty input, a single wallet and a
+/// cohort with zero total volume all return 0.0.
+pub fn volume_gini(stats: &[WalletStats]) -> f64 {
+    if stats.len() < 2 {
+        return 0.0;
+    }
+
+    let mut volumes: Vec<f64> = stats.iter().map(|s| s.total_volume_sol.max(0.0)).collect();
+    volumes.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let total: f64 = volumes.iter().sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+
+    let n = volumes.len() as f64;
+    let weighted: f64 = volumes
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (i as f64 + 1.0) * v)
+        .sum();
+
+    (2.0 * weighted) / (n * total) - (n + 1.0) / n
+}
+
+/// Herfindahl-Hirschman index of per-wallet buy volume shares in one token
+///
+/// Sums the squared share of the token's buy volume from each wallet, giving
+/// a value in `(0, 1]`: 1.0 when a single wallet did all the buying, `1 / n`
+/// when `n` wallets split it evenly. Sells are ignored so a wallet that
+/// dumps its position isn't counted twice. Returns 0.0 for empty input or
+/// zero buy volume.
+pub fn holder_concentration(interactions_for_token: &[TokenInteraction]) -> f64 {
+    let mut volumes: HashMap<&str, f64> = HashMap::new();
+    for interaction in interactions_for_token.iter().filter(|i| i.side == TradeSide::Buy) {
+        *volumes.entry(interaction.wallet_address.as_str()).or_insert(0.0) += interaction.sol_amount;
+    }
+
+    let total: f64 = volumes.values().sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+
+    volumes.values().map(|v| (v / total).powi(2)).sum()
+}
+
+/// Buy-volume-weighted average whale score (0-100) of a token's buyers
+///
+/// Each buyer's score comes from its entry in `stats_map` (normally the
+/// wallet's stats across all tokens) and is weighted by how much SOL it put
+/// into this token, so a token that whales bought heavily ranks above one
+/// they only touched. Sells and buyers missing from `stats_map` are
+/// ignored; returns 0 when no scored buy volume remains.
+pub fn token_interest_score(
+    interactions_for_token: &[TokenInteraction],
+    stats_map: &HashMap<String, WalletStats>,
+) -> u8 {
+    let mut volumes: HashMap<&str, f64> = HashMap::new();
+    for interaction in interactions_for_token.iter().filter(|i| i.side == TradeSide::Buy) {
+        *volumes.entry(interaction.wallet_address.as_str()).or_insert(0.0) += interaction.sol_amount;
+    }
+
+    let (weighted, total) = volumes
+        .iter()
+        .filter_map(|(address, &volume)| Some((calculate_whale_score(stats_map.get(*address)?), volume)))
+        .fold((0.0, 0.0), |(weighted, total), (score, volume)| {
+            (weighted + score as f64 * volume, total + volume)
+        });
+    if total <= 0.0 {
+        return 0;
+    }
+
+    (weighted / total).round().min(100.0) as u8
+}
+
+/// A token's first `n` buyers as `(address, seconds after launch, sol_amount)`
+///
+/// Each wallet appears once, with its earliest buy of the token (the larger
+/// one if it bought twice in that block). Wallets are ordered by that buy's
+/// `block_time`, larger buys first at equal times, then by address. Buys
+/// before `creation_time` report 0 seconds; sells are ignored.
+pub fn token_early_leaderboard(
+    interactions_for_token: &[TokenInteraction],
+    creation_time: u64,
+    n: usize,
+) -> Vec<(String, u64, f64)> {
+    let mut first_buys: HashMap<&str, (u64, f64)> = HashMap::new();
+    for interaction in interactions_for_token.iter().filter(|i| i.side == TradeSide::Buy) {
+        let candidate = (interaction.block_time, interaction.sol_amount);
+        first_buys
+            .entry(interaction.wallet_address.as_str())
+            .and_modify(|best| {
+                if candidate.0 < best.0 || (candidate.0 == best.0 && candidate.1 > best.1) {
+                    *best = candidate;
+                }
+            })
+            .or_insert(candidate);
+    }
+
+    let mut leaderboard: Vec<(&str, u64, f64)> =
+        first_buys.into_iter().map(|(address, (time, amount))| (address, time, amount)).collect();
+    leaderboard.sort_by(|a, b| a.1.cmp(&b.1).then(b.2.total_cmp(&a.2)).then(a.0.cmp(b.0)));
+
+    leaderboard
+        .into_iter()
+        .take(n)
+        .map(|(address, time, amount)| (address.to_string(), saturating_elapsed(time, creation_time), amount))
+        .collect()
+}
+
+/// Net SOL flow per token across all wallets (buys minus sells)
+///
+/// Positive values mean the market is accumulating the token, negative that
+/// it is being distributed.
+pub fn token_net_flow(interactions: &[TokenInteraction]) -> HashMap<String, f64> {
+    let mut flows: HashMap<String, f64> = HashMap::new();
+    for interaction in interactions {
+        *flows.entry(interaction.token_mint.clone()).or_insert(0.0) += signed_amount(interaction);
+    }
+    flows
+}
+
+/// Activity in one time bucket of a token's history
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Candle {
+    /// Bucket start, a multiple of the bucket width
+    pub start_time: u64,
+    /// SOL traded in the bucket, buys and sells alike
+    pub total_volume: f64,
+    pub buy_count: u64,
+    /// Distinct wallets that traded in the bucket
+    pub unique_wallets: usize,
+    /// Largest single buy in SOL (0.0 without buys)
+    pub max_single_buy: f64,
+}
+
+/// Bucket a token's interactions into a time series of volume candles
+///
+/// Buckets are `bucket_seconds` (at least 1) wide and aligned to multiples of
+/// it, so candles from different tokens line up. Buckets with no
+/// interactions are skipped rather than emitted as zero-volume candles; a
+/// front end can gap-fill from `start_time`. Candles are in time order.
+pub fn volume_candles(interactions_for_token: &[TokenInteraction], bucket_seconds: u64) -> Vec<Candle> {
+    let bucket_seconds = bucket_seconds.max(1);
+    let mut buckets: BTreeMap<u64, Vec<&TokenInteraction>> = BTreeMap::new();
+    for interaction in interactions_for_token {
+        let start = interaction.block_time / bucket_seconds * bucket_seconds;
+        buckets.entry(start).or_default().push(interaction);
+    }
+
+    buckets
+        .into_iter()
+        .map(|(start_time, interactions)| {
+            let buys = interactions.iter().filter(|i| i.side == TradeSide::Buy);
+            Candle {
+                start_time,
+                total_volume: interactions.iter().map(|i| i.sol_amount).sum(),
+                buy_count: buys.clone().count() as u64,
+                unique_wallets: interactions.iter().map(|i| i.wallet_address.as_str()).collect::<HashSet<_>>().len(),
+                max_single_buy: buys.map(|i| i.sol_amount).fold(0.0, f64::max),
+            }
+        })
+        .collect()
+}
+
+/// Z-score of each wallet's whale score within the cohort
+///
+/// Uses the population standard deviation, so the returned values have mean 0
+/// and standard deviation 1. When every wallet has the same score (including a
+/// single-wallet cohort) everyone gets 0.0.
+pub fn relative_scores(stats_map: &HashMap<String, WalletStats>) -> HashMap<String, f64> {
+    let scores: Vec<(&String, f64)> = stats_map
+        .iter()
+        .map(|(address, stats)| (address, calculate_whale_score(stats) as f64))
+        .collect();
+
+    if scores.is_empty() {
+        return HashMap::new();
+    }
+
+    let n = scores.len() as f64;
+    let mean = scores.iter().map(|(_, s)| s).sum::<f64>() / n;
+    let variance = scores.iter().map(|(_, s)| (s - mean).powi(2)).sum::<f64>() / n;
+    let std_dev = variance.sqrt();
+
+    scores
+        .into_iter()
+        .map(|(address, score)| {
+            let z = if std_dev > 0.0 { (score - mean) / std_dev } else { 0.0 };
+            (address.clone(), z)
+        })
+        .collect()
+}
+
+/// Per-wallet features compared by `behavioral_outlier_score`
+///
+/// Magnitudes are log-scaled so a whale's volume doesn't swamp the ratios.
+fn outlier_features(stats: &WalletStats) -> [f64; 5] {
+    let early_ratio = if stats.interaction_count > 0 {
+        clamped_early_count(stats).0 as f64 / stats.interaction_count as f64
+    } else {
+        0.0
+    };
+    [
+        stats.total_volume_sol.max(0.0).ln_1p(),
+        stats.average_entry_size.max(0.0).ln_1p(),
+        (stats.interaction_count as f64).ln_1p(),
+        early_ratio,
+        stats.winrate_proxy,
+    ]
+}
+
+/// 0-100 score for how far a wallet's behavior sits from its cohort's
+///
+/// Compares log volume, log average entry size, log interaction count,
+/// early entry ratio and winrate. Each feature's deviation from the cohort
+/// mean is divided by the cohort's standard deviation (a diagonal
+/// Mahalanobis distance) and the root mean square `r` of those z-scores is
+/// mapped linearly: `r <= 1` (typical) scores 0 and `r >= 4` scores 100.
+/// The wallet's own entry (by address) is left out of the cohort so an
+/// extreme wallet can't dilute its own distance. Features without spread
+/// across the cohort are skipped; with fewer than two other wallets, or no
+/// feature with spread, the score is 0.0.
+pub fn behavioral_outlier_score(stats: &WalletStats, cohort: &HashMap<String, WalletStats>) -> f64 {
+    let peers: Vec<[f64; 5]> = cohort
+        .iter()
+        .filter(|(address, _)| **address != stats.address)
+        .map(|(_, peer)| outlier_features(peer))
+        .collect();
+    if peers.len() < 2 {
+        return 0.0;
+    }
+
+    let n = peers.len() as f64;
+    let features = outlier_features(stats);
+    let mut squared_z = 0.0;
+    let mut used = 0;
+    for (k, &value) in features.iter().enumerate() {
+        let mean = peers.iter().map(|p| p[k]).sum::<f64>() / n;
+        let std_dev = (peers.iter().map(|p| (p[k] - mean).powi(2)).sum::<f64>() / n).sqrt();
+        if std_dev > 1e-12 {
+            squared_z += ((value - mean) / std_dev).powi(2);
+            used += 1;
+        }
+    }
+    if used == 0 {
+        return 0.0;
+    }
+
+    let rms = (squared_z / used as f64).sqrt();
+    ((rms - 1.0) / 3.0 * 100.0).clamp(0.0, 100.0)
+}
+
+/// `cohort_score_ema` smoothing factor outside `(0, 1]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidAlphaError(pub f64);
+
+impl fmt::Display for InvalidAlphaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "alpha must be within (0, 1], got {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidAlphaError {}
+
+/// Exponential moving average of the mean cohort score across batches
+///
+/// Feed each batch's scores together with the EMA returned for the previous
+/// batch: the result is `alpha * mean + (1 - alpha) * previous`, or just the
+/// batch mean for the first batch (`previous_ema` of `None`). A rising EMA
+/// means whale activity across the market is picking up. An empty batch
+/// leaves the EMA unchanged (0.0 before any batch had scores). `alpha` must
+/// be in `(0, 1]`; 1 ignores history entirely.
+pub fn cohort_score_ema(previous_ema: Option<f64>, current_scores: &[u8], alpha: f64) -> Result<f64, InvalidAlphaError> {
+    if !(alpha > 0.0 && alpha <= 1.0) {
+        return Err(InvalidAlphaError(alpha));
+    }
+    if current_scores.is_empty() {
+        return Ok(previous_ema.unwrap_or(0.0));
+    }
+
+    let mean = current_scores.iter().map(|&s| s as f64).sum::<f64>() / current_scores.len() as f64;
+    Ok(match previous_ema {
+        Some(previous) => alpha * mean + (1.0 - alpha) * previous,
+        None => mean,
+    })
+}
+
+/// Fraction (0-1) of the cohort whose whale score is at or below `score`
+///
+/// Places one wallet without ranking everyone: the wallet doesn't have to
+/// be in `stats_map`, and if it is, it counts towards the cohort like any
+/// other entry (so the top scorer gets 1.0). Returns 0.0 for an empty
+/// cohort.
+pub fn score_percentile(score: u8, stats_map: &HashMap<String, WalletStats>) -> f64 {
+    if stats_map.is_empty() {
+        return 0.0;
+    }
+
+    let at_or_below = stats_map.values().filter(|stats| calculate_whale_score(stats) <= score).count();
+    at_or_below as f64 / stats_map.len() as f64
+}
+
+/// Number of wallets in each `WhaleTier`
+///
+/// Every tier is present as a key, with 0 when no wallet falls into it, so
+/// a summary chart always has all four bars.
+pub fn tier_distribution(stats_map: &HashMap<String, WalletStats>) -> HashMap<WhaleTier, usize> {
+    let mut counts: HashMap<WhaleTier, usize> = WhaleTier::ALL.iter().map(|&tier| (tier, 0)).collect();
+    for stats in stats_map.values() {
+        *counts.entry(WhaleTier::from_score(calculate_whale_score(stats))).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Wallets and volume in one `score_deciles` bucket
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecileStat {
+    pub wallet_count: usize,
+    pub total_volume_sol: f64,
+}
+
+/// Wallet count and total volume per whale score decile
+///
+/// Index `d` covers scores `10 * d` to `10 * d + 9`; the last decile also
+/// takes a perfect 100. All ten entries are always present, so empty
+/// deciles show up as zeros. Comparing volume across deciles shows whether
+/// capital concentrates in high-scoring wallets.
+pub fn score_deciles(stats_map: &HashMap<String, WalletStats>) -> [DecileStat; 10] {
+    let mut deciles = [DecileStat::default(); 10];
+    for stats in stats_map.values() {
+        let decile = &mut deciles[(calculate_whale_score(stats) / 10).min(9) as usize];
+        decile.wallet_count += 1;
+        decile.total_volume_sol += stats.total_volume_sol;
+    }
+    deciles
+}
+
+/// Score difference `diff_stats` must exceed to report a wallet as changed
+pub const DEFAULT_SCORE_CHANGE_THRESHOLD: u8 = 0;
+
+/// A wallet whose whale score moved between two snapshots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreChange {
+    pub address: String,
+    pub old_score: u8,
+    pub new_score: u8,
+}
+
+/// Differences between two `calculate_batch_stats` snapshots
+///
+/// Every list is sorted by address.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatsDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<ScoreChange>,
+}
+
+/// Diff two stats maps using `DEFAULT_SCORE_CHANGE_THRESHOLD`
+pub fn diff_stats(
+    old: &HashMap<String, WalletStats>,
+    new: &HashMap<String, WalletStats>,
+) -> StatsDiff {
+    diff_stats_with_threshold(old, new, DEFAULT_SCORE_CHANGE_THRESHOLD)
+}
+
+/// Diff two stats maps, reporting new, dropped and re-scored wallets
+///
+/// A wallet present in both maps is "changed" when its whale score differs
+/// by more than `threshold` points.
+pub fn diff_stats_with_threshold(
+    old: &HashMap<String, WalletStats>,
+    new: &HashMap<String, WalletStats>,
+    threshold: u8,
+) -> StatsDiff {
+    let mut diff = StatsDiff::default();
+
+    for (address, new_stats) in new {
+        match old.get(address) {
+            None => diff.added.push(address.clone()),
+            Some(old_stats) => {
+                let old_score = calculate_whale_score(old_stats);
+                let new_score = calculate_whale_score(new_stats);
+                if old_score.abs_diff(new_score) > threshold {
+                    diff.changed.push(ScoreChange {
+                        address: address.clone(),
+                        old_score,
+                        new_score,
+                    });
+                }
+            }
+        }
+    }
+
+    diff.removed = old.keys().filter(|a| !new.contains_key(*a)).cloned().collect();
+
+    diff.added.sort();
+    diff.removed.sort();
+    diff.changed.sort_by(|a, b| a.address.cmp(&b.address));
+    diff
+}
+
+/// The `n` wallets whose whale score rose the most between two snapshots
+///
+/// Returns `(address, new_score - old_score)` for wallets in `new` with a
+/// positive change, largest first and by address on ties. Wallets missing
+/// from `old` count their whole score as the change; wallets that dropped
+/// out of `new` are ignored.
+pub fn top_movers(
+    old: &HashMap<String, WalletStats>,
+    new: &HashMap<String, WalletStats>,
+    n: usize,
+) -> Vec<(String, i16)> {
+    let mut movers: Vec<(String, i16)> = new
+        .iter()
+        .map(|(address, new_stats)| {
+            let old_score = old.get(address).map_or(0, calculate_whale_score);
+            (address.clone(), calculate_whale_score(new_stats) as i16 - old_score as i16)
+        })
+        .filter(|(_, delta)| *delta > 0)
+        .collect();
+
+    movers.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    movers.truncate(n);
+    movers
+}
+
+/// Running distribution of whale scores in constant memory
+///
+/// Scores are `u8`, so a count per possible value is both smaller than a
+/// t-digest and exact: percentiles match a sort of every score ever added.
+#[derive(Debug, Clone)]
+pub struct ScoreDistribution {
+    counts: [u64; 256],
+    total: u64,
+}
+
+impl Default for ScoreDistribution {
+    fn default() -> Self {
+        Self { counts: [0; 256], total: 0 }
+    }
+}
+
+impl ScoreDistribution {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, score: u8) {
+        self.counts[score as usize] += 1;
+        self.total += 1;
+    }
+
+    /// Number of scores added
+    pub fn count(&self) -> u64 {
+        self.total
+    }
+
+    /// The `q`-th percentile (0-100, clamped) of the scores seen so far
+    ///
+    /// Interpolates linearly between the two nearest ranks, like the usual
+    /// sample percentile over sorted data. Returns 0.0 when empty.
+    pub fn percentile(&self, q: f64) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+
+        let rank = q.clamp(0.0, 100.0) / 100.0 * (self.total - 1) as f64;
+        let lower = self.value_at(rank.floor() as u64);
+        let upper = self.value_at(rank.ceil() as u64);
+        lower + (upper - lower) * rank.fract()
+    }
+
+    /// Fraction (0-1) of scores at or below `score`
+    ///
+    /// A wallet whose score has a rank of 0.99 or more is in the top 1%.
+    pub fn percentile_rank(&self, score: u8) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        let at_or_below: u64 = self.counts[..=score as usize].iter().sum();
+        at_or_below as f64 / self.total as f64
+    }
+
+    /// Score at 0-based position `index` of the sorted scores
+    fn value_at(&self, index: u64) -> f64 {
+        let mut seen = 0;
+        for (score, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen > index {
+                return score as f64;
+            }
+        }
+        255.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TradeSide;
+
+    fn stats_with_volume(volume: f64) -> WalletStats {
+        WalletStats {
+            address: "test".to_string(),
+            total_volume_sol: volume,
+            interaction_count: 1,
+            average_entry_size: volume,
+            early_entry_count: 0,
+            winrate_proxy: 0.0,
+            avg_realized_multiple: None,
+            net_volume_sol: volume,
+        }
+    }
+
+    #[test]
+    fn test_volume_candles() {
+        let trade = |wallet: &str, block_time: u64, sol_amount: f64, side: TradeSide| TokenInteraction {
+            wallet_address: wallet.to_string(),
+            token_mint: "t1".to_string(),
+            block_time,
+            sol_amount,
+            is_early_entry: false,
+            side,
+            source: None,
+        };
+        let interactions = vec![
+            trade("b", 1075, 2.0, TradeSide::Buy),
+            trade("a", 1000, 5.0, TradeSide::Buy),
+            trade("a", 1010, 3.0, TradeSide::Buy),
+            trade("b", 1030, 1.0, TradeSide::Sell),
+            // Nothing in [1080, 1140): that bucket is skipped
+            trade("c", 1150, 9.0, TradeSide::Buy),
+        ];
+
+        let candles = volume_candles(&interactions, 60);
+        assert_eq!(
+            candles,
+            vec![
+                Candle { start_time: 960, total_volume: 8.0, buy_count: 2, unique_wallets: 1, max_single_buy: 5.0 },
+                Candle { start_time: 1020, total_volume: 3.0, buy_count: 1, unique_wallets: 1, max_single_buy: 2.0 },
+                Candle { start_time: 1140, total_volume: 9.0, buy_count: 1, unique_wallets: 1, max_single_buy: 9.0 },
+            ]
+        );
+        assert!(volume_candles(&[], 60).is_empty());
+    }
+
+    #[test]
+    fn test_volume_gini_known_distributions() {
+        let equal: Vec<WalletStats> = (0..4).map(|_| stats_with_volume(10.0)).collect();
+        assert!(volume_gini(&equal).abs() < 1e-9);
+
+        // One of n wallets holds everything: G = (n - 1) / n
+        let monopoly: Vec<WalletStats> = [0.0, 0.0, 0.0, 100.0].iter().map(|&v| stats_with_volume(v)).collect();
+        assert!((volume_gini(&monopoly) - 0.75).abs() < 1e-9);
+
+        // 1, 2, 3, 4: G = 2 * 30 / (4 * 10) - 5 / 4 = 0.25
+        let linear: Vec<WalletStats> = [4.0, 1.0, 3.0, 2.0].iter().map(|&v| stats_with_volume(v)).collect();
+        assert!((volume_gini(&linear) - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_volume_gini_degenerate_cases() {
+        assert_eq!(volume_gini(&[]), 0.0);
+        assert_eq!(volume_gini(&[stats_with_volume(50.0)]), 0.0);
+        assert_eq!(volume_gini(&[stats_with_volume(0.0), stats_with_volume(0.0)]), 0.0);
+    }
+
+    #[test]
+    fn test_token_interest_score() {
+        let buy = |wallet: &str, token: &str, sol_amount: f64| TokenInteraction {
+            wallet_address: wallet.to_string(),
+            token_mint: token.to_string(),
+            block_time: 1000,
+            sol_amount,
+            is_early_entry: true,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        let mut history = Vec::new();
+        for i in 0..30 {
+            history.push(buy("whale1", &format!("past{}", i), 60.0));
+            history.push(buy("whale2", &format!("past{}", i), 40.0));
+        }
+        for (wallet, sol_amount) in [("minnow1", 0.5), ("minnow2", 0.2)] {
+            history.push(TokenInteraction { is_early_entry: false, ..buy(wallet, "past0", sol_amount) });
+        }
+        let stats_map = crate::calculate_batch_stats(&history);
+
+        let whale_token = vec![buy("whale1", "hot", 20.0), buy("whale2", "hot", 10.0), buy("minnow1", "hot", 0.5)];
+        let minnow_token = vec![buy("minnow1", "cold", 0.5), buy("minnow2", "cold", 0.3), buy("unknown", "cold", 50.0)];
+
+        let hot = token_interest_score(&whale_token, &stats_map);
+        let cold = token_interest_score(&minnow_token, &stats_map);
+        assert!(hot > 80, "hot = {}", hot);
+        assert!(cold < 20, "cold = {}", cold);
+        assert_eq!(
+            token_interest_score(&[buy("solo", "t", 1.0)], &stats_map),
+            0,
+            "buyers without stats are ignored"
+        );
+    }
+
+    #[test]
+    fn test_token_early_leaderboard() {
+        let trade = |wallet: &str, block_time: u64, sol_amount: f64, side: TradeSide| TokenInteraction {
+            wallet_address: wallet.to_string(),
+            token_mint: "t1".to_string(),
+            block_time,
+            sol_amount,
+            is_early_entry: false,
+            side,
+            source: None,
+        };
+        let interactions = vec![
+            trade("late", 1300, 90.0, TradeSide::Buy),
+            trade("small", 1002, 1.0, TradeSide::Buy),
+            trade("big", 1002, 25.0, TradeSide::Buy),
+            trade("first", 1001, 3.0, TradeSide::Buy),
+            trade("first", 1500, 50.0, TradeSide::Buy),
+            trade("seller", 1000, 40.0, TradeSide::Sell),
+        ];
+
+        assert_eq!(
+            token_early_leaderboard(&interactions, 1000, 10),
+            vec![
+                ("first".to_string(), 1, 3.0),
+                ("big".to_string(), 2, 25.0),
+                ("small".to_string(), 2, 1.0),
+                ("late".to_string(), 300, 90.0),
+            ]
+        );
+        let top_two: Vec<String> =
+            token_early_leaderboard(&interactions, 1000, 2).into_iter().map(|(address, _, _)| address).collect();
+        assert_eq!(top_two, ["first", "big"]);
+        assert!(token_early_leaderboard(&interactions, 1000, 0).is_empty());
+    }
+
+    #[test]
+    fn test_holder_concentration() {
+        let buy = |wallet: &str, sol_amount: f64| TokenInteraction {
+            wallet_address: wallet.to_string(),
+            token_mint: "token1".to_string(),
+            block_time: 1000,
+            sol_amount,
+            is_early_entry: false,
+            side: TradeSide::Buy,
+            source: None,
+        };
+
+        let monopolized = vec![buy("whale", 990.0), buy("whale", 5.0), buy("minnow", 5.0)];
+        assert!(holder_concentration(&monopolized) > 0.98);
+
+        let fragmented: Vec<TokenInteraction> = (0..20).map(|i| buy(&format!("w{}", i), 10.0)).collect();
+        assert!((holder_concentration(&fragmented) - 0.05).abs() < 1e-9);
+
+        assert_eq!(holder_concentration(&[buy("solo", 3.0)]), 1.0);
+        assert_eq!(holder_concentration(&[]), 0.0);
+
+        // The whale's sell doesn't add to its share
+        let dumped = vec![
+            buy("whale", 50.0),
+            buy("minnow", 50.0),
+            TokenInteraction { side: TradeSide::Sell, ..buy("whale", 50.0) },
+        ];
+        assert!((holder_concentration(&dumped) - 0.5).abs() < 1e-9);
+        assert_eq!(holder_concentration(&[TokenInteraction { side: TradeSide::Sell, ..buy("seller", 5.0) }]), 0.0);
+    }
+
+    #[test]
+    fn test_token_net_flow() {
+        let trade = |wallet: &str, mint: &str, sol_amount: f64, side: TradeSide| TokenInteraction {
+            wallet_address: wallet.to_string(),
+            token_mint: mint.to_string(),
+            block_time: 1000,
+            sol_amount,
+            is_early_entry: false,
+            side,
+            source: None,
+        };
+        let interactions = vec![
+            trade("a", "t1", 10.0, TradeSide::Buy),
+            trade("b", "t1", 4.0, TradeSide::Sell),
+            trade("a", "t2", 3.0, TradeSide::Buy),
+            trade("c", "t2", 8.0, TradeSide::Sell),
+            trade("c", "t1", 1.5, TradeSide::Buy),
+        ];
+
+        let flows = token_net_flow(&interactions);
+        assert_eq!(flows.len(), 2);
+        assert_eq!(flows["t1"], 7.5);
+        assert_eq!(flows["t2"], -5.0);
+        assert!(token_net_flow(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_top_movers() {
+        let wallet = |address: &str, early: u64| WalletStats {
+            address: address.to_string(),
+            total_volume_sol: 100.0,
+            interaction_count: 10,
+            average_entry_size: 10.0,
+            early_entry_count: early,
+            winrate_proxy: 0.5,
+            avg_realized_multiple: None,
+            net_volume_sol: 100.0,
+        };
+        let snapshot = |wallets: &[(&str, u64)]| -> HashMap<String, WalletStats> {
+            wallets.iter().map(|&(address, early)| (address.to_string(), wallet(address, early))).collect()
+        };
+        let old = snapshot(&[("steady", 3), ("riser", 1), ("big_riser", 0), ("faller", 9), ("gone", 5)]);
+        let new = snapshot(&[("steady", 3), ("riser", 3), ("big_riser", 8), ("faller", 2), ("fresh", 0)]);
+        let score = |early: u64| calculate_whale_score(&wallet("x", early)) as i16;
+
+        // Deltas of 32, 15 (fresh, its full score) and 8; steady and faller don't rise
+        let movers = top_movers(&old, &new, 10);
+        assert_eq!(
+            movers,
+            vec![
+                ("big_riser".to_string(), score(8) - score(0)),
+                ("fresh".to_string(), score(0)),
+                ("riser".to_string(), score(3) - score(1)),
+            ]
+        );
+        assert_eq!(top_movers(&old, &new, 1), movers[..1].to_vec());
+        assert!(top_movers(&old, &old, 5).is_empty());
+    }
+
+    #[test]
+    fn test_diff_stats_categories() {
+        let wallet = |address: &str, early: u64| WalletStats {
+            address: address.to_string(),
+            total_volume_sol: 100.0,
+            interaction_count: 10,
+            average_entry_size: 10.0,
+            early_entry_count: early,
+            winrate_proxy: 0.5,
+            avg_realized_multiple: None,
+            net_volume_sol: 100.0,
+        };
+        let mut old = HashMap::new();
+        old.insert("kept".to_string(), wallet("kept", 2));
+        old.insert("moved".to_string(), wallet("moved", 1));
+        old.insert("dropped".to_string(), wallet("dropped", 1));
+
+        let mut new = HashMap::new();
+        new.insert("kept".to_string(), wallet("kept", 2));
+        new.insert("moved".to_string(), wallet("moved", 9));
+        new.insert("fresh".to_string(), wallet("fresh", 1));
+
+        let diff = diff_stats(&old, &new);
+        assert_eq!(diff.added, vec!["fresh".to_string()]);
+        assert_eq!(diff.removed, vec!["dropped".to_string()]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].address, "moved");
+        assert!(diff.changed[0].new_score > diff.changed[0].old_score);
+
+        let tolerant = diff_stats_with_threshold(&old, &new, 100);
+        assert!(tolerant.changed.is_empty());
+    }
+
+    #[test]
+    fn test_relative_scores() {
+        // Repetition-only wallets: 10, 20 and 30 interactions score 4, 8 and 12
+        let mut stats_map = HashMap::new();
+        for count in [10u64, 20, 30] {
+            let address = format!("w{}", count);
+            stats_map.insert(
+                address.clone(),
+                WalletStats {
+                    address,
+                    total_volume_sol: 0.0,
+                    interaction_count: count,
+                    average_entry_size: 0.0,
+                    early_entry_count: 0,
+                    winrate_proxy: 0.0,
+                    avg_realized_multiple: None,
+                    net_volume_sol: 0.0,
+                },
+            );
+        }
+
+        let z = relative_scores(&stats_map);
+        let mean: f64 = z.values().sum::<f64>() / z.len() as f64;
+        assert!(mean.abs() < 1e-9);
+
+        // Scores 4, 8, 12: mean 8, population std sqrt(32 / 3)
+        let std_dev = (32.0f64 / 3.0).sqrt();
+        assert!((z["w10"] + 4.0 / std_dev).abs() < 1e-9);
+        assert!(z["w20"].abs() < 1e-9);
+        assert!((z["w30"] - 4.0 / std_dev).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_behavioral_outlier_score() {
+        let wallet = |address: &str, count: u64, avg: f64, early: u64| WalletStats {
+            address: address.to_string(),
+            total_volume_sol: avg * count as f64,
+            interaction_count: count,
+            average_entry_size: avg,
+            early_entry_count: early,
+            winrate_proxy: crate::winrate_proxy(early as f64, count as f64),
+            avg_realized_multiple: None,
+            net_volume_sol: avg * count as f64,
+        };
+        let mut cohort: HashMap<String, WalletStats> = [
+            wallet("a", 10, 1.0, 1),
+            wallet("b", 12, 1.2, 2),
+            wallet("c", 8, 0.8, 1),
+            wallet("d", 11, 1.1, 0),
+            wallet("e", 9, 0.9, 2),
+        ]
+        .into_iter()
+        .map(|s| (s.address.clone(), s))
+        .collect();
+        let outlier = wallet("whale", 200, 80.0, 190);
+        cohort.insert(outlier.address.clone(), outlier.clone());
+
+        assert_eq!(behavioral_outlier_score(&outlier, &cohort), 100.0);
+        for typical in ["a", "b", "c", "d", "e"] {
+            assert!(behavioral_outlier_score(&cohort[typical], &cohort) < 50.0, "{}", typical);
+        }
+
+        // No spread on any feature, or too few peers
+        let clones: HashMap<String, WalletStats> =
+            ["x", "y", "z"].iter().map(|a| (a.to_string(), wallet(a, 10, 1.0, 1))).collect();
+        assert_eq!(behavioral_outlier_score(&outlier, &clones), 0.0);
+        assert_eq!(behavioral_outlier_score(&outlier, &HashMap::new()), 0.0);
+    }
+
+    #[test]
+    fn test_cohort_score_ema() {
+        let first = cohort_score_ema(None, &[10, 20, 30], 0.25).unwrap();
+        assert_eq!(first, 20.0);
+
+        // Batch mean 60: 0.25 * 60 + 0.75 * 20
+        let second = cohort_score_ema(Some(first), &[50, 70], 0.25).unwrap();
+        assert!((second - 30.0).abs() < 1e-9);
+        assert_eq!(cohort_score_ema(Some(second), &[], 0.25), Ok(second));
+        assert_eq!(cohort_score_ema(Some(second), &[90], 1.0), Ok(90.0));
+
+        for alpha in [0.0, -0.5, 1.5, f64::NAN] {
+            assert!(cohort_score_ema(None, &[10], alpha).is_err());
+        }
+        assert_eq!(
+            cohort_score_ema(None, &[10], 2.0).unwrap_err().to_string(),
+            "alpha must be within (0, 1], got 2"
+        );
+    }
+
+    #[test]
+    fn test_score_percentile() {
+        // Repetition-only wallets: 10, 20, 30, 40 and 50 interactions score 4, 8, 12, 16 and 20
+        let stats_map: HashMap<String, WalletStats> = [10u64, 20, 30, 40, 50]
+            .into_iter()
+            .map(|count| {
+                let stats = WalletStats { interaction_count: count, ..WalletStats::empty(&format!("w{}", count)) };
+                (stats.address.clone(), stats)
+            })
+            .collect();
+
+        assert_eq!(score_percentile(12, &stats_map), 0.6);
+        assert_eq!(score_percentile(20, &stats_map), 1.0);
+        // Not in the cohort: between the second and third wallet, or below everyone
+        assert_eq!(score_percentile(10, &stats_map), 0.4);
+        assert_eq!(score_percentile(3, &stats_map), 0.0);
+        assert_eq!(score_percentile(50, &HashMap::new()), 0.0);
+    }
+
+    #[test]
+    fn test_tier_distribution() {
+        let wallet = |address: &str, early_entry_count: u64, winrate_proxy: f64, volume: f64| WalletStats {
+            address: address.to_string(),
+            total_volume_sol: volume,
+            interaction_count: 50,
+            average_entry_size: volume / 50.0,
+            early_entry_count,
+            winrate_proxy,
+            avg_realized_multiple: None,
+            net_volume_sol: volume,
+        };
+        // Scores 0, 20, 30, 30, 70 and 100
+        let cohort = [
+            WalletStats::empty("a"),
+            wallet("b", 0, 0.0, 0.0),
+            wallet("c", 0, 1.0, 0.0),
+            wallet("d", 0, 1.0, 0.0),
+            wallet("e", 50, 1.0, 0.0),
+            wallet("f", 50, 1.0, 2500.0),
+        ];
+        let stats_map: HashMap<String, WalletStats> = cohort.into_iter().map(|s| (s.address.clone(), s)).collect();
+
+        let distribution = tier_distribution(&stats_map);
+        assert_eq!(distribution[&WhaleTier::Minnow], 2);
+        assert_eq!(distribution[&WhaleTier::Dolphin], 2);
+        assert_eq!(distribution[&WhaleTier::Whale], 1);
+        assert_eq!(distribution[&WhaleTier::Mega], 1);
+
+        let empty = tier_distribution(&HashMap::new());
+        assert_eq!(empty.len(), 4);
+        assert!(empty.values().all(|&count| count == 0));
+    }
+
+    #[test]
+    fn test_score_deciles() {
+        // Repetition-only wallets score 0.4 points per interaction
+        let wallet = |address: &str, interaction_count: u64| WalletStats { interaction_count, ..WalletStats::empty(address) };
+        let mut stats_map: HashMap<String, WalletStats> = [wallet("a", 10), wallet("b", 20), wallet("c", 25), wallet("d", 30)]
+            .into_iter()
+            .map(|s| (s.address.clone(), s))
+            .collect();
+        // Volume is counted, here in a perfect-score wallet
+        let whale = WalletStats {
+            address: "whale".to_string(),
+            total_volume_sol: 5000.0,
+            interaction_count: 50,
+            average_entry_size: 100.0,
+            early_entry_count: 50,
+            winrate_proxy: 1.0,
+            avg_realized_multiple: None,
+            net_volume_sol: 5000.0,
+        };
+        stats_map.insert(whale.address.clone(), whale);
+
+        let deciles = score_deciles(&stats_map);
+        let counts: Vec<usize> = deciles.iter().map(|d| d.wallet_count).collect();
+        // Scores 4, 8, 10, 12 and 100
+        assert_eq!(counts, [2, 2, 0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(deciles[9].total_volume_sol, 5000.0);
+        assert_eq!(deciles[1].total_volume_sol, 0.0);
+        assert_eq!(score_deciles(&HashMap::new()), [DecileStat::default(); 10]);
+    }
+
+    #[test]
+    fn test_relative_scores_all_equal() {
+        let mut stats_map = HashMap::new();
+        for address in ["a", "b"] {
+            stats_map.insert(address.to_string(), stats_with_volume(10.0));
+        }
+
+        assert!(relative_scores(&stats_map).values().all(|&z| z == 0.0));
+        assert!(relative_scores(&HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_score_distribution_matches_exact_percentiles() {
+        // Skewed cohort: mostly low scores with a long tail
+        let scores: Vec<u8> = (0..1000u32).map(|i| ((i * i) % 9973 / 100) as u8).collect();
+        let mut distribution = ScoreDistribution::new();
+        for &score in &scores {
+            distribution.add(score);
+        }
+
+        let mut sorted: Vec<f64> = scores.iter().map(|&s| s as f64).collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let exact = |q: f64| {
+            let rank = q / 100.0 * (sorted.len() - 1) as f64;
+            let (lo, hi) = (sorted[rank.floor() as usize], sorted[rank.ceil() as usize]);
+            lo + (hi - lo) * rank.fract()
+        };
+
+        assert_eq!(distribution.count(), 1000);
+        for q in [0.0, 1.0, 25.0, 50.0, 90.0, 99.0, 100.0] {
+            assert!((distribution.percentile(q) - exact(q)).abs() < 1e-9, "q = {}", q);
+        }
+        let top = distribution.percentile(99.0).ceil() as u8;
+        assert!(distribution.percentile_rank(top) >= 0.99);
+    }
+
+    #[test]
+    fn test_score_distribution_empty() {
+        let distribution = ScoreDistribution::new();
+
+        assert_eq!(distribution.percentile(50.0), 0.0);
+        assert_eq!(distribution.percentile_rank(50), 0.0);
+    }
+}
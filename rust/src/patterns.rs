@@ -0,0 +1,509 @@
+//! Behavioral pattern detectors over a single wallet's interactions
+//! Each detector answers one yes/no question about how a wallet trades
+
+use crate::timing::{saturating_elapsed, sorted_by_time, timing_entropy};
+use crate::{signed_amount, TokenInteraction, TradeSide};
+use std::collections::{HashMap, VecDeque};
+
+/// Largest dip below the running peak (as a fraction of it) that doesn't break a ladder
+pub const DEFAULT_LADDER_TOLERANCE: f64 = 0.1;
+
+/// Share of recent gross volume that net flow must reach to leave `Phase::Neutral`
+pub const PHASE_NET_RATIO_THRESHOLD: f64 = 0.2;
+
+/// A sell this many seconds or less after a buy of the same token counts as a round trip
+pub const WASH_WINDOW_SECONDS: u64 = 300;
+
+/// Bucket width for the inter-buy gaps `risk_score` feeds to `timing_entropy`
+pub const CADENCE_BUCKET_SECONDS: u64 = 10;
+
+/// Distance from a whole-SOL amount the penalized scorer still treats as round
+pub const ROUND_NUMBER_TOLERANCE: f64 = 0.01;
+
+/// Slack `risk_score` allows around a two-significant-figure amount, as a
+/// fraction of the second digit's place value (0.01 SOL for 1-10 SOL buys)
+const SIGNIFICANT_ROUND_TOLERANCE: f64 = 0.1;
+
+/// Whether a wallet is currently building or unwinding a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Recent buys clearly outweigh sells
+    Accumulating,
+    /// Recent sells clearly outweigh buys
+    Distributing,
+    /// Balanced or no recent activity
+    Neutral,
+}
+
+/// Detect ladder accumulation: buy sizes increasing over time
+///
+/// Equivalent to `detect_ladder_with_tolerance` with `DEFAULT_LADDER_TOLERANCE`.
+pub fn detect_ladder(interactions: &[TokenInteraction], min_steps: usize) -> bool {
+    detect_ladder_with_tolerance(interactions, min_steps, DEFAULT_LADDER_TOLERANCE)
+}
+
+/// Detect ladder accumulation with an explicit dip tolerance
+///
+/// Interactions are ordered by `block_time` and every buy that sets a new high
+/// `sol_amount` counts as a step. Buys that dip at most `tolerance` below the
+/// current peak are ignored; deeper dips restart the ladder. Returns true if any
+/// ladder reaches `min_steps` steps.
+pub fn detect_ladder_with_tolerance(
+    interactions: &[TokenInteraction],
+    min_steps: usize,
+    tolerance: f64,
+) -> bool {
+    let sorted = sorted_by_time(interactions);
+    let mut iter = sorted.iter();
+    let mut peak = match iter.next() {
+        Some(first) => first.sol_amount,
+        None => return false,
+    };
+
+    let mut steps = 1;
+    let mut best = steps;
+
+    for interaction in iter {
+        let amount = interaction.sol_amount;
+        if amount > peak {
+            steps += 1;
+            peak = amount;
+        } else if amount < peak * (1.0 - tolerance) {
+            steps = 1;
+            peak = amount;
+        }
+        best = best.max(steps);
+    }
+
+    best >= min_steps
+}
+
+/// Least-squares slope of buy size over time
+///
+/// Fits `sol_amount` against `block_time` rescaled to `[0, 1]` across the
+/// wallet's buys (sells are ignored), so the slope is the change in SOL per
+/// buy from the first to the last buy: positive means sizes are growing,
+/// negative shrinking. Returns 0.0 with fewer than two buys or when every buy
+/// shares one block.
+pub fn buy_size_trend(interactions: &[TokenInteraction]) -> f64 {
+    let buys: Vec<&TokenInteraction> = interactions.iter().filter(|i| i.side == TradeSide::Buy).collect();
+    if buys.len() < 2 {
+        return 0.0;
+    }
+
+    let first = buys.iter().map(|i| i.block_time).min().unwrap_or(0);
+    let last = buys.iter().map(|i| i.block_time).max().unwrap_or(0);
+    if last == first {
+        return 0.0;
+    }
+
+    let span = saturating_elapsed(last, first) as f64;
+    let points: Vec<(f64, f64)> = buys
+        .iter()
+        .map(|i| (saturating_elapsed(i.block_time, first) as f64 / span, i.sol_amount))
+        .collect();
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let covariance: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    covariance / variance
+}
+
+/// Fraction of buys within `tolerance` SOL of a whole-SOL amount
+///
+/// People typing amounts by hand favor figures like 1, 5 or 10 SOL, while
+/// bots and sized-by-formula buys land on arbitrary fractions, so a high
+/// ratio suggests manual trading. Amounts closer to 0 than to 1 SOL are never
+/// round. Sells are ignored; returns 0.0 when there are no buys.
+pub fn round_number_ratio(interactions: &[TokenInteraction], tolerance: f64) -> f64 {
+    let amounts: Vec<f64> = interactions
+        .iter()
+        .filter(|i| i.side == TradeSide::Buy)
+        .map(|i| i.sol_amount)
+        .collect();
+    if amounts.is_empty() {
+        return 0.0;
+    }
+
+    let tolerance = tolerance.abs();
+    let round = amounts
+        .iter()
+        .filter(|&&amount| {
+            let nearest = amount.round();
+            nearest >= 1.0 && (amount - nearest).abs() <= tolerance
+        })
+        .count();
+    round as f64 / amounts.len() as f64
+}
+
+/// Share (0-1) of gross volume bought and sold back within `window` seconds
+///
+/// Per token, each sell is matched against the wallet's still-unmatched buy
+/// volume from the preceding `window` seconds (oldest first); the matched
+/// amount counts twice, once for each leg.
+fn round_trip_share(interactions: &[TokenInteraction], window: u64) -> f64 {
+    let gross: f64 = interactions.iter().map(|i| i.sol_amount).sum();
+    if gross <= 0.0 {
+        return 0.0;
+    }
+
+    let mut open_buys: HashMap<&str, VecDeque<(u64, f64)>> = HashMap::new();
+    let mut matched = 0.0;
+    for interaction in sorted_by_time(interactions) {
+        let buys = open_buys.entry(interaction.token_mint.as_str()).or_default();
+        match interaction.side {
+            TradeSide::Buy => buys.push_back((interaction.block_time, interaction.sol_amount)),
+            TradeSide::Sell => {
+                buys.retain(|&(time, _)| saturating_elapsed(interaction.block_time, time) <= window);
+                let mut remaining = interaction.sol_amount;
+                while remaining > 0.0 {
+                    let Some(front) = buys.front_mut() else { break };
+                    let taken = front.1.min(remaining);
+                    front.1 -= taken;
+                    remaining -= taken;
+                    matched += taken;
+                    if front.1 <= 0.0 {
+                        buys.pop_front();
+                    }
+                }
+            }
+        }
+    }
+
+    (2.0 * matched / gross).min(1.0)
+}
+
+/// Fraction of buys that are round at their own magnitude
+///
+/// An amount counts as round when it is within `SIGNIFICANT_ROUND_TOLERANCE`
+/// of the second significant digit's place value from its two-significant-
+/// figure rounding: 0.25, 1.5, 5 and 120 SOL are round, 0.2537 and 1.37 are
+/// not. Unlike `round_number_ratio`, sub-1 SOL buys can be round. Sells are
+/// ignored; returns 0.0 when there are no buys.
+fn significant_round_ratio(interactions: &[TokenInteraction]) -> f64 {
+    let amounts: Vec<f64> = interactions
+        .iter()
+        .filter(|i| i.side == TradeSide::Buy)
+        .map(|i| i.sol_amount)
+        .collect();
+    if amounts.is_empty() {
+        return 0.0;
+    }
+
+    let round = amounts
+        .iter()
+        .filter(|&&amount| {
+            if !(amount > 0.0 && amount.is_finite()) {
+                return false;
+            }
+            let place = 10f64.powi(amount.log10().floor() as i32 - 1);
+            let nearest = (amount / place).round() * place;
+            (amount - nearest).abs() <= SIGNIFICANT_ROUND_TOLERANCE * place
+        })
+        .count();
+    round as f64 / amounts.len() as f64
+}
+
+/// Composite 0-100 risk that a wallet's activity is artificial
+///
+/// Weighs three negative signals:
+/// - 40 points for wash trading: the share of volume bought and sold back
+///   within `WASH_WINDOW_SECONDS`
+/// - 35 points for bot cadence: `1 - timing_entropy` with
+///   `CADENCE_BUCKET_SECONDS` buckets (needs at least three buys)
+/// - 25 points for formula sizing: the share of buys that aren't round at
+///   their own magnitude (two significant figures, so small retail buys
+///   like 0.25 SOL aren't mistaken for formula sizing)
+///
+/// A high risk means the whale score deserves less trust. Wallets without
+/// buys score 0 on the last two signals.
+pub fn risk_score(interactions: &[TokenInteraction]) -> u8 {
+    let buys = interactions.iter().filter(|i| i.side == TradeSide::Buy).count();
+
+    let wash = round_trip_share(interactions, WASH_WINDOW_SECONDS);
+    let cadence = if buys >= 3 { 1.0 - timing_entropy(interactions, CADENCE_BUCKET_SECONDS) } else { 0.0 };
+    let formula_sizing = if buys > 0 { 1.0 - significant_round_ratio(interactions) } else { 0.0 };
+
+    (wash * 40.0 + cadence * 35.0 + formula_sizing * 25.0).clamp(0.0, 100.0).round() as u8
+}
+
+/// Detect a wallet that went dormant and has recently started trading again
+///
+/// Interactions are ordered by `block_time` and the largest gap between
+/// consecutive interactions must be at least `dormancy_gap` seconds. The
+/// activity after that gap counts as recent when the latest interaction is
+/// less than `dormancy_gap` seconds before `now`, i.e. the wallet hasn't gone
+/// quiet again.
+pub fn detect_reawakening(interactions: &[TokenInteraction], dormancy_gap: u64, now: u64) -> bool {
+    let sorted = sorted_by_time(interactions);
+    let last = match sorted.last() {
+        Some(last) => last.block_time,
+        None => return false,
+    };
+
+    let largest_gap = sorted
+        .windows(2)
+        .map(|pair| saturating_elapsed(pair[1].block_time, pair[0].block_time))
+        .max()
+        .unwrap_or(0);
+
+    largest_gap >= dormancy_gap && saturating_elapsed(now, last) < dormancy_gap
+}
+
+/// Detect a probe buy followed quickly by a much larger buy of the same token
+///
+/// Flags the wallet when some buy is followed, at most `max_gap` seconds
+/// later and in the same token, by a buy at least `size_ratio` times its
+/// `sol_amount`: the tiny buy tests the route and the large one is the real
+/// position. Sells and zero-sized buys never count as the probe.
+pub fn detect_test_then_size(interactions: &[TokenInteraction], size_ratio: f64, max_gap: u64) -> bool {
+    let mut by_token: HashMap<&str, Vec<&TokenInteraction>> = HashMap::new();
+    for interaction in sorted_by_time(interactions) {
+        if interaction.side == TradeSide::Buy {
+            by_token.entry(interaction.token_mint.as_str()).or_default().push(interaction);
+        }
+    }
+
+    by_token.values().any(|buys| {
+        buys.iter().enumerate().any(|(i, probe)| {
+            probe.sol_amount > 0.0
+                && buys[i + 1..]
+                    .iter()
+                    .take_while(|later| saturating_elapsed(later.block_time, probe.block_time) <= max_gap)
+                    .any(|later| later.sol_amount >= probe.sol_amount * size_ratio)
+        })
+    })
+}
+
+/// Classify recent net flow as accumulation, distribution or neither
+///
+/// Only interactions in the last `recent_window` seconds before `now`
+/// (inclusive) count. Net volume (buys minus sells) is divided by gross
+/// volume; a ratio of at least `PHASE_NET_RATIO_THRESHOLD` is `Accumulating`,
+/// at most `-PHASE_NET_RATIO_THRESHOLD` is `Distributing`, and anything in
+/// between, or no recent volume, is `Neutral`.
+pub fn wallet_phase(interactions: &[TokenInteraction], recent_window: u64, now: u64) -> Phase {
+    let recent = interactions
+        .iter()
+        .filter(|i| i.block_time <= now && saturating_elapsed(now, i.block_time) <= recent_window);
+
+    let (net, gross) = recent.fold((0.0, 0.0), |(net, gross), i| (net + signed_amount(i), gross + i.sol_amount));
+    if gross <= 0.0 {
+        return Phase::Neutral;
+    }
+
+    let ratio = net / gross;
+    if ratio >= PHASE_NET_RATIO_THRESHOLD {
+        Phase::Accumulating
+    } else if ratio <= -PHASE_NET_RATIO_THRESHOLD {
+        Phase::Distributing
+    } else {
+        Phase::Neutral
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buys(amounts: &[f64]) -> Vec<TokenInteraction> {
+        amounts
+            .iter()
+            .enumerate()
+            .map(|(i, &amount)| TokenInteraction {
+                wallet_address: "addr1".to_string(),
+                token_mint: "token1".to_string(),
+                block_time: 1000 + i as u64 * 10,
+                sol_amount: amount,
+                is_early_entry: false,
+                side: TradeSide::Buy,
+                source: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_round_number_ratio() {
+        assert_eq!(round_number_ratio(&buys(&[1.0, 5.0, 10.0, 25.0]), 0.01), 1.0);
+        assert_eq!(round_number_ratio(&buys(&[1.37, 4.62, 0.83, 0.004]), 0.01), 0.0);
+        assert_eq!(round_number_ratio(&buys(&[2.0, 3.004, 7.41, 0.5]), 0.01), 0.5);
+
+        let mut with_sells = buys(&[1.0, 2.73]);
+        with_sells.push(TokenInteraction { side: TradeSide::Sell, ..buys(&[3.0])[0].clone() });
+        assert_eq!(round_number_ratio(&with_sells, 0.01), 0.5);
+        assert_eq!(round_number_ratio(&[], 0.01), 0.0);
+    }
+
+    #[test]
+    fn test_risk_score() {
+        let trade = |block_time: u64, sol_amount: f64, side: TradeSide| TokenInteraction {
+            block_time,
+            side,
+            ..buys(&[sol_amount])[0].clone()
+        };
+
+        // Round sizes, irregular timing, holds what it buys
+        let clean = vec![
+            trade(1000, 5.0, TradeSide::Buy),
+            trade(1007, 2.0, TradeSide::Buy),
+            trade(1400, 10.0, TradeSide::Buy),
+            trade(9000, 1.0, TradeSide::Buy),
+        ];
+        assert_eq!(risk_score(&clean), 0);
+
+        // Odd sizes every 60s, each sold straight back
+        let mut bot = Vec::new();
+        for n in 0..6 {
+            bot.push(trade(1000 + n * 60, 1.37, TradeSide::Buy));
+            bot.push(trade(1000 + n * 60 + 5, 1.37, TradeSide::Sell));
+        }
+        assert_eq!(risk_score(&bot), 100);
+
+        // A sell long after the buy isn't a round trip
+        let slow_exit = vec![trade(1000, 5.0, TradeSide::Buy), trade(5000, 5.0, TradeSide::Sell)];
+        assert_eq!(risk_score(&slow_exit), 0);
+        assert_eq!(risk_score(&[]), 0);
+    }
+
+    #[test]
+    fn test_risk_score_ignores_small_round_sizes() {
+        let trade = |block_time: u64, sol_amount: f64| TokenInteraction {
+            block_time,
+            ..buys(&[sol_amount])[0].clone()
+        };
+
+        // A small retail wallet: hand-typed sub-1 SOL buys at irregular times
+        let small = vec![trade(1000, 0.5), trade(1013, 0.25), trade(1400, 0.1), trade(9000, 0.3)];
+        assert_eq!(round_number_ratio(&small, ROUND_NUMBER_TOLERANCE), 0.0);
+        assert_eq!(risk_score(&small), 0);
+
+        // Formula-sized small buys still count
+        let formula = vec![trade(1000, 0.2537), trade(1013, 0.4182), trade(1400, 0.1379), trade(9000, 0.3311)];
+        assert_eq!(risk_score(&formula), 25);
+        assert_eq!(significant_round_ratio(&[trade(1000, 120.0), trade(1000, 1.37)]), 0.5);
+    }
+
+    #[test]
+    fn test_detect_clean_ladder() {
+        let mut interactions = buys(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        interactions.reverse();
+
+        assert!(detect_ladder(&interactions, 5));
+        assert!(!detect_ladder(&interactions, 6));
+    }
+
+    #[test]
+    fn test_detect_noisy_ladder_within_tolerance() {
+        let interactions = buys(&[1.0, 2.0, 1.9, 3.0, 2.8, 4.0, 5.0]);
+
+        assert!(detect_ladder(&interactions, 5));
+        assert!(!detect_ladder_with_tolerance(&interactions, 5, 0.0));
+    }
+
+    #[test]
+    fn test_random_sequence_is_not_ladder() {
+        let interactions = buys(&[5.0, 1.0, 4.0, 2.0, 3.0]);
+
+        assert!(!detect_ladder(&interactions, 3));
+        assert!(!detect_ladder(&[], 1));
+    }
+
+    #[test]
+    fn test_buy_size_trend() {
+        // Evenly spaced buys: time rescales to 0, 0.25, ..., 1
+        let increasing = buys(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        let flat = buys(&[3.0, 3.0, 3.0]);
+        let mut decreasing = buys(&[10.0, 8.0, 6.0]);
+        // Input order doesn't matter, only block_time does
+        decreasing.reverse();
+
+        assert!((buy_size_trend(&increasing) - 4.0).abs() < 1e-9);
+        assert!(buy_size_trend(&flat).abs() < 1e-9);
+        assert!((buy_size_trend(&decreasing) + 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_buy_size_trend_degenerate() {
+        let mut same_block = buys(&[1.0, 5.0]);
+        same_block[1].block_time = same_block[0].block_time;
+
+        assert_eq!(buy_size_trend(&buys(&[7.0])), 0.0);
+        assert_eq!(buy_size_trend(&[]), 0.0);
+        assert_eq!(buy_size_trend(&same_block), 0.0);
+    }
+
+    #[test]
+    fn test_detect_reawakening() {
+        let at = |times: &[u64]| -> Vec<TokenInteraction> {
+            let mut interactions = buys(&vec![1.0; times.len()]);
+            for (interaction, &time) in interactions.iter_mut().zip(times) {
+                interaction.block_time = time;
+            }
+            interactions
+        };
+        let day = 86_400;
+        let now = 100 * day;
+
+        let continuous = at(&[90 * day, 92 * day, 94 * day, 96 * day, 99 * day]);
+        let reawakened = at(&[10 * day, 11 * day, 98 * day, 99 * day]);
+        let dormant_again = at(&[10 * day, 50 * day]);
+
+        assert!(!detect_reawakening(&continuous, 30 * day, now));
+        assert!(detect_reawakening(&reawakened, 30 * day, now));
+        assert!(!detect_reawakening(&dormant_again, 30 * day, now));
+        assert!(!detect_reawakening(&[], 30 * day, now));
+    }
+
+    #[test]
+    fn test_detect_test_then_size() {
+        let trades = |trades: &[(&str, u64, f64)]| -> Vec<TokenInteraction> {
+            let mut interactions = buys(&trades.iter().map(|&(_, _, amount)| amount).collect::<Vec<_>>());
+            for (interaction, &(mint, time, _)) in interactions.iter_mut().zip(trades) {
+                interaction.token_mint = mint.to_string();
+                interaction.block_time = time;
+            }
+            interactions
+        };
+
+        let probing = trades(&[("t1", 1000, 5.0), ("t2", 2000, 0.05), ("t2", 2030, 8.0)]);
+        assert!(detect_test_then_size(&probing, 20.0, 60));
+        // Too slow, or not large enough
+        assert!(!detect_test_then_size(&probing, 20.0, 10));
+        assert!(!detect_test_then_size(&probing, 500.0, 60));
+
+        let random = trades(&[("t1", 1000, 2.0), ("t1", 1020, 3.5), ("t2", 1040, 0.1), ("t3", 1050, 9.0), ("t1", 1100, 1.0)]);
+        assert!(!detect_test_then_size(&random, 10.0, 300));
+
+        let mut sold = trades(&[("t1", 1000, 0.1), ("t1", 1010, 10.0)]);
+        sold[0].side = TradeSide::Sell;
+        assert!(!detect_test_then_size(&sold, 10.0, 60));
+        assert!(!detect_test_then_size(&[], 10.0, 60));
+    }
+
+    #[test]
+    fn test_wallet_phase() {
+        let trades = |sides: &[(f64, TradeSide)]| -> Vec<TokenInteraction> {
+            let mut interactions = buys(&sides.iter().map(|&(amount, _)| amount).collect::<Vec<_>>());
+            for (interaction, &(_, side)) in interactions.iter_mut().zip(sides) {
+                interaction.side = side;
+            }
+            interactions
+        };
+        // buys() places trades at t=1000, 1010, 1020, ...
+        let now = 1030;
+
+        let accumulating = trades(&[(5.0, TradeSide::Buy), (4.0, TradeSide::Buy), (2.0, TradeSide::Sell)]);
+        let distributing = trades(&[(1.0, TradeSide::Buy), (6.0, TradeSide::Sell), (3.0, TradeSide::Sell)]);
+        let neutral = trades(&[(5.0, TradeSide::Buy), (4.0, TradeSide::Sell)]);
+        // Heavy selling happened long before the window; only the balanced recent pair counts
+        let stale = trades(&[(50.0, TradeSide::Sell), (1.0, TradeSide::Buy), (1.0, TradeSide::Sell)]);
+
+        assert_eq!(wallet_phase(&accumulating, 60, now), Phase::Accumulating);
+        assert_eq!(wallet_phase(&distributing, 60, now), Phase::Distributing);
+        assert_eq!(wallet_phase(&neutral, 60, now), Phase::Neutral);
+        assert_eq!(wallet_phase(&stale, 25, now), Phase::Neutral);
+        assert_eq!(wallet_phase(&[], 60, now), Phase::Neutral);
+    }
+}
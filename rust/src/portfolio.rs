@@ -0,0 +1,431 @@
+//! How a wallet spreads its volume across tokens
+//! Concentration and diversification of a single wallet's buys
+
+use crate::timing::sorted_by_time;
+use crate::{calculate_whale_score, signed_amount, TokenInteraction, TradeSide, WalletStats};
+use std::collections::{HashMap, HashSet};
+
+/// Total `sol_amount` per token mint
+pub(crate) fn volume_per_token(interactions: &[TokenInteraction]) -> HashMap<&str, f64> {
+    let mut volumes: HashMap<&str, f64> = HashMap::new();
+    for interaction in interactions {
+        *volumes.entry(interaction.token_mint.as_str()).or_insert(0.0) += interaction.sol_amount;
+    }
+    volumes
+}
+
+/// Share of total volume placed in the wallet's single largest token
+///
+/// 1.0 means everything went into one token (always the case with a single
+/// token); returns 0.0 for empty input or zero total volume.
+pub fn concentration_ratio(interactions: &[TokenInteraction]) -> f64 {
+    let volumes = volume_per_token(interactions);
+    let total: f64 = volumes.values().sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+
+    let largest = volumes.values().cloned().fold(0.0, f64::max);
+    largest / total
+}
+
+/// Number of distinct tokens and how evenly volume is spread across them
+///
+/// The diversity score is the Shannon entropy of per-token volume shares
+/// divided by `ln(unique_tokens)`, so it lies in `[0, 1]`: 0 for a single
+/// token (or zero volume), 1 for volume split evenly across every token.
+pub fn token_diversity(interactions: &[TokenInteraction]) -> (usize, f64) {
+    let volumes = volume_per_token(interactions);
+    let unique = volumes.len();
+    let total: f64 = volumes.values().sum();
+    if unique < 2 || total <= 0.0 {
+        return (unique, 0.0);
+    }
+
+    let entropy: f64 = volumes
+        .values()
+        .map(|&v| v / total)
+        .filter(|&p| p > 0.0)
+        .map(|p| -p * p.ln())
+        .sum();
+
+    (unique, entropy / (unique as f64).ln())
+}
+
+/// Fraction of the wallet's distinct tokens that did not rug
+///
+/// Tokens missing from `rugged_tokens` count as survived, so the rate is only
+/// as good as the outcome data. A high rate suggests good token selection.
+/// Returns 0.0 for a wallet with no interactions.
+pub fn token_survival_rate(wallet_interactions: &[TokenInteraction], rugged_tokens: &HashSet<String>) -> f64 {
+    let tokens: HashSet<&str> = wallet_interactions.iter().map(|i| i.token_mint.as_str()).collect();
+    if tokens.is_empty() {
+        return 0.0;
+    }
+
+    let survived = tokens.iter().filter(|mint| !rugged_tokens.contains(**mint)).count();
+    survived as f64 / tokens.len() as f64
+}
+
+/// Whale score discounted by how concentrated the wallet is
+///
+/// Multiplies the score by `1 - penalty * concentration_ratio`, with
+/// `penalty` clamped to 0-1; a penalty of 0 leaves the score unchanged.
+pub fn concentration_adjusted_score(
+    stats: &WalletStats,
+    interactions: &[TokenInteraction],
+    penalty: f64,
+) -> u8 {
+    let factor = 1.0 - penalty.clamp(0.0, 1.0) * concentration_ratio(interactions);
+    (calculate_whale_score(stats) as f64 * factor) as u8
+}
+
+/// Positive price of the interaction's token at its block, if known
+fn block_price(prices: &HashMap<(String, u64), f64>, interaction: &TokenInteraction) -> Option<f64> {
+    prices
+        .get(&(interaction.token_mint.clone(), interaction.block_time))
+        .copied()
+        .filter(|&price| price > 0.0)
+}
+
+/// Volume-weighted average entry price per token for one wallet
+///
+/// `prices` maps `(token_mint, block_time)` to the SOL price per token at
+/// that block. Each buy converts its `sol_amount` into a token quantity at
+/// its block's price, and a token's entry price is SOL spent over tokens
+/// acquired, i.e. the average price per token actually bought. Sells, buys
+/// without a positive price and tokens left with no priced buys are
+/// skipped.
+pub fn twap_entry(
+    wallet_interactions: &[TokenInteraction],
+    prices: &HashMap<(String, u64), f64>,
+) -> HashMap<String, f64> {
+    let mut totals: HashMap<String, (f64, f64)> = HashMap::new();
+    for interaction in wallet_interactions.iter().filter(|i| i.side == TradeSide::Buy) {
+        let Some(price) = block_price(prices, interaction) else { continue };
+        let (spent, quantity) = totals.entry(interaction.token_mint.clone()).or_insert((0.0, 0.0));
+        *spent += interaction.sol_amount;
+        *quantity += interaction.sol_amount / price;
+    }
+
+    totals
+        .into_iter()
+        .filter(|(_, (_, quantity))| *quantity > 0.0)
+        .map(|(mint, (spent, quantity))| (mint, spent / quantity))
+        .collect()
+}
+
+/// Fraction of the wallet's sells executed above its average entry price
+///
+/// Prices are looked up as in `twap_entry`. Interactions are walked in
+/// `block_time` order and each priced sell is compared to the volume-weighted
+/// average price of the priced buys of that token before it, so later buys
+/// never judge an earlier exit. Sells with no price or no earlier priced buy
+/// are excluded, as are tokens that were only bought. Returns 0.0 when no
+/// sell can be judged.
+pub fn profitable_exit_ratio(
+    wallet_interactions: &[TokenInteraction],
+    prices: &HashMap<(String, u64), f64>,
+) -> f64 {
+    let mut entries: HashMap<&str, (f64, f64)> = HashMap::new();
+    let mut judged = 0usize;
+    let mut profitable = 0usize;
+    for interaction in sorted_by_time(wallet_interactions) {
+        let Some(price) = block_price(prices, interaction) else { continue };
+        let (spent, quantity) = entries.entry(interaction.token_mint.as_str()).or_insert((0.0, 0.0));
+        match interaction.side {
+            TradeSide::Buy => {
+                *spent += interaction.sol_amount;
+                *quantity += interaction.sol_amount / price;
+            }
+            TradeSide::Sell if *quantity > 0.0 => {
+                judged += 1;
+                if price > *spent / *quantity {
+                    profitable += 1;
+                }
+            }
+            TradeSide::Sell => {}
+        }
+    }
+
+    if judged == 0 {
+        return 0.0;
+    }
+    profitable as f64 / judged as f64
+}
+
+/// Share of the wallet's priced buy volume bought below `threshold`
+///
+/// Prices are looked up as in `twap_entry`. A high share means the wallet
+/// buys dips rather than chasing tops. Sells and buys without a positive
+/// price are skipped; returns 0.0 when no priced buy volume remains.
+pub fn accumulation_below_price(
+    wallet_interactions: &[TokenInteraction],
+    prices: &HashMap<(String, u64), f64>,
+    threshold: f64,
+) -> f64 {
+    let (below, total) = wallet_interactions
+        .iter()
+        .filter(|i| i.side == TradeSide::Buy)
+        .filter_map(|i| Some((block_price(prices, i)?, i.sol_amount)))
+        .fold((0.0, 0.0), |(below, total), (price, amount)| {
+            (if price < threshold { below + amount } else { below }, total + amount)
+        });
+    if total <= 0.0 {
+        return 0.0;
+    }
+    below / total
+}
+
+/// Latest price at or before `time` in an ascending series (the first price
+/// if `time` predates it)
+fn price_at(series: &[(u64, f64)], time: u64) -> f64 {
+    let after = series.partition_point(|&(t, _)| t <= time);
+    series[after.saturating_sub(1)].1
+}
+
+/// Realized volatility of the wallet's holdings value
+///
+/// `price_series` maps a token mint to `(time, SOL price per token)` points.
+/// Each trade converts its `sol_amount` into a token quantity at the price
+/// in effect at `block_time` (sells reduce the position). The holdings are
+/// then valued at every price timestamp from the wallet's first trade on,
+/// and the result is the population standard deviation of the
+/// period-over-period returns of that value. Tokens without price data are
+/// skipped; returns 0.0 when fewer than two returns can be measured.
+pub fn holdings_volatility(
+    wallet_interactions: &[TokenInteraction],
+    price_series: &HashMap<String, Vec<(u64, f64)>>,
+) -> f64 {
+    let mut series: HashMap<&str, Vec<(u64, f64)>> = HashMap::new();
+    let mut trades: Vec<(&str, u64, f64)> = Vec::new();
+    for interaction in wallet_interactions {
+        let mint = interaction.token_mint.as_str();
+        let points = match price_series.get(mint) {
+            Some(points) if !points.is_empty() => points,
+            _ => continue,
+        };
+        let points = series.entry(mint).or_insert_with(|| {
+            let mut sorted = points.clone();
+            sorted.sort_by_key(|&(t, _)| t);
+            sorted
+        });
+        let price = price_at(points, interaction.block_time);
+        if price > 0.0 {
+            trades.push((mint, interaction.block_time, signed_amount(interaction) / price));
+        }
+    }
+
+    let first_trade = match trades.iter().map(|&(_, t, _)| t).min() {
+        Some(first) => first,
+        None => return 0.0,
+    };
+    let mut times: Vec<u64> = series.values().flatten().map(|&(t, _)| t).filter(|&t| t >= first_trade).collect();
+    times.sort_unstable();
+    times.dedup();
+
+    let values: Vec<f64> = times
+        .iter()
+        .map(|&time| {
+            let mut holdings: HashMap<&str, f64> = HashMap::new();
+            for &(mint, _, quantity) in trades.iter().filter(|&&(_, t, _)| t <= time) {
+                *holdings.entry(mint).or_insert(0.0) += quantity;
+            }
+            holdings.iter().map(|(mint, quantity)| quantity * price_at(&series[mint], time)).sum()
+        })
+        .collect();
+
+    let returns: Vec<f64> = values
+        .windows(2)
+        .filter(|pair| pair[0] > 0.0)
+        .map(|pair| pair[1] / pair[0] - 1.0)
+        .collect();
+    if returns.len() < 2 {
+        return 0.0;
+    }
+
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / n).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_interactions;
+
+    fn buys(trades: &[(&str, f64)]) -> Vec<TokenInteraction> {
+        trades
+            .iter()
+            .map(|&(mint, amount)| TokenInteraction {
+                wallet_address: "addr1".to_string(),
+                token_mint: mint.to_string(),
+                block_time: 1000,
+                sol_amount: amount,
+                is_early_entry: false,
+                side: TradeSide::Buy,
+                source: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_concentration_ratio() {
+        let concentrated = buys(&[("t1", 50.0), ("t1", 40.0), ("t2", 5.0), ("t3", 5.0)]);
+        let diversified = buys(&[("t1", 10.0), ("t2", 10.0), ("t3", 10.0), ("t4", 10.0)]);
+
+        assert!((concentration_ratio(&concentrated) - 0.9).abs() < 1e-9);
+        assert!((concentration_ratio(&diversified) - 0.25).abs() < 1e-9);
+        assert_eq!(concentration_ratio(&buys(&[("t1", 3.0)])), 1.0);
+        assert_eq!(concentration_ratio(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_concentration_adjusted_score() {
+        let concentrated = buys(&[("t1", 90.0), ("t2", 10.0)]);
+        let diversified = buys(&[("t1", 50.0), ("t2", 50.0)]);
+        let stats = process_interactions(&concentrated);
+
+        assert_eq!(concentration_adjusted_score(&stats, &concentrated, 0.0), calculate_whale_score(&stats));
+        assert!(
+            concentration_adjusted_score(&stats, &concentrated, 0.5)
+                < concentration_adjusted_score(&stats, &diversified, 0.5)
+        );
+    }
+
+    #[test]
+    fn test_token_survival_rate() {
+        let rugged: HashSet<String> = ["r1", "r2", "r3"].iter().map(|m| m.to_string()).collect();
+        let careful = buys(&[("t1", 5.0), ("t2", 5.0), ("t1", 2.0), ("t3", 1.0)]);
+        let unlucky = buys(&[("r1", 5.0), ("r2", 5.0), ("r2", 1.0), ("r3", 1.0), ("t1", 1.0)]);
+
+        assert_eq!(token_survival_rate(&careful, &rugged), 1.0);
+        assert!((token_survival_rate(&unlucky, &rugged) - 0.25).abs() < 1e-9);
+        assert_eq!(token_survival_rate(&careful, &HashSet::new()), 1.0);
+        assert_eq!(token_survival_rate(&[], &rugged), 0.0);
+    }
+
+    #[test]
+    fn test_token_diversity() {
+        let single = buys(&[("t1", 5.0), ("t1", 7.0)]);
+        let even = buys(&[("t1", 10.0), ("t2", 10.0), ("t3", 10.0)]);
+        let skewed = buys(&[("t1", 97.0), ("t2", 1.0), ("t3", 2.0)]);
+
+        assert_eq!(token_diversity(&single), (1, 0.0));
+        let (unique, diversity) = token_diversity(&even);
+        assert_eq!(unique, 3);
+        assert!((diversity - 1.0).abs() < 1e-9);
+        let (unique, diversity) = token_diversity(&skewed);
+        assert_eq!(unique, 3);
+        assert!(diversity < 0.2);
+        assert_eq!(token_diversity(&[]), (0, 0.0));
+    }
+
+    #[test]
+    fn test_twap_entry() {
+        let mut interactions = buys(&[("t1", 10.0), ("t1", 30.0), ("t2", 4.0), ("t3", 1.0)]);
+        for (interaction, time) in interactions.iter_mut().zip([1000, 2000, 1000, 1000]) {
+            interaction.block_time = time;
+        }
+        let mut sell = interactions[0].clone();
+        sell.side = TradeSide::Sell;
+        sell.block_time = 3000;
+        interactions.push(sell);
+
+        let key = |mint: &str, time: u64| (mint.to_string(), time);
+        let prices: HashMap<(String, u64), f64> =
+            [(key("t1", 1000), 1.0), (key("t1", 2000), 3.0), (key("t2", 1000), 0.5), (key("t1", 3000), 9.0)]
+                .into_iter()
+                .collect();
+
+        // 10 tokens at 1.0 and 10 tokens at 3.0: 40 SOL for 20 tokens
+        let entries = twap_entry(&interactions, &prices);
+        assert!((entries["t1"] - 2.0).abs() < 1e-9);
+        assert!((entries["t2"] - 0.5).abs() < 1e-9);
+        assert!(!entries.contains_key("t3"));
+        assert!(twap_entry(&interactions, &HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_profitable_exit_ratio() {
+        // (token, time, sol, side, price)
+        let trades = |trades: &[(&str, u64, f64, TradeSide, f64)]| {
+            let mut interactions = buys(&trades.iter().map(|&(mint, _, amount, _, _)| (mint, amount)).collect::<Vec<_>>());
+            let mut prices: HashMap<(String, u64), f64> = HashMap::new();
+            for (interaction, &(mint, time, _, side, price)) in interactions.iter_mut().zip(trades) {
+                interaction.block_time = time;
+                interaction.side = side;
+                prices.insert((mint.to_string(), time), price);
+            }
+            (interactions, prices)
+        };
+        let (buy, sell) = (TradeSide::Buy, TradeSide::Sell);
+
+        // Entries at 1.0 and 3.0 average 1.5 per token; exits at 2.0 and 4.0
+        let (winner, prices) = trades(&[
+            ("t1", 1000, 10.0, buy, 1.0),
+            ("t1", 1100, 10.0, buy, 3.0),
+            ("t1", 1200, 5.0, sell, 2.0),
+            ("t2", 1000, 4.0, buy, 0.5),
+            ("t2", 1300, 4.0, sell, 4.0),
+            ("held", 1000, 4.0, buy, 1.0),
+        ]);
+        assert_eq!(profitable_exit_ratio(&winner, &prices), 1.0);
+
+        // Every exit below entry; the profitable-looking t2 sell predates any t2 buy
+        // and isn't judged
+        let (loser, prices) = trades(&[
+            ("t1", 1000, 10.0, buy, 2.0),
+            ("t1", 1200, 5.0, sell, 1.0),
+            ("t1", 1300, 5.0, sell, 1.5),
+            ("t2", 900, 5.0, sell, 9.0),
+            ("t2", 1000, 5.0, buy, 1.0),
+        ]);
+        assert_eq!(profitable_exit_ratio(&loser, &prices), 0.0);
+        assert_eq!(profitable_exit_ratio(&loser, &HashMap::new()), 0.0);
+    }
+
+    #[test]
+    fn test_accumulation_below_price() {
+        let mut interactions = buys(&[("t1", 30.0), ("t1", 10.0), ("t1", 10.0), ("t1", 99.0)]);
+        for (interaction, time) in interactions.iter_mut().zip([1000, 2000, 3000, 4000]) {
+            interaction.block_time = time;
+        }
+        let priced = |points: &[(u64, f64)]| -> HashMap<(String, u64), f64> {
+            points.iter().map(|&(time, price)| (("t1".to_string(), time), price)).collect()
+        };
+
+        // The 99 SOL buy has no price and is skipped
+        let dip_buyer = priced(&[(1000, 0.8), (2000, 0.9), (3000, 1.5)]);
+        assert!((accumulation_below_price(&interactions, &dip_buyer, 1.0) - 0.8).abs() < 1e-9);
+
+        let top_chaser = priced(&[(1000, 2.0), (2000, 1.8), (3000, 0.5)]);
+        assert!((accumulation_below_price(&interactions, &top_chaser, 1.0) - 0.2).abs() < 1e-9);
+
+        assert_eq!(accumulation_below_price(&interactions, &HashMap::new(), 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_holdings_volatility() {
+        // 10 SOL buys 10 of t1 at price 1 and 5 of t2 at price 2
+        let mut portfolio = buys(&[("t1", 10.0), ("t2", 10.0)]);
+        portfolio.extend(buys(&[("unpriced", 50.0)]));
+        let series = |prices: &[f64]| -> Vec<(u64, f64)> {
+            prices.iter().enumerate().map(|(i, &p)| (1000 + i as u64 * 60, p)).collect()
+        };
+
+        // Value goes 20 -> 30 -> 20: returns +50% and -33%
+        let swinging: HashMap<String, Vec<(u64, f64)>> =
+            [("t1".to_string(), series(&[1.0, 2.0, 1.0])), ("t2".to_string(), series(&[2.0, 2.0, 2.0]))]
+                .into_iter()
+                .collect();
+        assert!((holdings_volatility(&portfolio, &swinging) - 5.0 / 12.0).abs() < 1e-9);
+
+        let flat: HashMap<String, Vec<(u64, f64)>> =
+            [("t1".to_string(), series(&[1.0, 1.0, 1.0])), ("t2".to_string(), series(&[2.0, 2.0, 2.0]))]
+                .into_iter()
+                .collect();
+        assert!(holdings_volatility(&portfolio, &flat).abs() < 1e-12);
+        assert_eq!(holdings_volatility(&portfolio, &HashMap::new()), 0.0);
+    }
+}
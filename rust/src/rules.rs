@@ -0,0 +1,267 @@
+//! Analyst-defined threshold rules
+//! Scores from a data-driven rule set instead of the fixed formula
+
+use crate::{clamped_early_count, WalletStats};
+use std::fmt;
+
+/// Most points a rule set may award in total
+pub const MAX_RULE_POINTS: f64 = 100.0;
+
+/// A `WalletStats` value a rule condition can test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RuleFeature {
+    /// `early_entry_count / interaction_count` (0 without interactions)
+    EarlyRatio,
+    EarlyEntryCount,
+    InteractionCount,
+    AverageEntrySize,
+    TotalVolume,
+    NetVolume,
+    Winrate,
+    /// `avg_realized_multiple`; conditions on it fail when it's unknown
+    RealizedMultiple,
+}
+
+impl RuleFeature {
+    /// Value of this feature for `stats`, `None` when it's unknown
+    pub fn value(self, stats: &WalletStats) -> Option<f64> {
+        let value = match self {
+            RuleFeature::EarlyRatio => {
+                if stats.interaction_count == 0 {
+                    0.0
+                } else {
+                    clamped_early_count(stats).0 as f64 / stats.interaction_count as f64
+                }
+            }
+            RuleFeature::EarlyEntryCount => clamped_early_count(stats).0 as f64,
+            RuleFeature::InteractionCount => stats.interaction_count as f64,
+            RuleFeature::AverageEntrySize => stats.average_entry_size,
+            RuleFeature::TotalVolume => stats.total_volume_sol,
+            RuleFeature::NetVolume => stats.net_volume_sol,
+            RuleFeature::Winrate => stats.winrate_proxy,
+            RuleFeature::RealizedMultiple => return stats.avg_realized_multiple,
+        };
+        Some(value)
+    }
+}
+
+/// How a condition compares a feature against its threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Comparison {
+    Above,
+    AtLeast,
+    Below,
+    AtMost,
+}
+
+/// `feature <comparison> threshold`, e.g. "early_ratio above 0.5"
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleCondition {
+    pub feature: RuleFeature,
+    pub comparison: Comparison,
+    pub threshold: f64,
+}
+
+impl RuleCondition {
+    pub fn new(feature: RuleFeature, comparison: Comparison, threshold: f64) -> Self {
+        Self { feature, comparison, threshold }
+    }
+
+    /// Whether `stats` satisfies the condition (never for an unknown or NaN value)
+    pub fn matches(&self, stats: &WalletStats) -> bool {
+        let Some(value) = self.feature.value(stats) else {
+            return false;
+        };
+        match self.comparison {
+            Comparison::Above => value > self.threshold,
+            Comparison::AtLeast => value >= self.threshold,
+            Comparison::Below => value < self.threshold,
+            Comparison::AtMost => value <= self.threshold,
+        }
+    }
+}
+
+/// Award `points` when every condition holds
+///
+/// Negative points act as penalties. A rule without conditions always fires.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rule {
+    pub name: String,
+    pub conditions: Vec<RuleCondition>,
+    pub points: f64,
+}
+
+/// Why a list of rules doesn't form a valid `RuleSet`
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleSetError {
+    /// A rule's points are NaN or infinite
+    InvalidPoints { rule: String, points: f64 },
+    /// The positive points of all rules add up to more than `MAX_RULE_POINTS`
+    PointsExceedMax(f64),
+}
+
+impl fmt::Display for RuleSetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleSetError::InvalidPoints { rule, points } => {
+                write!(f, "rule {} has invalid points {}", rule, points)
+            }
+            RuleSetError::PointsExceedMax(total) => {
+                write!(f, "rules award up to {} points, more than {}", total, MAX_RULE_POINTS)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RuleSetError {}
+
+/// Validated list of scoring rules
+///
+/// Construction checks that the rules can award at most `MAX_RULE_POINTS`
+/// between them, so a wallet matching every rule scores at most 100. With
+/// the `serde` feature a rule set (de)serializes as a plain list of rules,
+/// and deserialization runs the same check.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Vec<Rule>", into = "Vec<Rule>"))]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    pub fn new(rules: Vec<Rule>) -> Result<Self, RuleSetError> {
+        if let Some(rule) = rules.iter().find(|rule| !rule.points.is_finite()) {
+            return Err(RuleSetError::InvalidPoints {
+                rule: rule.name.clone(),
+                points: rule.points,
+            });
+        }
+
+        let possible: f64 = rules.iter().map(|rule| rule.points.max(0.0)).sum();
+        if possible > MAX_RULE_POINTS {
+            return Err(RuleSetError::PointsExceedMax(possible));
+        }
+        Ok(Self { rules })
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+}
+
+impl TryFrom<Vec<Rule>> for RuleSet {
+    type Error = RuleSetError;
+
+    fn try_from(rules: Vec<Rule>) -> Result<Self, Self::Error> {
+        Self::new(rules)
+    }
+}
+
+impl From<RuleSet> for Vec<Rule> {
+    fn from(rule_set: RuleSet) -> Self {
+        rule_set.rules
+    }
+}
+
+/// Score a wallet with an analyst-defined rule set
+///
+/// Sums the points of every rule whose conditions all hold, clamped to
+/// 0-100 (penalties can't push the score below 0), then truncates toward
+/// zero like `calculate_whale_score`: 79.9 points score 79.
+pub fn score_with_rules(stats: &WalletStats, rules: &RuleSet) -> u8 {
+    let points: f64 = rules
+        .rules
+        .iter()
+        .filter(|rule| rule.conditions.iter().all(|condition| condition.matches(stats)))
+        .map(|rule| rule.points)
+        .sum();
+    points.clamp(0.0, MAX_RULE_POINTS) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(early_entry_count: u64, average_entry_size: f64) -> WalletStats {
+        WalletStats {
+            address: "addr1".to_string(),
+            total_volume_sol: average_entry_size * 10.0,
+            interaction_count: 10,
+            average_entry_size,
+            early_entry_count,
+            winrate_proxy: 0.5,
+            avg_realized_multiple: None,
+            net_volume_sol: average_entry_size * 10.0,
+        }
+    }
+
+    fn rule(name: &str, conditions: Vec<RuleCondition>, points: f64) -> Rule {
+        Rule { name: name.to_string(), conditions, points }
+    }
+
+    fn sample_rules() -> RuleSet {
+        RuleSet::new(vec![
+            rule(
+                "early_and_large",
+                vec![
+                    RuleCondition::new(RuleFeature::EarlyRatio, Comparison::Above, 0.5),
+                    RuleCondition::new(RuleFeature::AverageEntrySize, Comparison::Above, 10.0),
+                ],
+                25.0,
+            ),
+            rule("active", vec![RuleCondition::new(RuleFeature::InteractionCount, Comparison::AtLeast, 10.0)], 15.0),
+            rule("proven", vec![RuleCondition::new(RuleFeature::RealizedMultiple, Comparison::AtLeast, 2.0)], 40.0),
+            rule("tiny", vec![RuleCondition::new(RuleFeature::AverageEntrySize, Comparison::Below, 1.0)], -30.0),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_score_with_rules() {
+        let rules = sample_rules();
+
+        assert_eq!(score_with_rules(&stats(6, 20.0), &rules), 40);
+        // Early but small: only "active" fires
+        assert_eq!(score_with_rules(&stats(6, 5.0), &rules), 15);
+        let proven = WalletStats { avg_realized_multiple: Some(3.0), ..stats(6, 20.0) };
+        assert_eq!(score_with_rules(&proven, &rules), 80);
+        // The penalty outweighs "active", bottoming out at 0
+        assert_eq!(score_with_rules(&stats(0, 0.5), &rules), 0);
+
+        let fractional = RuleSet::new(vec![rule("almost", vec![], 79.9)]).unwrap();
+        assert_eq!(score_with_rules(&stats(0, 1.0), &fractional), 79);
+    }
+
+    #[test]
+    fn test_rule_set_caps_possible_points() {
+        let too_many = vec![rule("a", vec![], 60.0), rule("b", vec![], 50.0), rule("penalty", vec![], -20.0)];
+        assert_eq!(RuleSet::new(too_many).unwrap_err(), RuleSetError::PointsExceedMax(110.0));
+        assert!(matches!(
+            RuleSet::new(vec![rule("nan", vec![], f64::NAN)]),
+            Err(RuleSetError::InvalidPoints { .. })
+        ));
+        assert!(RuleSet::new(vec![rule("all", vec![], 100.0)]).is_ok());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_rule_set_from_json() {
+        let json = r#"[
+            {"name": "early", "conditions": [{"feature": "early_ratio", "comparison": "above", "threshold": 0.5}], "points": 30.0},
+            {"name": "base", "conditions": [], "points": 10.0}
+        ]"#;
+        let rules: RuleSet = serde_json::from_str(json).unwrap();
+        assert_eq!(score_with_rules(&stats(6, 1.0), &rules), 40);
+
+        let round_trip: RuleSet = serde_json::from_str(&serde_json::to_string(&rules).unwrap()).unwrap();
+        assert_eq!(round_trip, rules);
+        let over = r#"[{"name": "all", "conditions": [], "points": 101.0}]"#;
+        assert!(serde_json::from_str::<RuleSet>(over).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+//! Deterministic sampling utilities
+//! Used to bound the cost of statistics over very large interaction lists
+
+use crate::{calculate_whale_score, process_interactions, TokenInteraction};
+
+/// Small seeded pseudo-random generator (SplitMix64)
+///
+/// Not cryptographically secure; it only exists so sampling is reproducible
+/// for a given seed without pulling in an RNG dependency.
+pub(crate) struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform integer in `0..bound` (`bound` must be non-zero)
+    pub(crate) fn next_below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    /// Uniform float in `[0, 1)`
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Downsample interactions with seeded reservoir sampling
+///
+/// Returns at most `k` interactions chosen uniformly at random; the same seed
+/// always yields the same sample. When `k >= interactions.len()` every
+/// interaction is returned in its original order.
+pub fn sample_interactions(
+    interactions: &[TokenInteraction],
+    k: usize,
+    seed: u64,
+) -> Vec<TokenInteraction> {
+    if k >= interactions.len() {
+        return interactions.to_vec();
+    }
+
+    let mut rng = SeededRng::new(seed);
+    let mut reservoir: Vec<TokenInteraction> = interactions[..k].to_vec();
+
+    for (i, interaction) in interactions.iter().enumerate().skip(k) {
+        let j = rng.next_below(i as u64 + 1) as usize;
+        if j < k {
+            reservoir[j] = interaction.clone();
+        }
+    }
+
+    reservoir
+}
+
+/// Bootstrap 90% interval `(5th, 95th percentile)` of the whale score
+///
+/// Draws `resamples` seeded resamples of the interactions with replacement
+/// (each the size of the input), scores each with `process_interactions` and
+/// `calculate_whale_score`, and reads the percentiles off the sorted scores
+/// by nearest rank. Consistent histories give a narrow interval; a few
+/// very different interactions give a wide one. With no interactions the
+/// interval is `(0, 0)`, and with `resamples == 0` it collapses to the
+/// point score.
+pub fn score_confidence_interval(interactions: &[TokenInteraction], resamples: usize, seed: u64) -> (u8, u8) {
+    if interactions.is_empty() {
+        return (0, 0);
+    }
+    if resamples == 0 {
+        let score = calculate_whale_score(&process_interactions(interactions));
+        return (score, score);
+    }
+
+    let mut rng = SeededRng::new(seed);
+    let n = interactions.len();
+    let mut scores: Vec<u8> = (0..resamples)
+        .map(|_| {
+            let resample: Vec<TokenInteraction> =
+                (0..n).map(|_| interactions[rng.next_below(n as u64) as usize].clone()).collect();
+            calculate_whale_score(&process_interactions(&resample))
+        })
+        .collect();
+    scores.sort_unstable();
+
+    let at = |q: f64| scores[((scores.len() - 1) as f64 * q).round() as usize];
+    (at(0.05), at(0.95))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TradeSide;
+
+    fn interactions(n: usize) -> Vec<TokenInteraction> {
+        (0..n)
+            .map(|i| TokenInteraction {
+                wallet_address: "addr1".to_string(),
+                token_mint: format!("token{}", i),
+                block_time: 1000 + i as u64,
+                sol_amount: i as f64,
+                is_early_entry: i % 2 == 0,
+                side: TradeSide::Buy,
+                source: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sample_is_deterministic_for_seed() {
+        let data = interactions(1000);
+
+        let first = sample_interactions(&data, 10, 42);
+        let second = sample_interactions(&data, 10, 42);
+        let other = sample_interactions(&data, 10, 7);
+
+        assert_eq!(first.len(), 10);
+        let mints = |s: &[TokenInteraction]| s.iter().map(|i| i.token_mint.clone()).collect::<Vec<_>>();
+        assert_eq!(mints(&first), mints(&second));
+        assert_ne!(mints(&first), mints(&other));
+    }
+
+    #[test]
+    fn test_score_confidence_interval() {
+        let consistent: Vec<TokenInteraction> = interactions(40)
+            .into_iter()
+            .map(|i| TokenInteraction { sol_amount: 10.0, is_early_entry: true, ..i })
+            .collect();
+        let (low, high) = score_confidence_interval(&consistent, 200, 42);
+        assert_eq!(low, high);
+
+        // One large early buy and three tiny late ones
+        let mut sparse = interactions(4);
+        sparse[0].sol_amount = 200.0;
+        for (i, interaction) in sparse.iter_mut().enumerate() {
+            interaction.is_early_entry = i == 0;
+        }
+        let (low, high) = score_confidence_interval(&sparse, 200, 42);
+        assert!(high - low >= 30, "interval ({}, {})", low, high);
+        assert_eq!(score_confidence_interval(&sparse, 200, 42), (low, high));
+
+        let point = calculate_whale_score(&process_interactions(&sparse));
+        assert_eq!(score_confidence_interval(&sparse, 0, 42), (point, point));
+        assert_eq!(score_confidence_interval(&[], 200, 42), (0, 0));
+    }
+
+    #[test]
+    fn test_sample_returns_everything_when_k_exceeds_len() {
+        let data = interactions(5);
+
+        assert_eq!(sample_interactions(&data, 5, 1).len(), 5);
+        assert_eq!(sample_interactions(&data, 50, 1).len(), 5);
+        assert!(sample_interactions(&[], 3, 1).is_empty());
+    }
+}
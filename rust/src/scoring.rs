@@ -1,8 +1,16 @@
-/// Advanced scoring algorithms for whale detection
-/// High-performance implementations using Rust
+//! Advanced scoring algorithms for whale detection
+//! High-performance implementations using Rust
 
-use crate::{WalletStats, TokenInteraction};
-use std::collections::HashMap;
+use crate::{
+    calculate_whale_score, clamped_early_count, decay_weight, early_volume_ratio, hash_wallet_address, normalize, process_interactions,
+    process_interactions_decayed, profit_fraction, raw_subscores_with, round_number_ratio, uniformity_score, whale_score_components_with,
+    whale_score_total, WalletStats, TokenInteraction, TradeSide, FULL_PROFIT_MULTIPLE, ROUND_NUMBER_TOLERANCE,
+};
+use crate::coordination::find_root;
+use crate::sampling::SeededRng;
+use crate::timing::sorted_by_time;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 /// Calculate dynamic score weights based on market conditions
 pub struct DynamicScorer {
@@ -26,22 +34,538 @@ impl Default for DynamicScorer {
 impl DynamicScorer {
     /// Calculate score with custom weights
     pub fn calculate_score(&self, stats: &WalletStats) -> u8 {
+        self.total(stats) as u8
+    }
+
+    /// Change in the (unrounded) score when each weight alone rises by `delta`
+    ///
+    /// Returns `(component, change)` for `early_entry`, `buy_size`,
+    /// `repetition` and `profit`. A component's change is `delta` times the
+    /// fraction of its weight the wallet earns, so a component the wallet
+    /// doesn't score on is insensitive to its weight, while one it maxes out
+    /// passes the whole `delta` through, until the 100-point cap absorbs it.
+    pub fn weight_sensitivity(&self, stats: &WalletStats, delta: f64) -> Vec<(String, f64)> {
+        let base = self.total(stats);
+        let names = ["early_entry", "buy_size", "repetition", "profit"];
+
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let mut weights = self.weights();
+                weights[i] = (weights[i] + delta).max(0.0);
+                let perturbed = DynamicScorer {
+                    early_entry_weight: weights[0],
+                    buy_size_weight: weights[1],
+                    repetition_weight: weights[2],
+                    profit_weight: weights[3],
+                };
+                (name.to_string(), perturbed.total(stats) - base)
+            })
+            .collect()
+    }
+
+    /// Score before truncation to an integer (0.0-100.0)
+    fn total(&self, stats: &WalletStats) -> f64 {
         if stats.interaction_count == 0 {
-            return 0;
+            return 0.0;
         }
 
-        let early_ratio = stats.early_entry_count as f64 / stats.interaction_count as f64;
-        let early_score = (early_ratio * self.early_entry_weight).min(self.early_entry_weight);
+        let (early_entry_count, _) = clamped_early_count(stats);
+        let early_ratio = early_entry_count as f64 / stats.interaction_count as f64;
+        let early_score = normalize(early_ratio, 1.0, self.early_entry_weight);
         
-        let size_score = ((stats.average_entry_size / 50.0) * self.buy_size_weight).min(self.buy_size_weight);
+        let size_score = normalize(stats.average_entry_size, 50.0, self.buy_size_weight);
         
-        let rep_score = ((stats.interaction_count as f64 / 50.0) * self.repetition_weight).min(self.repetition_weight);
+        let rep_score = normalize(stats.interaction_count as f64, 50.0, self.repetition_weight);
         
-        let profit_score = stats.winrate_proxy * self.profit_weight;
+        let profit_score = profit_fraction(stats) * self.profit_weight;
 
         let total = early_score + size_score + rep_score + profit_score;
+        total.min(100.0)
+    }
+
+    /// Tune the four weights to separate labeled whale / non-whale examples
+    ///
+    /// Runs `epochs` rounds of logistic-regression-style gradient descent with
+    /// learning rate `lr`, treating `calculate_score >= 50` as "whale". After
+    /// every round the weights are clamped to be non-negative and rescaled to
+    /// sum to 100. Does nothing when `examples` is empty.
+    pub fn fit(&mut self, examples: &[(WalletStats, bool)], lr: f64, epochs: usize) {
+        if examples.is_empty() {
+            return;
+        }
+
+        let features: Vec<([f64; 4], f64)> = examples
+            .iter()
+            .map(|(stats, is_whale)| (Self::component_fractions(stats), if *is_whale { 1.0 } else { 0.0 }))
+            .collect();
+
+        for _ in 0..epochs {
+            let weights = self.weights();
+            let mut gradient = [0.0; 4];
+
+            for (fractions, label) in &features {
+                let score: f64 = weights.iter().zip(fractions).map(|(w, f)| w * f).sum();
+                let predicted = 1.0 / (1.0 + (-(score - WHALE_DECISION_SCORE) / 10.0).exp());
+                for (g, f) in gradient.iter_mut().zip(fractions) {
+                    *g += (predicted - label) * f / 10.0;
+                }
+            }
+
+            let mut updated = [0.0; 4];
+            for i in 0..4 {
+                updated[i] = (weights[i] - lr * gradient[i] / features.len() as f64).max(0.0);
+            }
+            self.set_weights(updated);
+        }
+    }
+
+    /// Fraction of examples whose `calculate_score >= 50` agrees with the label
+    pub fn accuracy(&self, examples: &[(WalletStats, bool)]) -> f64 {
+        if examples.is_empty() {
+            return 0.0;
+        }
+
+        let correct = examples
+            .iter()
+            .filter(|(stats, is_whale)| (self.calculate_score(stats) as f64 >= WHALE_DECISION_SCORE) == *is_whale)
+            .count();
+        correct as f64 / examples.len() as f64
+    }
+
+    fn weights(&self) -> [f64; 4] {
+        [self.early_entry_weight, self.buy_size_weight, self.repetition_weight, self.profit_weight]
+    }
+
+    /// Store weights rescaled to sum to 100 (equal weights if all are zero)
+    fn set_weights(&mut self, weights: [f64; 4]) {
+        let total: f64 = weights.iter().sum();
+        let scaled = if total > 0.0 {
+            weights.map(|w| w / total * 100.0)
+        } else {
+            [25.0; 4]
+        };
+        self.early_entry_weight = scaled[0];
+        self.buy_size_weight = scaled[1];
+        self.repetition_weight = scaled[2];
+        self.profit_weight = scaled[3];
+    }
+
+    /// Each component's share of its weight, as used by `calculate_score`
+    fn component_fractions(stats: &WalletStats) -> [f64; 4] {
+        if stats.interaction_count == 0 {
+            return [0.0; 4];
+        }
+
+        [
+            clamped_early_count(stats).0 as f64 / stats.interaction_count as f64,
+            normalize(stats.average_entry_size, 50.0, 1.0),
+            normalize(stats.interaction_count as f64, 50.0, 1.0),
+            profit_fraction(stats),
+        ]
+    }
+}
+
+/// Score at or above which `DynamicScorer::fit` treats a wallet as a whale
+const WHALE_DECISION_SCORE: f64 = 50.0;
+
+/// Ceilings of the four whale score components, in component order
+pub(crate) const COMPONENT_CEILINGS: [f64; 4] = [40.0, 30.0, 20.0, 10.0];
+
+/// Normalization curve mapping a value's fraction of its ceiling to points
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScoringCurve {
+    /// Points grow proportionally with size (the original behavior)
+    #[default]
+    Linear,
+    /// Compresses the high end: `ln(1 + 9x) / ln(10)`
+    Log,
+    /// Milder compression than `Log`: `sqrt(x)`
+    Sqrt,
+}
+
+impl ScoringCurve {
+    /// Map a normalized value (1.0 = at the ceiling) through the curve
+    pub fn apply(self, x: f64) -> f64 {
+        match self {
+            ScoringCurve::Linear => x,
+            ScoringCurve::Log => (1.0 + 9.0 * x.max(0.0)).ln() / 10f64.ln(),
+            ScoringCurve::Sqrt => x.max(0.0).sqrt(),
+        }
+    }
+}
+
+/// Tunable options for the configurable scoring entry points
+#[derive(Debug, Clone)]
+pub struct ScoringConfig {
+    /// Fraction of its ceiling (0-1) a component must reach before it contributes any points
+    pub component_floor: f64,
+    /// Strength (0-1) of the imbalance penalty; 0 disables it, 1 lets it zero the score
+    pub balance_penalty: f64,
+    /// Curve applied to the average entry size and total volume normalization
+    pub volume_curve: ScoringCurve,
+    /// Points (0-40) of the early entry component given to the early ratio;
+    /// the rest of the 40 go to the raw early count
+    ///
+    /// The default 20 is the original 20/20 split. Raising it (e.g. 30 for a
+    /// 30/10 split) favors precise early buyers over high-volume ones.
+    pub early_ratio_points: f64,
+    /// Neutral prior (0-100) for wallets with too little data to judge
+    ///
+    /// Wallets with fewer than `cold_start_threshold` interactions score at
+    /// least this much instead of the near-zero score their sparse stats
+    /// earn. The default of 0 keeps the original behavior.
+    pub cold_start_score: u8,
+    /// Interaction count below which `cold_start_score` applies
+    pub cold_start_threshold: u64,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            component_floor: 0.05,
+            balance_penalty: 0.5,
+            volume_curve: ScoringCurve::Linear,
+            early_ratio_points: 20.0,
+            cold_start_score: 0,
+            cold_start_threshold: 5,
+        }
+    }
+}
+
+/// Calculate whale score using the curves in `config`
+///
+/// With the default config this matches `calculate_whale_score` exactly.
+/// Sparse wallets are raised to `config.cold_start_score`.
+pub fn calculate_whale_score_with_config(stats: &WalletStats, config: &ScoringConfig) -> u8 {
+    let score = if stats.interaction_count == 0 {
+        0
+    } else {
+        let total: f64 = whale_score_components_with(stats, config).iter().sum();
         (total.min(100.0)) as u8
+    };
+
+    if stats.interaction_count < config.cold_start_threshold {
+        score.max(config.cold_start_score.min(100))
+    } else {
+        score
+    }
+}
+
+/// What to do when an intermediate score value falls outside its range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClampPolicy {
+    /// Silently clamp to the documented range (what `calculate_whale_score` does)
+    #[default]
+    Saturate,
+    /// Report the first out-of-range value as an error
+    Error,
+}
+
+/// An intermediate whale score value outside its documented range
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScoreRangeError {
+    /// A sub-score fell outside `[0, ceiling]` before clamping
+    ComponentOutOfRange { component: &'static str, value: f64, ceiling: f64 },
+    /// The component total exceeded 100 before capping
+    TotalOutOfRange(f64),
+}
+
+impl fmt::Display for ScoreRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScoreRangeError::ComponentOutOfRange { component, value, ceiling } => {
+                write!(f, "{} score {} is outside [0, {}]", component, value, ceiling)
+            }
+            ScoreRangeError::TotalOutOfRange(total) => write!(f, "total score {} exceeds 100", total),
+        }
+    }
+}
+
+impl std::error::Error for ScoreRangeError {}
+
+/// Calculate whale score, optionally rejecting out-of-range intermediates
+///
+/// Under `ClampPolicy::Saturate` this is `calculate_whale_score`. Under
+/// `ClampPolicy::Error` the sub-scores are checked before any saturation: a
+/// raw total above 100 is reported first, then the first sub-score outside
+/// `[0, weight]` (or NaN). Saturating magnitudes count too, so a 100 SOL
+/// average entry is an error rather than 20 points. Inputs that stay in
+/// range score exactly as with `Saturate`, and wallets with no interactions
+/// always score 0.
+pub fn calculate_whale_score_with_policy(stats: &WalletStats, policy: ClampPolicy) -> Result<u8, ScoreRangeError> {
+    if policy == ClampPolicy::Saturate || stats.interaction_count == 0 {
+        return Ok(calculate_whale_score(stats));
+    }
+
+    // The same sub-scores `calculate_whale_score` clamps, without sanitizing the early count
+    let subscores = raw_subscores_with(stats, stats.early_entry_count as f64, &ScoringConfig::default());
+    let total: f64 = subscores.iter().map(|subscore| subscore.raw_points()).sum();
+    if total > 100.0 {
+        return Err(ScoreRangeError::TotalOutOfRange(total));
+    }
+
+    for subscore in subscores {
+        let value = subscore.raw_points();
+        if value.is_nan() || !(0.0..=subscore.weight).contains(&value) {
+            return Err(ScoreRangeError::ComponentOutOfRange {
+                component: subscore.name,
+                value,
+                ceiling: subscore.weight,
+            });
+        }
+    }
+
+    Ok(calculate_whale_score(stats))
+}
+
+/// Score a wallet on its current relevance rather than its whole history
+///
+/// Builds stats with `process_interactions_decayed` and scores them with
+/// `calculate_whale_score_with_config`. A wallet whose activity is all old
+/// scores lower than one with identical activity that happened recently.
+pub fn calculate_whale_score_decayed(
+    interactions: &[TokenInteraction],
+    now: u64,
+    half_life: u64,
+    config: &ScoringConfig,
+) -> u8 {
+    calculate_whale_score_with_config(&process_interactions_decayed(interactions, now, half_life), config)
+}
+
+/// Repetition component (0-20 points) from a time-decayed interaction count
+///
+/// Each interaction counts `0.5^(age / half_life)` toward the 50 that earn
+/// the full 20 points, as in `process_interactions_decayed`, so recent
+/// activity counts almost fully while old history fades toward nothing. A
+/// dormant wallet with a huge historical count no longer maxes out
+/// repetition.
+pub fn repetition_score_decayed(interactions: &[TokenInteraction], now: u64, half_life: u64) -> f64 {
+    let weighted_count: f64 = interactions.iter().map(|i| decay_weight(i, now, half_life)).sum();
+    normalize(weighted_count, 50.0, 20.0)
+}
+
+/// Score a wallet with the early ratio blended toward its early volume share
+///
+/// The ratio half of the early entry component (`config.early_ratio_points`)
+/// is earned by `(1 - volume_blend) * count_ratio + volume_blend *
+/// early_volume_ratio`, with `volume_blend` clamped to 0-1; every other
+/// component is unchanged. A blend of 0 matches
+/// `calculate_whale_score_with_config` apart from its cold-start floor.
+pub fn calculate_whale_score_early_volume(
+    interactions: &[TokenInteraction],
+    volume_blend: f64,
+    config: &ScoringConfig,
+) -> u8 {
+    let stats = process_interactions(interactions);
+    if stats.interaction_count == 0 {
+        return 0;
+    }
+
+    let blend = if volume_blend.is_nan() { 0.0 } else { volume_blend.clamp(0.0, 1.0) };
+    let count_ratio = clamped_early_count(&stats).0 as f64 / stats.interaction_count as f64;
+    let mixed_ratio = (1.0 - blend) * count_ratio + blend * early_volume_ratio(interactions);
+    let ratio_points = config.early_ratio_points.clamp(0.0, 40.0);
+
+    let mut components = whale_score_components_with(&stats, config);
+    components[0] += normalize(mixed_ratio, 1.0, ratio_points) - normalize(count_ratio, 1.0, ratio_points);
+    (components.iter().sum::<f64>().clamp(0.0, 100.0)) as u8
+}
+
+/// Whale score discounted by how automated the wallet's buying looks
+///
+/// The base score is `calculate_whale_score_with_config(stats, config)`,
+/// multiplied by `1 - risk_fraction` where
+///
+/// `risk_fraction = uniformity / 100 * (1 + formula_sizing) / 2`
+///
+/// with `uniformity` from `uniformity_score` and `formula_sizing` the share
+/// of buys that aren't round (`1 - round_number_ratio` at
+/// `ROUND_NUMBER_TOLERANCE`, 0 without buys). Irregular timing alone
+/// clears a wallet; metronomic timing halves the score, and metronomic
+/// timing with formula-sized buys removes it entirely.
+pub fn calculate_whale_score_penalized(
+    stats: &WalletStats,
+    interactions: &[TokenInteraction],
+    config: &ScoringConfig,
+) -> u8 {
+    let base = calculate_whale_score_with_config(stats, config) as f64;
+    let has_buys = interactions.iter().any(|i| i.side == TradeSide::Buy);
+
+    let uniformity = uniformity_score(interactions) / 100.0;
+    let formula_sizing = if has_buys { 1.0 - round_number_ratio(interactions, ROUND_NUMBER_TOLERANCE) } else { 0.0 };
+    let risk_fraction = (uniformity * (1.0 + formula_sizing) / 2.0).clamp(0.0, 1.0);
+
+    (base * (1.0 - risk_fraction)).round() as u8
+}
+
+/// Winrate assumed for a wallet with no trustworthy history
+pub const NEUTRAL_WINRATE: f64 = 0.5;
+
+/// `winrate` decayed toward `NEUTRAL_WINRATE` as the wallet goes stale
+///
+/// Shorthand for `decayed_winrate_toward` with the default neutral target.
+/// To score with it, replace `winrate_proxy` before scoring:
+/// `WalletStats { winrate_proxy: decayed_winrate(..), ..stats }`.
+pub fn decayed_winrate(winrate: f64, seconds_since_last_trade: u64, half_life: u64) -> f64 {
+    decayed_winrate_toward(winrate, seconds_since_last_trade, half_life, NEUTRAL_WINRATE)
+}
+
+/// `winrate` decayed toward `neutral` with the given half-life
+///
+/// The distance from `neutral` halves every `half_life` seconds (at least 1)
+/// since the last trade, so a fresh wallet keeps its winrate and a long-idle
+/// one converges on `neutral`.
+pub fn decayed_winrate_toward(winrate: f64, seconds_since_last_trade: u64, half_life: u64, neutral: f64) -> f64 {
+    let retained = 0.5f64.powf(seconds_since_last_trade as f64 / half_life.max(1) as f64);
+    neutral + (winrate - neutral) * retained
+}
+
+/// Calculate whale score while penalizing one-dimensional wallets
+///
+/// Components below `config.component_floor` of their ceiling are dropped, then
+/// the total is multiplied by `1 - balance_penalty * (1 - min/max)`, where
+/// `min/max` is the ratio between the weakest and strongest component expressed
+/// as fractions of their ceilings. A perfectly balanced wallet keeps its full score.
+pub fn calculate_whale_score_balanced(stats: &WalletStats, config: &ScoringConfig) -> u8 {
+    if stats.interaction_count == 0 {
+        return 0;
+    }
+
+    let components = whale_score_components_with(stats, config);
+    let mut fractions = [0.0; 4];
+    let mut total = 0.0;
+
+    for (i, points) in components.iter().enumerate() {
+        let fraction = (points / COMPONENT_CEILINGS[i]).clamp(0.0, 1.0);
+        if fraction >= config.component_floor {
+            fractions[i] = fraction;
+            total += points;
+        }
     }
+
+    let max = fractions.iter().cloned().fold(0.0, f64::max);
+    let min = fractions.iter().cloned().fold(1.0, f64::min);
+    let balance_ratio = if max > 0.0 { min / max } else { 0.0 };
+    let factor = 1.0 - config.balance_penalty.clamp(0.0, 1.0) * (1.0 - balance_ratio);
+
+    ((total * factor).min(100.0)) as u8
+}
+
+/// Leave-one-out contribution of each interaction to the wallet's score
+///
+/// Entry `i` is the (unrounded) whale score of all interactions minus the
+/// score without interaction `i`, aligned to input order. Positive values
+/// raised the score. This re-processes the wallet once per interaction, so
+/// it's O(n²) and meant for explaining individual wallets.
+pub fn interaction_contributions(interactions: &[TokenInteraction]) -> Vec<f64> {
+    let full = whale_score_total(&process_interactions(interactions));
+
+    (0..interactions.len())
+        .map(|skip| {
+            let without: Vec<TokenInteraction> = interactions
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != skip)
+                .map(|(_, interaction)| interaction.clone())
+                .collect();
+            full - whale_score_total(&process_interactions(&without))
+        })
+        .collect()
+}
+
+/// Early entries weighted by how their tokens turned out
+///
+/// Each early entry contributes the token's realized multiple from
+/// `token_outcomes` (e.g. 100.0 for a 100x, 0.0 for a rug; negative values
+/// count as 0). Tokens missing from the map contribute the unweighted
+/// baseline of 1.0, so with no outcome data the result equals the number of
+/// early entries.
+pub fn outcome_weighted_early_score(
+    wallet_interactions: &[TokenInteraction],
+    token_outcomes: &HashMap<String, f64>,
+) -> f64 {
+    wallet_interactions
+        .iter()
+        .filter(|i| i.is_early_entry)
+        .map(|i| token_outcomes.get(&i.token_mint).map_or(1.0, |&multiple| multiple.max(0.0)))
+        .sum()
+}
+
+/// Market cap (in the units of `token_caps`) at or below which an early entry earns full weight
+pub const MICRO_CAP_REFERENCE: f64 = 1_000.0;
+
+/// Early entries weighted inversely by their token's market cap
+///
+/// Each early entry into a token with cap `c` in `token_caps` contributes
+/// `min(1, MICRO_CAP_REFERENCE / c)`: micro-caps count fully, a token ten
+/// times the reference counts a tenth. Sniping a micro-cap is riskier and a
+/// stronger signal than getting into a large-cap early. Tokens missing from
+/// `token_caps` (or with a non-positive cap) contribute the unweighted
+/// baseline of 1.0, so with no cap data the result equals the number of
+/// early entries, as in `outcome_weighted_early_score`.
+pub fn cap_weighted_early_score(wallet_interactions: &[TokenInteraction], token_caps: &HashMap<String, f64>) -> f64 {
+    cap_weighted_early_score_with(wallet_interactions, token_caps, |cap| (MICRO_CAP_REFERENCE / cap).min(1.0))
+}
+
+/// `cap_weighted_early_score` with a caller-chosen weight for each known cap
+///
+/// `weight` receives a positive cap and its result (negatives count as 0)
+/// replaces the default inverse weighting; missing caps still count 1.0.
+pub fn cap_weighted_early_score_with(
+    wallet_interactions: &[TokenInteraction],
+    token_caps: &HashMap<String, f64>,
+    weight: impl Fn(f64) -> f64,
+) -> f64 {
+    wallet_interactions
+        .iter()
+        .filter(|i| i.is_early_entry)
+        .map(|i| match token_caps.get(&i.token_mint) {
+            Some(&cap) if cap > 0.0 => weight(cap).max(0.0),
+            _ => 1.0,
+        })
+        .sum()
+}
+
+/// 0-100 "smart money" score from how the wallet's tokens actually turned out
+///
+/// Over the distinct tokens the wallet bought that have an entry in
+/// `token_outcomes` (realized multiples, negative values count as 0):
+/// - 50 points for hit rate, the share of those tokens that returned more
+///   than 1x
+/// - 50 points for their mean multiple on the profit component's log
+///   scale (1x earns nothing, 10x or better earns everything)
+///
+/// Unlike the proxy-based whale score this reflects real results. A wallet
+/// with no outcome-covered buys scores 0, the same as one that only bought
+/// rugs.
+pub fn smart_money_score(
+    wallet_interactions: &[TokenInteraction],
+    token_outcomes: &HashMap<String, f64>,
+) -> u8 {
+    let multiples: Vec<f64> = wallet_interactions
+        .iter()
+        .filter(|i| i.side == TradeSide::Buy)
+        .map(|i| i.token_mint.as_str())
+        .collect::<HashSet<&str>>()
+        .into_iter()
+        .filter_map(|mint| token_outcomes.get(mint).map(|&multiple| multiple.max(0.0)))
+        .collect();
+    if multiples.is_empty() {
+        return 0;
+    }
+
+    let n = multiples.len() as f64;
+    let hit_rate = multiples.iter().filter(|&&m| m > 1.0).count() as f64 / n;
+    let mean_multiple = multiples.iter().sum::<f64>() / n;
+    let multiple_fraction = if mean_multiple > 0.0 {
+        (mean_multiple.ln() / FULL_PROFIT_MULTIPLE.ln()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    (hit_rate * 50.0 + multiple_fraction * 50.0).round() as u8
 }
 
 /// Pattern detection for insider behavior
@@ -70,36 +594,148 @@ impl PatternDetector {
 
     /// Calculate pattern consistency score
     pub fn consistency_score(&self, interactions: &[TokenInteraction]) -> f64 {
-        if interactions.len() < 3 {
-            return 0.0;
-        }
-
         let sizes: Vec<f64> = interactions.iter().map(|i| i.sol_amount).collect();
-        let mean = sizes.iter().sum::<f64>() / sizes.len() as f64;
-        
-        let variance = sizes.iter()
-            .map(|&x| (x - mean).powi(2))
-            .sum::<f64>() / sizes.len() as f64;
-        
-        let std_dev = variance.sqrt();
-        let coefficient_of_variation = if mean > 0.0 { std_dev / mean } else { 0.0 };
-        
-        // Lower CV = more consistent = higher score
-        (1.0 - (coefficient_of_variation.min(1.0))) * 100.0
+        size_consistency(&sizes)
+    }
+}
+
+/// Consistency (0-100) of a list of buy sizes; fewer than 3 sizes score 0
+fn size_consistency(sizes: &[f64]) -> f64 {
+    if sizes.len() < 3 {
+        return 0.0;
+    }
+    variation_consistency(sizes)
+}
+
+/// `100 * (1 - coefficient of variation)`, floored at 0, for non-empty `sizes`
+fn variation_consistency(sizes: &[f64]) -> f64 {
+    let mean = sizes.iter().sum::<f64>() / sizes.len() as f64;
+    
+    let variance = sizes.iter()
+        .map(|&x| (x - mean).powi(2))
+        .sum::<f64>() / sizes.len() as f64;
+    
+    let std_dev = variance.sqrt();
+    let coefficient_of_variation = if mean > 0.0 { std_dev / mean } else { 0.0 };
+    
+    // Lower CV = more consistent = higher score
+    (1.0 - (coefficient_of_variation.min(1.0))) * 100.0
+}
+
+/// Consistency score over each sliding window of `window` interactions
+///
+/// Interactions are ordered by `block_time`; entry `i` scores interactions
+/// `i..i + window` the same way `PatternDetector::consistency_score` does
+/// (windows shorter than 3 therefore score 0). Returns an empty vector when
+/// `window` is 0 or larger than the number of interactions.
+pub fn rolling_consistency(interactions: &[TokenInteraction], window: usize) -> Vec<f64> {
+    if window == 0 || window > interactions.len() {
+        return Vec::new();
+    }
+
+    let sizes: Vec<f64> = sorted_by_time(interactions).iter().map(|i| i.sol_amount).collect();
+    sizes.windows(window).map(size_consistency).collect()
+}
+
+/// Consistency (0-100) of the wallet's average buy size per token
+///
+/// Averages the buys of each token first and scores those averages the way
+/// `PatternDetector::consistency_score` scores individual buys, so a wallet
+/// that opens similarly sized positions everywhere scores high even if it
+/// builds each one in uneven clips. Sells are ignored; fewer than two
+/// tokens score 0.
+pub fn cross_token_consistency(interactions: &[TokenInteraction]) -> f64 {
+    let mut per_token: HashMap<&str, (f64, usize)> = HashMap::new();
+    for interaction in interactions.iter().filter(|i| i.side == TradeSide::Buy) {
+        let (total, count) = per_token.entry(interaction.token_mint.as_str()).or_insert((0.0, 0));
+        *total += interaction.sol_amount;
+        *count += 1;
+    }
+    if per_token.len() < 2 {
+        return 0.0;
+    }
+
+    let averages: Vec<f64> = per_token.values().map(|&(total, count)| total / count as f64).collect();
+    variation_consistency(&averages)
+}
+
+/// Whale scores on growing prefixes of a wallet's history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StabilityReport {
+    /// Scores on the first 25%, 50%, 75% and 100% of interactions
+    pub scores: [u8; 4],
+    /// Largest absolute change between consecutive cutoffs
+    pub max_swing: u8,
+}
+
+/// How much a wallet's score still moves as more of its history is added
+///
+/// Interactions are ordered by `block_time` and scored on the first 25%,
+/// 50%, 75% and 100% of them (each cutoff rounded up, so at least one
+/// interaction). A small `max_swing` means the score has settled and more
+/// data is unlikely to change it much. Empty input scores 0 throughout.
+pub fn score_stability(interactions: &[TokenInteraction]) -> StabilityReport {
+    let sorted: Vec<TokenInteraction> = sorted_by_time(interactions).into_iter().cloned().collect();
+    let n = sorted.len();
+
+    let mut scores = [0u8; 4];
+    if n > 0 {
+        for (quarter, score) in scores.iter_mut().enumerate() {
+            let cutoff = (n * (quarter + 1)).div_ceil(4);
+            *score = calculate_whale_score(&process_interactions(&sorted[..cutoff]));
+        }
     }
+    let max_swing = scores.windows(2).map(|pair| pair[0].abs_diff(pair[1])).max().unwrap_or(0);
+
+    StabilityReport { scores, max_swing }
 }
 
 /// Cluster wallets by behavior patterns
+///
+/// Build with `WalletClusterer::new` (plus `with_scale_features`); the
+/// struct is `#[non_exhaustive]` so new options don't break callers.
+#[non_exhaustive]
 pub struct WalletClusterer {
     pub similarity_threshold: f64,
+    /// Min-max scale volume, entry size and winrate across the cohort before
+    /// comparing wallets
+    ///
+    /// Unscaled, volume and entry size dominate because of their magnitude;
+    /// scaled, each feature spans `[0, 1]` and contributes equally. Turning
+    /// this on changes cluster results, so it is off unless set.
+    pub scale_features: bool,
 }
 
 impl WalletClusterer {
+    /// A clusterer with feature scaling off
+    pub fn new(similarity_threshold: f64) -> Self {
+        Self {
+            similarity_threshold,
+            scale_features: false,
+        }
+    }
+
+    /// Turn min-max feature scaling on or off (see `scale_features`)
+    pub fn with_scale_features(mut self, scale_features: bool) -> Self {
+        self.scale_features = scale_features;
+        self
+    }
+
+    /// Greedily group wallets whose similarity to a seed clears the threshold
+    ///
+    /// Output is canonical regardless of `HashMap` iteration order: seeds are
+    /// visited in address order, each cluster's members are sorted, and the
+    /// clusters are sorted by their first (smallest) member, which is also
+    /// the cluster's seed.
     pub fn cluster_wallets(&self, stats_map: &HashMap<String, WalletStats>) -> Vec<Vec<String>> {
         let mut clusters: Vec<Vec<String>> = Vec::new();
         let mut assigned: std::collections::HashSet<String> = std::collections::HashSet::new();
 
-        for (addr1, stats1) in stats_map {
+        let ranges = self.feature_ranges(stats_map);
+        let mut wallets: Vec<(&String, &WalletStats)> = stats_map.iter().collect();
+        wallets.sort_by(|a, b| a.0.cmp(b.0));
+
+        for &(addr1, stats1) in &wallets {
             if assigned.contains(addr1) {
                 continue;
             }
@@ -107,12 +743,12 @@ impl WalletClusterer {
             let mut cluster = vec![addr1.clone()];
             assigned.insert(addr1.clone());
 
-            for (addr2, stats2) in stats_map {
+            for &(addr2, stats2) in &wallets {
                 if assigned.contains(addr2) || addr1 == addr2 {
                     continue;
                 }
 
-                if self.similarity(stats1, stats2) >= self.similarity_threshold {
+                if self.compare(ranges.as_ref(), stats1, stats2) >= self.similarity_threshold {
                     cluster.push(addr2.clone());
                     assigned.insert(addr2.clone());
                 }
@@ -123,15 +759,101 @@ impl WalletClusterer {
             }
         }
 
+        canonicalize_clusters(&mut clusters);
         clusters
     }
 
+    /// Move a single changed wallet to the cluster that now fits it best
+    ///
+    /// The wallet is removed from its current cluster (dropping the cluster if
+    /// it becomes empty) and joins the cluster whose first member (its seed)
+    /// is most similar, as long as that similarity clears
+    /// `similarity_threshold`; otherwise it becomes a new singleton cluster.
+    /// A wallet missing from `stats_map` is only removed. The clusters are left
+    /// in the same canonical order `cluster_wallets` produces.
+    ///
+    /// This is an approximation of full reclustering: other members are not
+    /// revisited, so if the changed wallet was a seed its former cluster keeps
+    /// members that `cluster_wallets` might now split differently.
+    pub fn reassign(
+        &self,
+        clusters: &mut Vec<Vec<String>>,
+        stats_map: &HashMap<String, WalletStats>,
+        changed: &str,
+    ) {
+        for cluster in clusters.iter_mut() {
+            cluster.retain(|address| address != changed);
+        }
+        clusters.retain(|cluster| !cluster.is_empty());
+
+        let changed_stats = match stats_map.get(changed) {
+            Some(stats) => stats,
+            None => return,
+        };
+
+        let ranges = self.feature_ranges(stats_map);
+        let best = clusters
+            .iter()
+            .enumerate()
+            .filter_map(|(i, cluster)| {
+                let seed = stats_map.get(&cluster[0])?;
+                Some((i, self.compare(ranges.as_ref(), seed, changed_stats)))
+            })
+            .filter(|(_, sim)| *sim >= self.similarity_threshold)
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        match best {
+            Some((i, _)) => clusters[i].push(changed.to_string()),
+            None => clusters.push(vec![changed.to_string()]),
+        }
+        canonicalize_clusters(clusters);
+    }
+
+    /// Pairwise similarities between every wallet in `stats_map`
+    ///
+    /// Returns the addresses in sorted order and a symmetric matrix where
+    /// entry `[i][j]` is the similarity `cluster_wallets` would use for
+    /// addresses `i` and `j` (scaled when `scale_features` is set).
+    pub fn similarity_matrix(&self, stats_map: &HashMap<String, WalletStats>) -> (Vec<String>, Vec<Vec<f64>>) {
+        let mut addresses: Vec<String> = stats_map.keys().cloned().collect();
+        addresses.sort();
+
+        let ranges = self.feature_ranges(stats_map);
+        let n = addresses.len();
+        let mut matrix = vec![vec![0.0; n]; n];
+        for i in 0..n {
+            for j in i..n {
+                let sim = self.compare(ranges.as_ref(), &stats_map[&addresses[i]], &stats_map[&addresses[j]]);
+                matrix[i][j] = sim;
+                matrix[j][i] = sim;
+            }
+        }
+
+        (addresses, matrix)
+    }
+
+    /// Cohort feature ranges when `scale_features` is set
+    fn feature_ranges(&self, stats_map: &HashMap<String, WalletStats>) -> Option<FeatureRanges<3>> {
+        self.scale_features
+            .then(|| FeatureRanges::from_vectors(stats_map.values().map(similarity_features)))
+    }
+
+    /// Similarity on scaled features when `ranges` is given, raw features otherwise
+    fn compare(&self, ranges: Option<&FeatureRanges<3>>, stats1: &WalletStats, stats2: &WalletStats) -> f64 {
+        match ranges {
+            Some(ranges) => {
+                let a = ranges.scale(similarity_features(stats1));
+                let b = ranges.scale(similarity_features(stats2));
+                a.iter().zip(&b).map(|(x, y)| 1.0 - (x - y).abs()).sum::<f64>() / a.len() as f64
+            }
+            None => self.similarity(stats1, stats2),
+        }
+    }
+
     fn similarity(&self, stats1: &WalletStats, stats2: &WalletStats) -> f64 {
-        let volume_sim = 1.0 - ((stats1.total_volume_sol - stats2.total_volume_sol).abs() 
-            / (stats1.total_volume_sol + stats2.total_volume_sol + 1.0));
+        let volume_sim = relative_similarity(stats1.total_volume_sol, stats2.total_volume_sol);
         
-        let size_sim = 1.0 - ((stats1.average_entry_size - stats2.average_entry_size).abs() 
-            / (stats1.average_entry_size + stats2.average_entry_size + 1.0));
+        let size_sim = relative_similarity(stats1.average_entry_size, stats2.average_entry_size);
         
         let ratio_sim = 1.0 - ((stats1.winrate_proxy - stats2.winrate_proxy).abs());
         
@@ -139,52 +861,1525 @@ impl WalletClusterer {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Clusters that grow one wallet at a time, for live dashboards
+///
+/// Each new wallet joins the existing cluster whose seed (its first member)
+/// is most similar, provided the similarity clears `similarity_threshold`,
+/// and otherwise starts a cluster of its own. Only raw features are
+/// compared, since scaling needs the whole cohort up front.
+///
+/// This approximates `WalletClusterer::cluster_wallets`: seeds are the
+/// earliest arrivals rather than the smallest addresses, and existing
+/// members are never revisited, so the result can depend on arrival order.
+/// Well-separated groups come out the same either way.
+pub struct IncrementalClusterer {
+    clusterer: WalletClusterer,
+    /// Seed stats and member addresses, in creation order
+    clusters: Vec<(WalletStats, Vec<String>)>,
+}
 
-    #[test]
-    fn test_dynamic_scorer() {
-        let scorer = DynamicScorer::default();
-        let stats = WalletStats {
-            address: "test".to_string(),
-            total_volume_sol: 50.0,
-            interaction_count: 5,
-            average_entry_size: 10.0,
-            early_entry_count: 3,
-            winrate_proxy: 0.7,
-        };
+impl IncrementalClusterer {
+    pub fn new(similarity_threshold: f64) -> Self {
+        Self {
+            clusterer: WalletClusterer::new(similarity_threshold),
+            clusters: Vec::new(),
+        }
+    }
 
-        let score = scorer.calculate_score(&stats);
-        assert!(score <= 100);
+    /// Place a wallet in the best-matching cluster, or a new one
+    ///
+    /// Adding an address that is already clustered moves it as
+    /// `WalletClusterer::reassign` would, dropping a cluster it leaves empty.
+    pub fn add_wallet(&mut self, address: String, stats: &WalletStats) {
+        for (_, members) in self.clusters.iter_mut() {
+            members.retain(|member| *member != address);
+        }
+        self.clusters.retain(|(_, members)| !members.is_empty());
+
+        let best = self
+            .clusters
+            .iter()
+            .enumerate()
+            .map(|(i, (seed, _))| (i, self.clusterer.similarity(seed, stats)))
+            .filter(|(_, sim)| *sim >= self.clusterer.similarity_threshold)
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        match best {
+            Some((i, _)) => self.clusters[i].1.push(address),
+            None => self.clusters.push((stats.clone(), vec![address])),
+        }
     }
 
-    #[test]
-    fn test_pattern_detector() {
-        let detector = PatternDetector {
-            min_early_entries: 2,
-            min_avg_buy_size: 5.0,
-            consistency_threshold: 0.8,
-        };
+    /// Current clusters in the canonical order `cluster_wallets` uses
+    pub fn clusters(&self) -> Vec<Vec<String>> {
+        let mut clusters: Vec<Vec<String>> = self.clusters.iter().map(|(_, members)| members.clone()).collect();
+        canonicalize_clusters(&mut clusters);
+        clusters
+    }
+}
 
-        let interactions = vec![
-            TokenInteraction {
-                wallet_address: "addr1".to_string(),
-                token_mint: "token1".to_string(),
-                block_time: 1000,
-                sol_amount: 10.0,
-                is_early_entry: true,
-            },
-            TokenInteraction {
-                wallet_address: "addr1".to_string(),
-                token_mint: "token2".to_string(),
-                block_time: 2000,
-                sol_amount: 12.0,
-                is_early_entry: true,
-            },
-        ];
+/// Per-feature `(min, max)` of feature vectors across a cohort
+struct FeatureRanges<const N: usize> {
+    ranges: [(f64, f64); N],
+}
 
-        assert!(detector.detect_pattern(&interactions));
+impl<const N: usize> FeatureRanges<N> {
+    fn from_vectors(vectors: impl Iterator<Item = [f64; N]>) -> Self {
+        let mut ranges = [(f64::INFINITY, f64::NEG_INFINITY); N];
+        for vector in vectors {
+            for (range, value) in ranges.iter_mut().zip(vector) {
+                *range = (range.0.min(value), range.1.max(value));
+            }
+        }
+        Self { ranges }
+    }
+
+    /// Features mapped to `[0, 1]`; a feature with no spread maps to 0
+    fn scale(&self, mut vector: [f64; N]) -> [f64; N] {
+        for (value, &(min, max)) in vector.iter_mut().zip(&self.ranges) {
+            *value = if max > min { ((*value - min) / (max - min)).clamp(0.0, 1.0) } else { 0.0 };
+        }
+        vector
+    }
+}
+
+/// `[total_volume_sol, average_entry_size, winrate_proxy]`, as compared by `WalletClusterer`
+fn similarity_features(stats: &WalletStats) -> [f64; 3] {
+    [stats.total_volume_sol, stats.average_entry_size, stats.winrate_proxy]
+}
+
+/// Similarity (0-1) of two magnitudes: `1 - |a - b| / (|a| + |b|)`
+///
+/// Two zero values are identical (1.0); a zero paired with a non-zero value
+/// has nothing in common (0.0). This avoids the small-value bias of padding
+/// the denominator.
+fn relative_similarity(a: f64, b: f64) -> f64 {
+    match (a == 0.0, b == 0.0) {
+        (true, true) => 1.0,
+        (true, false) | (false, true) => 0.0,
+        (false, false) => 1.0 - (a - b).abs() / (a.abs() + b.abs()),
     }
 }
 
+/// `[total_volume_sol, average_entry_size, winrate_proxy, early_ratio]` for k-means
+fn kmeans_features(stats: &WalletStats) -> [f64; 4] {
+    let early_ratio = if stats.interaction_count > 0 {
+        clamped_early_count(stats).0 as f64 / stats.interaction_count as f64
+    } else {
+        0.0
+    };
+    [stats.total_volume_sol, stats.average_entry_size, stats.winrate_proxy, early_ratio]
+}
+
+fn squared_distance(a: &[f64; 4], b: &[f64; 4]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum()
+}
+
+/// Partition wallets into at most `k` cohorts with seeded k-means
+///
+/// Wallets are compared on `[volume, avg_size, winrate, early_ratio]`, each
+/// min-max scaled across the cohort so volume doesn't dominate. Centroids are
+/// seeded with k-means++ from `seed`, so the same input and seed always give
+/// the same clusters; Lloyd iterations then run until assignments stop
+/// changing or `max_iters` is reached. Clusters that end up empty are
+/// dropped and the output is in the canonical `cluster_wallets` order. With
+/// `k >= stats_map.len()` every wallet is its own cluster; `k == 0` or empty
+/// input returns no clusters.
+pub fn kmeans_cluster_wallets(
+    stats_map: &HashMap<String, WalletStats>,
+    k: usize,
+    seed: u64,
+    max_iters: usize,
+) -> Vec<Vec<String>> {
+    let mut addresses: Vec<&String> = stats_map.keys().collect();
+    addresses.sort();
+    if k == 0 || addresses.is_empty() {
+        return Vec::new();
+    }
+    if k >= addresses.len() {
+        return addresses.into_iter().map(|address| vec![address.clone()]).collect();
+    }
+
+    let raw: Vec<[f64; 4]> = addresses.iter().map(|a| kmeans_features(&stats_map[*a])).collect();
+    let ranges = FeatureRanges::from_vectors(raw.iter().copied());
+    let points: Vec<[f64; 4]> = raw.into_iter().map(|v| ranges.scale(v)).collect();
+
+    // k-means++: each new centroid is drawn with probability proportional to
+    // its squared distance from the nearest centroid chosen so far
+    let mut rng = SeededRng::new(seed);
+    let mut centroids = vec![points[rng.next_below(points.len() as u64) as usize]];
+    while centroids.len() < k {
+        let distances: Vec<f64> = points
+            .iter()
+            .map(|p| centroids.iter().map(|c| squared_distance(p, c)).fold(f64::INFINITY, f64::min))
+            .collect();
+        let total: f64 = distances.iter().sum();
+        if total <= 0.0 {
+            break;
+        }
+
+        let mut target = rng.next_f64() * total;
+        let mut chosen = distances.len() - 1;
+        for (i, d) in distances.iter().enumerate() {
+            if target < *d {
+                chosen = i;
+                break;
+            }
+            target -= d;
+        }
+        centroids.push(points[chosen]);
+    }
+
+    let nearest = |point: &[f64; 4], centroids: &[[f64; 4]]| -> usize {
+        let mut best = 0;
+        for (i, c) in centroids.iter().enumerate() {
+            if squared_distance(point, c) < squared_distance(point, &centroids[best]) {
+                best = i;
+            }
+        }
+        best
+    };
+
+    let mut assignments: Vec<usize> = points.iter().map(|p| nearest(p, &centroids)).collect();
+    for _ in 0..max_iters {
+        for (c, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<&[f64; 4]> = points
+                .iter()
+                .zip(&assignments)
+                .filter(|(_, &a)| a == c)
+                .map(|(p, _)| p)
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            for (dim, value) in centroid.iter_mut().enumerate() {
+                *value = members.iter().map(|p| p[dim]).sum::<f64>() / members.len() as f64;
+            }
+        }
+
+        let updated: Vec<usize> = points.iter().map(|p| nearest(p, &centroids)).collect();
+        if updated == assignments {
+            break;
+        }
+        assignments = updated;
+    }
+
+    let mut clusters: Vec<Vec<String>> = vec![Vec::new(); centroids.len()];
+    for (address, &cluster) in addresses.iter().zip(&assignments) {
+        clusters[cluster].push((*address).clone());
+    }
+    clusters.retain(|cluster| !cluster.is_empty());
+    canonicalize_clusters(&mut clusters);
+    clusters
+}
+
+/// Groups of wallets that `cluster_wallets` puts together at most thresholds
+///
+/// Clusters the wallets once per threshold (unscaled features) and links two
+/// wallets when they share a cluster in strictly more than half of the runs.
+/// The groups are the connected components of those links, so a pair that
+/// only clusters at permissive thresholds is split up. Every wallet appears
+/// in exactly one group and the output is in canonical order. With no
+/// thresholds every wallet is its own group.
+pub fn consensus_clusters(stats_map: &HashMap<String, WalletStats>, thresholds: &[f64]) -> Vec<Vec<String>> {
+    let mut addresses: Vec<&String> = stats_map.keys().collect();
+    addresses.sort();
+    let index: HashMap<&String, usize> = addresses.iter().enumerate().map(|(i, a)| (*a, i)).collect();
+
+    let mut together: HashMap<(usize, usize), usize> = HashMap::new();
+    for &similarity_threshold in thresholds {
+        let clusterer = WalletClusterer::new(similarity_threshold);
+        for cluster in clusterer.cluster_wallets(stats_map) {
+            let members: Vec<usize> = cluster.iter().map(|address| index[address]).collect();
+            for (n, &a) in members.iter().enumerate() {
+                for &b in &members[n + 1..] {
+                    *together.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    let mut parents: Vec<usize> = (0..addresses.len()).collect();
+    for (&(a, b), &count) in &together {
+        if count * 2 > thresholds.len() {
+            let (ra, rb) = (find_root(&mut parents, a), find_root(&mut parents, b));
+            parents[rb] = ra;
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<String>> = HashMap::new();
+    for (i, address) in addresses.iter().enumerate() {
+        groups.entry(find_root(&mut parents, i)).or_default().push((*address).clone());
+    }
+    let mut groups: Vec<Vec<String>> = groups.into_values().collect();
+    canonicalize_clusters(&mut groups);
+    groups
+}
+
+/// Highest similarity threshold at which `address` still has a peer
+///
+/// The wallet's best (unscaled) similarity to any other wallet: at this
+/// threshold or below, some other wallet clears it, while any higher
+/// threshold leaves the wallet alone. Higher values mean a near-twin exists;
+/// low values mark a distinctive wallet. Returns 0.0 if the address is
+/// missing or has no other wallets to compare against.
+pub fn isolation_threshold(stats_map: &HashMap<String, WalletStats>, address: &str) -> f64 {
+    let stats = match stats_map.get(address) {
+        Some(stats) => stats,
+        None => return 0.0,
+    };
+
+    let clusterer = WalletClusterer::new(0.0);
+    stats_map
+        .iter()
+        .filter(|(other, _)| other.as_str() != address)
+        .map(|(_, other)| clusterer.similarity(stats, other))
+        .fold(0.0, f64::max)
+}
+
+/// Sort each cluster's members and then the clusters by their first member
+pub(crate) fn canonicalize_clusters(clusters: &mut [Vec<String>]) {
+    for cluster in clusters.iter_mut() {
+        cluster.sort();
+    }
+    clusters.sort_by(|a, b| a.first().cmp(&b.first()));
+}
+
+/// Presentable aggregate view of one cluster
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClusterSummary {
+    /// First 16 hex chars of the SHA-256 of the sorted, comma-joined members
+    pub id: String,
+    pub member_count: usize,
+    pub total_volume_sol: f64,
+    pub mean_whale_score: f64,
+}
+
+/// Stable 16-hex-digit ID of a cluster's member set, independent of order
+fn cluster_id(cluster: &[String]) -> String {
+    let mut members: Vec<&str> = cluster.iter().map(String::as_str).collect();
+    members.sort_unstable();
+    hash_wallet_address(&members.join(","))[..16].to_string()
+}
+
+/// Enrich cluster membership with aggregate stats
+///
+/// The ID depends only on the set of members, not their order. Members
+/// missing from `stats_map` are counted in `member_count` but contribute
+/// neither volume nor a score; the mean is over members that have stats
+/// (0.0 if none do). Summaries are returned in the order of `clusters`.
+pub fn summarize_clusters(
+    clusters: &[Vec<String>],
+    stats_map: &HashMap<String, WalletStats>,
+) -> Vec<ClusterSummary> {
+    clusters
+        .iter()
+        .map(|cluster| {
+            let id = cluster_id(cluster);
+            let stats: Vec<&WalletStats> = cluster.iter().filter_map(|address| stats_map.get(address)).collect();
+            let total_volume_sol = stats.iter().map(|s| s.total_volume_sol).sum();
+            let mean_whale_score = if stats.is_empty() {
+                0.0
+            } else {
+                stats.iter().map(|s| calculate_whale_score(s) as f64).sum::<f64>() / stats.len() as f64
+            };
+
+            ClusterSummary {
+                id,
+                member_count: cluster.len(),
+                total_volume_sol,
+                mean_whale_score,
+            }
+        })
+        .collect()
+}
+
+/// Representative profile of a cluster: its members' volume-weighted mean
+///
+/// Every numeric field is the mean over members in `stats_map`, weighted by
+/// `total_volume_sol` (equally when the members have no volume at all);
+/// counts are rounded to the nearest integer, and `avg_realized_multiple`
+/// averages the members that know it. The address is the cluster's
+/// `ClusterSummary::id`, so centroids can be matched to summaries. A cluster
+/// with no members in `stats_map` yields empty stats under that ID.
+pub fn cluster_centroid(cluster: &[String], stats_map: &HashMap<String, WalletStats>) -> WalletStats {
+    let id = cluster_id(cluster);
+    let members: Vec<&WalletStats> = cluster.iter().filter_map(|address| stats_map.get(address)).collect();
+    if members.is_empty() {
+        return WalletStats::empty(&id);
+    }
+
+    let total_volume: f64 = members.iter().map(|s| s.total_volume_sol.max(0.0)).sum();
+    let weight = |s: &WalletStats| {
+        if total_volume > 0.0 { s.total_volume_sol.max(0.0) / total_volume } else { 1.0 / members.len() as f64 }
+    };
+    let mean = |field: fn(&WalletStats) -> f64| members.iter().map(|s| weight(s) * field(s)).sum::<f64>();
+
+    let (multiple_sum, multiple_weight) = members
+        .iter()
+        .filter_map(|s| Some((weight(s), s.avg_realized_multiple?)))
+        .fold((0.0, 0.0), |(sum, total), (w, multiple)| (sum + w * multiple, total + w));
+
+    WalletStats {
+        address: id,
+        total_volume_sol: mean(|s| s.total_volume_sol),
+        interaction_count: mean(|s| s.interaction_count as f64).round() as u64,
+        average_entry_size: mean(|s| s.average_entry_size),
+        early_entry_count: mean(|s| s.early_entry_count as f64).round() as u64,
+        winrate_proxy: mean(|s| s.winrate_proxy),
+        avg_realized_multiple: (multiple_weight > 0.0).then(|| multiple_sum / multiple_weight),
+        net_volume_sol: mean(|s| s.net_volume_sol),
+    }
+}
+
+/// Whale score nudged by the size of the wallet's cluster
+///
+/// Adds `boost_per_peer` points for every other member of the cluster
+/// (`cluster_size - 1`; a solo wallet has no peers and keeps its base score).
+/// A positive `boost_per_peer` boosts coordinated wallets but never past
+/// `cap` (a base score already above `cap` is left as-is); a negative one
+/// discounts them, bottoming out at 0.
+pub fn cluster_adjusted_score(stats: &WalletStats, cluster_size: usize, boost_per_peer: f64, cap: u8) -> u8 {
+    let base = calculate_whale_score(stats);
+    let peers = cluster_size.saturating_sub(1) as f64;
+    let adjustment = peers * boost_per_peer;
+    if adjustment.is_nan() || adjustment == 0.0 {
+        return base;
+    }
+
+    let adjusted = base as f64 + adjustment;
+    if adjustment > 0.0 {
+        adjusted.min(cap.max(base) as f64).min(100.0) as u8
+    } else {
+        adjusted.max(0.0) as u8
+    }
+}
+
+/// Whale score scaled by the wallet's share of cohort volume
+///
+/// Returns `calculate_whale_score(stats) * total_volume_sol / cohort_total_volume`,
+/// so a wallet only ranks high if it both trades like a whale and moves a
+/// real slice of the cohort's capital. The share is clamped to `[0, 1]`; a
+/// zero, negative or NaN `cohort_total_volume` gives 0.0.
+pub fn exposure_weighted_score(stats: &WalletStats, cohort_total_volume: f64) -> f64 {
+    if cohort_total_volume.is_nan() || cohort_total_volume <= 0.0 {
+        return 0.0;
+    }
+
+    let share = (stats.total_volume_sol / cohort_total_volume).clamp(0.0, 1.0);
+    calculate_whale_score(stats) as f64 * share
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TradeSide;
+
+    #[test]
+    fn test_dynamic_scorer() {
+        let scorer = DynamicScorer::default();
+        let stats = WalletStats {
+            address: "test".to_string(),
+            total_volume_sol: 50.0,
+            interaction_count: 5,
+            average_entry_size: 10.0,
+            early_entry_count: 3,
+            winrate_proxy: 0.7,
+            avg_realized_multiple: None,
+            net_volume_sol: 50.0,
+        };
+
+        let score = scorer.calculate_score(&stats);
+        assert!(score <= 100);
+    }
+
+    #[test]
+    fn test_weight_sensitivity() {
+        let scorer = DynamicScorer::default();
+        // Saturated on size, half way on repetition, never early
+        let stats = WalletStats {
+            address: "test".to_string(),
+            total_volume_sol: 2500.0,
+            interaction_count: 25,
+            average_entry_size: 100.0,
+            early_entry_count: 0,
+            winrate_proxy: 0.0,
+            avg_realized_multiple: None,
+            net_volume_sol: 2500.0,
+        };
+
+        let sensitivity = scorer.weight_sensitivity(&stats, 5.0);
+        let names: Vec<&str> = sensitivity.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["early_entry", "buy_size", "repetition", "profit"]);
+        assert!(sensitivity[0].1.abs() < 1e-9);
+        assert!((sensitivity[1].1 - 5.0).abs() < 1e-9);
+        assert!((sensitivity[2].1 - 2.5).abs() < 1e-9);
+        assert!(sensitivity[3].1.abs() < 1e-9);
+
+        // Once the total is capped at 100, no weight moves the score
+        let maxed = WalletStats { early_entry_count: 50, winrate_proxy: 1.0, interaction_count: 50, ..stats };
+        assert!(scorer.weight_sensitivity(&maxed, 5.0).iter().all(|(_, change)| change.abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_pattern_detector() {
+        let detector = PatternDetector {
+            min_early_entries: 2,
+            min_avg_buy_size: 5.0,
+            consistency_threshold: 0.8,
+        };
+
+        let interactions = vec![
+            TokenInteraction {
+                wallet_address: "addr1".to_string(),
+                token_mint: "token1".to_string(),
+                block_time: 1000,
+                sol_amount: 10.0,
+                is_early_entry: true,
+                side: TradeSide::Buy,
+                source: None,
+            },
+            TokenInteraction {
+                wallet_address: "addr1".to_string(),
+                token_mint: "token2".to_string(),
+                block_time: 2000,
+                sol_amount: 12.0,
+                is_early_entry: true,
+                side: TradeSide::Buy,
+                source: None,
+            },
+        ];
+
+        assert!(detector.detect_pattern(&interactions));
+    }
+
+    #[test]
+    fn test_balanced_score_penalizes_one_dimensional_wallet() {
+        let config = ScoringConfig::default();
+        let balanced = WalletStats {
+            address: "balanced".to_string(),
+            total_volume_sol: 250.0,
+            interaction_count: 25,
+            average_entry_size: 10.0,
+            early_entry_count: 10,
+            winrate_proxy: 0.5,
+            avg_realized_multiple: None,
+            net_volume_sol: 250.0,
+        };
+        // Maxes out early entry points with almost no volume
+        let one_dimensional = WalletStats {
+            address: "early_only".to_string(),
+            total_volume_sol: 0.2,
+            interaction_count: 10,
+            average_entry_size: 0.02,
+            early_entry_count: 10,
+            winrate_proxy: 0.0,
+            avg_realized_multiple: None,
+            net_volume_sol: 0.2,
+        };
+
+        assert!(crate::calculate_whale_score(&one_dimensional) >= 40);
+
+        let balanced_score = calculate_whale_score_balanced(&balanced, &config);
+        let one_dimensional_score = calculate_whale_score_balanced(&one_dimensional, &config);
+        assert!(balanced_score > one_dimensional_score);
+        assert!(one_dimensional_score < crate::calculate_whale_score(&one_dimensional));
+    }
+
+    #[test]
+    fn test_balanced_score_without_penalty_matches_base() {
+        let config = ScoringConfig {
+            component_floor: 0.0,
+            balance_penalty: 0.0,
+            ..ScoringConfig::default()
+        };
+        let stats = WalletStats {
+            address: "test".to_string(),
+            total_volume_sol: 100.0,
+            interaction_count: 10,
+            average_entry_size: 10.0,
+            early_entry_count: 5,
+            winrate_proxy: 0.8,
+            avg_realized_multiple: None,
+            net_volume_sol: 100.0,
+        };
+
+        assert_eq!(
+            calculate_whale_score_balanced(&stats, &config),
+            crate::calculate_whale_score(&stats)
+        );
+    }
+
+    #[test]
+    fn test_linear_curve_matches_base_score() {
+        let config = ScoringConfig::default();
+        for (volume, count, avg, early, winrate) in [
+            (0.5, 1, 0.5, 0, 0.0),
+            (100.0, 10, 10.0, 5, 0.8),
+            (480.0, 12, 40.0, 3, 0.3),
+            (5000.0, 80, 62.5, 70, 1.0),
+        ] {
+            let stats = WalletStats {
+                address: "test".to_string(),
+                total_volume_sol: volume,
+                interaction_count: count,
+                average_entry_size: avg,
+                early_entry_count: early,
+                winrate_proxy: winrate,
+                avg_realized_multiple: None,
+                net_volume_sol: volume,
+            };
+            assert_eq!(
+                calculate_whale_score_with_config(&stats, &config),
+                crate::calculate_whale_score(&stats)
+            );
+        }
+    }
+
+    #[test]
+    fn test_ratio_favoring_early_split() {
+        let wallet = |interaction_count: u64, early_entry_count: u64| WalletStats {
+            address: "test".to_string(),
+            total_volume_sol: 100.0,
+            interaction_count,
+            average_entry_size: 100.0 / interaction_count as f64,
+            early_entry_count,
+            winrate_proxy: 0.0,
+            avg_realized_multiple: None,
+            net_volume_sol: 100.0,
+        };
+        // Always early but rarely trades vs. early on a fifth of many trades
+        let precise = wallet(4, 4);
+        let spammer = wallet(50, 10);
+        let ratio_favoring = ScoringConfig { early_ratio_points: 30.0, ..ScoringConfig::default() };
+        let default = ScoringConfig::default();
+
+        assert_eq!(calculate_whale_score_with_config(&precise, &default), calculate_whale_score(&precise));
+        assert!(
+            calculate_whale_score_with_config(&precise, &ratio_favoring)
+                > calculate_whale_score_with_config(&precise, &default)
+        );
+        assert!(
+            calculate_whale_score_with_config(&spammer, &ratio_favoring)
+                < calculate_whale_score_with_config(&spammer, &default)
+        );
+    }
+
+    #[test]
+    fn test_early_volume_score_rewards_capital_in_early_entries() {
+        let interaction = |amount: f64, early: bool| TokenInteraction {
+            wallet_address: "addr1".to_string(),
+            token_mint: "token1".to_string(),
+            block_time: 1000,
+            sol_amount: amount,
+            is_early_entry: early,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        let interactions = vec![interaction(80.0, true), interaction(5.0, false), interaction(10.0, false), interaction(5.0, false)];
+        let config = ScoringConfig::default();
+
+        let base = calculate_whale_score(&process_interactions(&interactions));
+        assert_eq!(calculate_whale_score_early_volume(&interactions, 0.0, &config), base);
+        // Count ratio 0.25 vs volume ratio 0.8 on 20 points: +11 points
+        assert_eq!(calculate_whale_score_early_volume(&interactions, 1.0, &config), base + 11);
+        assert_eq!(calculate_whale_score_early_volume(&[], 1.0, &config), 0);
+    }
+
+    #[test]
+    fn test_repetition_score_decayed() {
+        let at = |block_time: u64| TokenInteraction {
+            wallet_address: "addr1".to_string(),
+            token_mint: "token1".to_string(),
+            block_time,
+            sol_amount: 1.0,
+            is_early_entry: false,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        let day = 86_400;
+        let now = 400 * day;
+        let recent: Vec<TokenInteraction> = (0..50).map(|i| at(now - i * 60)).collect();
+        let historic: Vec<TokenInteraction> = (0..50).map(|i| at(10 * day + i * 60)).collect();
+
+        // Same raw count, which the undecayed component scores at the full 20 points
+        assert_eq!(whale_score_components_with(&process_interactions(&historic), &ScoringConfig::default())[2], 20.0);
+        assert!(repetition_score_decayed(&recent, now, 30 * day) > 19.9);
+        assert!(repetition_score_decayed(&historic, now, 30 * day) < 0.01);
+        assert_eq!(repetition_score_decayed(&[], now, 30 * day), 0.0);
+    }
+
+    #[test]
+    fn test_penalized_score_discounts_bots() {
+        let buy = |block_time: u64, sol_amount: f64| TokenInteraction {
+            wallet_address: "addr1".to_string(),
+            token_mint: "token1".to_string(),
+            block_time,
+            sol_amount,
+            is_early_entry: true,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        // Round amounts at irregular times vs formula amounts every 60 seconds
+        let organic: Vec<TokenInteraction> =
+            [(1000, 10.0), (1004, 5.0), (1900, 20.0), (9000, 10.0), (40000, 5.0)].iter().map(|&(t, a)| buy(t, a)).collect();
+        let bot: Vec<TokenInteraction> = (0..5).map(|i| buy(1000 + i * 60, 10.137 + i as f64 * 0.25)).collect();
+        let stats = process_interactions(&organic);
+        let config = ScoringConfig::default();
+        let base = calculate_whale_score_with_config(&stats, &config);
+        assert!(base > 0);
+
+        assert_eq!(calculate_whale_score_penalized(&stats, &organic, &config), base);
+        assert_eq!(calculate_whale_score_penalized(&stats, &bot, &config), 0);
+
+        // Metronomic but round-sized: half the score
+        let scheduled: Vec<TokenInteraction> = (0..5).map(|i| buy(1000 + i * 60, 10.0)).collect();
+        assert_eq!(calculate_whale_score_penalized(&stats, &scheduled, &config), (base as f64 / 2.0).round() as u8);
+        assert_eq!(calculate_whale_score_penalized(&stats, &[], &config), base);
+    }
+
+    #[test]
+    fn test_decayed_score_favors_recent_activity() {
+        let day = 86_400;
+        let now = 100 * day;
+        let activity = |start: u64| -> Vec<TokenInteraction> {
+            (0..30)
+                .map(|i| TokenInteraction {
+                    wallet_address: "addr1".to_string(),
+                    token_mint: format!("token{}", i),
+                    block_time: start + i * 60,
+                    sol_amount: 20.0,
+                    is_early_entry: i % 2 == 0,
+                    side: TradeSide::Buy,
+                    source: None,
+                })
+                .collect()
+        };
+        let config = ScoringConfig::default();
+
+        let recent = calculate_whale_score_decayed(&activity(now - day), now, 7 * day, &config);
+        let old = calculate_whale_score_decayed(&activity(now - 60 * day), now, 7 * day, &config);
+
+        assert!(recent > old);
+        assert!(recent <= calculate_whale_score(&process_interactions(&activity(now - day))));
+        // With an effectively infinite half-life nothing decays
+        assert_eq!(
+            calculate_whale_score_decayed(&activity(now), now + 29 * 60, u64::MAX, &config),
+            calculate_whale_score(&process_interactions(&activity(now)))
+        );
+    }
+
+    #[test]
+    fn test_decayed_winrate() {
+        let day = 86_400;
+
+        assert_eq!(decayed_winrate(0.9, 0, day), 0.9);
+        assert!((decayed_winrate(0.9, day, day) - 0.7).abs() < 1e-9);
+        assert!((decayed_winrate(0.9, 60 * day, day) - NEUTRAL_WINRATE).abs() < 1e-9);
+        assert!((decayed_winrate(0.1, 60 * day, day) - NEUTRAL_WINRATE).abs() < 1e-9);
+        assert!((decayed_winrate_toward(0.9, 60 * day, day, 0.3) - 0.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_outcome_weighted_early_score() {
+        let entry = |mint: &str, early: bool| TokenInteraction {
+            wallet_address: "addr1".to_string(),
+            token_mint: mint.to_string(),
+            block_time: 1000,
+            sol_amount: 5.0,
+            is_early_entry: early,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        let outcomes: HashMap<String, f64> =
+            [("moon".to_string(), 100.0), ("rug".to_string(), 0.0)].into_iter().collect();
+
+        let winner = vec![entry("moon", true), entry("rug", false)];
+        let loser = vec![entry("rug", true), entry("moon", false)];
+        let unknown = vec![entry("new1", true), entry("new2", true)];
+
+        assert_eq!(outcome_weighted_early_score(&winner, &outcomes), 100.0);
+        assert_eq!(outcome_weighted_early_score(&loser, &outcomes), 0.0);
+        assert_eq!(outcome_weighted_early_score(&unknown, &outcomes), 2.0);
+    }
+
+    #[test]
+    fn test_cap_weighted_early_score() {
+        let entry = |mint: &str| TokenInteraction {
+            wallet_address: "addr1".to_string(),
+            token_mint: mint.to_string(),
+            block_time: 1000,
+            sol_amount: 5.0,
+            is_early_entry: true,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        let caps: HashMap<String, f64> = [("micro1", 200.0), ("micro2", 900.0), ("large1", 50_000.0), ("large2", 100_000.0)]
+            .iter()
+            .map(|&(mint, cap)| (mint.to_string(), cap))
+            .collect();
+
+        let micro_sniper = vec![entry("micro1"), entry("micro2")];
+        let large_cap_buyer = vec![entry("large1"), entry("large2")];
+        assert_eq!(cap_weighted_early_score(&micro_sniper, &caps), 2.0);
+        assert!((cap_weighted_early_score(&large_cap_buyer, &caps) - 0.03).abs() < 1e-9);
+
+        // Missing caps fall back to 1.0; late entries never count
+        let mixed = vec![entry("unknown"), TokenInteraction { is_early_entry: false, ..entry("micro1") }];
+        assert_eq!(cap_weighted_early_score(&mixed, &caps), 1.0);
+
+        let log_weight = |cap: f64| 1.0 / cap.log10();
+        assert!((cap_weighted_early_score_with(&large_cap_buyer, &caps, log_weight) - (1.0 / 4.69897 + 0.2)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_smart_money_score() {
+        let buy = |mint: &str| TokenInteraction {
+            wallet_address: "addr1".to_string(),
+            token_mint: mint.to_string(),
+            block_time: 1000,
+            sol_amount: 5.0,
+            is_early_entry: false,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        let outcomes: HashMap<String, f64> =
+            [("moon", 30.0), ("double", 2.0), ("flat", 1.0), ("rug", 0.0), ("rug2", -1.0)]
+                .iter()
+                .map(|&(mint, multiple)| (mint.to_string(), multiple))
+                .collect();
+
+        // Hit rate 2/3 and a mean multiple of 11x, repeated buys counted once
+        let smart = vec![buy("moon"), buy("moon"), buy("double"), buy("flat"), buy("unknown")];
+        assert_eq!(smart_money_score(&smart, &outcomes), 83);
+
+        let unlucky = vec![buy("rug"), buy("rug2"), buy("flat")];
+        assert_eq!(smart_money_score(&unlucky, &outcomes), 0);
+        assert_eq!(smart_money_score(&[buy("unknown")], &outcomes), 0);
+    }
+
+    #[test]
+    fn test_clamp_policy() {
+        let valid = WalletStats {
+            address: "test".to_string(),
+            total_volume_sol: 100.0,
+            interaction_count: 10,
+            average_entry_size: 10.0,
+            early_entry_count: 5,
+            winrate_proxy: 0.5,
+            avg_realized_multiple: None,
+            net_volume_sol: 100.0,
+        };
+        let out_of_range = WalletStats { early_entry_count: 15, ..valid.clone() };
+
+        assert_eq!(calculate_whale_score_with_policy(&valid, ClampPolicy::Error), Ok(calculate_whale_score(&valid)));
+
+        // Magnitudes are checked before they saturate
+        let large_entries = WalletStats { average_entry_size: 100.0, ..valid.clone() };
+        assert_eq!(
+            calculate_whale_score_with_policy(&large_entries, ClampPolicy::Error),
+            Err(ScoreRangeError::ComponentOutOfRange { component: "average_entry_size", value: 40.0, ceiling: 20.0 })
+        );
+        assert_eq!(
+            calculate_whale_score_with_policy(&large_entries, ClampPolicy::Saturate),
+            Ok(calculate_whale_score(&large_entries))
+        );
+
+        assert_eq!(
+            calculate_whale_score_with_policy(&out_of_range, ClampPolicy::Saturate),
+            Ok(calculate_whale_score(&out_of_range))
+        );
+        assert_eq!(
+            calculate_whale_score_with_policy(&out_of_range, ClampPolicy::Error),
+            Err(ScoreRangeError::ComponentOutOfRange { component: "early_entry_ratio", value: 30.0, ceiling: 20.0 })
+        );
+
+        let bad_winrate = WalletStats { winrate_proxy: 1.4, ..valid.clone() };
+        assert!(matches!(
+            calculate_whale_score_with_policy(&bad_winrate, ClampPolicy::Error),
+            Err(ScoreRangeError::ComponentOutOfRange { component: "profit", .. })
+        ));
+
+        // 20 + 200 + 20 + 10 + 40 + 5 raw points
+        let whale = WalletStats {
+            total_volume_sol: 500.0,
+            interaction_count: 100,
+            average_entry_size: 50.0,
+            early_entry_count: 100,
+            net_volume_sol: 500.0,
+            ..valid
+        };
+        assert_eq!(
+            calculate_whale_score_with_policy(&whale, ClampPolicy::Error),
+            Err(ScoreRangeError::TotalOutOfRange(295.0))
+        );
+        assert_eq!(calculate_whale_score_with_policy(&whale, ClampPolicy::Saturate), Ok(calculate_whale_score(&whale)));
+        assert_eq!(ClampPolicy::default(), ClampPolicy::Saturate);
+    }
+
+    #[test]
+    fn test_raw_subscores_follow_config() {
+        let stats = WalletStats {
+            address: "test".to_string(),
+            total_volume_sol: 120.0,
+            interaction_count: 12,
+            average_entry_size: 10.0,
+            early_entry_count: 4,
+            winrate_proxy: 0.5,
+            avg_realized_multiple: None,
+            net_volume_sol: 120.0,
+        };
+        let config = ScoringConfig { early_ratio_points: 30.0, volume_curve: ScoringCurve::Sqrt, ..ScoringConfig::default() };
+
+        let raw = raw_subscores_with(&stats, 4.0, &config);
+        let components = whale_score_components_with(&stats, &config);
+        assert_eq!(raw[0].weight, 30.0);
+        assert!((raw[0].points() + raw[1].points() - components[0]).abs() < 1e-9);
+        assert!((raw[2].points() + raw[3].points() - components[1]).abs() < 1e-9);
+        assert!((raw[2].raw_points() - (0.2f64).sqrt() * 20.0).abs() < 1e-9);
+        assert_eq!(raw[4].points(), components[2]);
+        assert_eq!(raw[5].points(), components[3]);
+    }
+
+    #[test]
+    fn test_cold_start_score() {
+        let wallet = |interaction_count: u64| WalletStats {
+            address: "new".to_string(),
+            total_volume_sol: interaction_count as f64,
+            interaction_count,
+            average_entry_size: 1.0,
+            early_entry_count: 0,
+            winrate_proxy: 0.0,
+            avg_realized_multiple: None,
+            net_volume_sol: interaction_count as f64,
+        };
+        let config = ScoringConfig { cold_start_score: 30, cold_start_threshold: 3, ..ScoringConfig::default() };
+
+        assert_eq!(calculate_whale_score_with_config(&wallet(0), &config), 30);
+        assert_eq!(calculate_whale_score_with_config(&wallet(2), &config), 30);
+        // At the threshold the wallet is scored on its own stats
+        assert_eq!(calculate_whale_score_with_config(&wallet(3), &config), calculate_whale_score(&wallet(3)));
+        assert!(calculate_whale_score(&wallet(3)) < 30);
+        // The default prior of 0 changes nothing
+        assert_eq!(calculate_whale_score_with_config(&wallet(2), &ScoringConfig::default()), calculate_whale_score(&wallet(2)));
+    }
+
+    #[test]
+    fn test_log_curve_compresses_high_end() {
+        let log = ScoringCurve::Log;
+
+        assert!(log.apply(0.0).abs() < 1e-9);
+        assert!((log.apply(1.0) - 1.0).abs() < 1e-9);
+        let low_gap = log.apply(0.2) - log.apply(0.0);
+        let high_gap = log.apply(1.0) - log.apply(0.8);
+        assert!(low_gap > high_gap);
+
+        let stats = WalletStats {
+            address: "test".to_string(),
+            total_volume_sol: 100.0,
+            interaction_count: 10,
+            average_entry_size: 10.0,
+            early_entry_count: 0,
+            winrate_proxy: 0.0,
+            avg_realized_multiple: None,
+            net_volume_sol: 100.0,
+        };
+        let log_config = ScoringConfig { volume_curve: ScoringCurve::Log, ..ScoringConfig::default() };
+        assert!(
+            calculate_whale_score_with_config(&stats, &log_config)
+                > calculate_whale_score_with_config(&stats, &ScoringConfig::default())
+        );
+    }
+
+    #[test]
+    fn test_reassign_moves_changed_wallet() {
+        let wallet = |address: &str, volume: f64, winrate: f64| WalletStats {
+            address: address.to_string(),
+            total_volume_sol: volume,
+            interaction_count: 10,
+            average_entry_size: volume / 10.0,
+            early_entry_count: 0,
+            winrate_proxy: winrate,
+            avg_realized_multiple: None,
+            net_volume_sol: volume,
+        };
+        let clusterer = WalletClusterer::new(0.8);
+
+        let mut stats_map = HashMap::new();
+        stats_map.insert("small_a".to_string(), wallet("small_a", 10.0, 0.2));
+        stats_map.insert("small_b".to_string(), wallet("small_b", 11.0, 0.2));
+        stats_map.insert("big_a".to_string(), wallet("big_a", 1000.0, 0.9));
+        stats_map.insert("big_b".to_string(), wallet("big_b", 1050.0, 0.9));
+
+        let mut clusters = vec![
+            vec!["small_a".to_string(), "small_b".to_string()],
+            vec!["big_a".to_string(), "big_b".to_string()],
+        ];
+
+        // small_b turns into a big wallet
+        stats_map.insert("small_b".to_string(), wallet("small_b", 1020.0, 0.9));
+        clusterer.reassign(&mut clusters, &stats_map, "small_b");
+
+        assert_eq!(clusters[0], vec!["big_a".to_string(), "big_b".to_string(), "small_b".to_string()]);
+        assert_eq!(clusters[1], vec!["small_a".to_string()]);
+
+        // A wallet that matches nothing gets its own cluster
+        stats_map.insert("small_a".to_string(), wallet("small_a", 300.0, 0.5));
+        clusterer.reassign(&mut clusters, &stats_map, "small_a");
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[1], vec!["small_a".to_string()]);
+    }
+
+    #[test]
+    fn test_incremental_clusterer_matches_batch() {
+        let wallet = |address: &str, volume: f64, winrate: f64| WalletStats {
+            address: address.to_string(),
+            total_volume_sol: volume,
+            interaction_count: 10,
+            average_entry_size: volume / 10.0,
+            early_entry_count: 0,
+            winrate_proxy: winrate,
+            avg_realized_multiple: None,
+            net_volume_sol: volume,
+        };
+        let arrivals = [
+            wallet("big_b", 1050.0, 0.9),
+            wallet("small_a", 10.0, 0.2),
+            wallet("mid", 300.0, 0.5),
+            wallet("big_a", 1000.0, 0.9),
+            wallet("small_b", 11.0, 0.2),
+        ];
+
+        let mut incremental = IncrementalClusterer::new(0.8);
+        for stats in &arrivals {
+            incremental.add_wallet(stats.address.clone(), stats);
+        }
+
+        let stats_map: HashMap<String, WalletStats> =
+            arrivals.iter().map(|s| (s.address.clone(), s.clone())).collect();
+        let batch = WalletClusterer::new(0.8).cluster_wallets(&stats_map);
+        assert_eq!(incremental.clusters(), batch);
+        assert_eq!(batch.len(), 3);
+
+        // Re-adding a wallet with new stats moves it
+        incremental.add_wallet("mid".to_string(), &wallet("mid", 12.0, 0.2));
+        assert_eq!(
+            incremental.clusters(),
+            vec![
+                vec!["big_a".to_string(), "big_b".to_string()],
+                vec!["mid".to_string(), "small_a".to_string(), "small_b".to_string()],
+            ]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_cluster_output_is_canonical() {
+        let clusterer = WalletClusterer::new(0.8);
+        let wallets: Vec<WalletStats> = [("d", 10.0), ("b", 11.0), ("a", 1000.0), ("e", 12.0), ("c", 1010.0)]
+            .iter()
+            .map(|&(address, volume)| WalletStats {
+                address: address.to_string(),
+                total_volume_sol: volume,
+                interaction_count: 10,
+                average_entry_size: volume / 10.0,
+                early_entry_count: 0,
+                winrate_proxy: 0.5,
+                avg_realized_multiple: None,
+                net_volume_sol: volume,
+            })
+            .collect();
+
+        let serialized: Vec<String> = (0..8)
+            .map(|run| {
+                // Fresh maps get fresh hash seeds; vary insertion order too
+                let mut stats_map = HashMap::new();
+                for i in 0..wallets.len() {
+                    let stats = &wallets[(i + run) % wallets.len()];
+                    stats_map.insert(stats.address.clone(), stats.clone());
+                }
+                serde_json::to_string(&clusterer.cluster_wallets(&stats_map)).unwrap()
+            })
+            .collect();
+
+        assert_eq!(serialized[0], r#"[["a","c"],["b","d","e"]]"#);
+        assert!(serialized.iter().all(|s| s == &serialized[0]));
+    }
+
+    #[test]
+    fn test_fit_improves_accuracy_on_separable_data() {
+        let wallet = |count: u64, avg: f64, early: u64, winrate: f64| WalletStats {
+            address: "test".to_string(),
+            total_volume_sol: avg * count as f64,
+            interaction_count: count,
+            average_entry_size: avg,
+            early_entry_count: early,
+            winrate_proxy: winrate,
+            avg_realized_multiple: None,
+            net_volume_sol: avg * count as f64,
+        };
+        // Whales: few large, profitable buys. Non-whales: many tiny early buys.
+        let examples = vec![
+            (wallet(6, 50.0, 0, 1.0), true),
+            (wallet(5, 45.0, 1, 0.9), true),
+            (wallet(8, 60.0, 0, 0.8), true),
+            (wallet(50, 0.1, 50, 0.0), false),
+            (wallet(60, 0.2, 55, 0.1), false),
+            (wallet(45, 0.1, 45, 0.0), false),
+        ];
+
+        let mut scorer = DynamicScorer::default();
+        let before = scorer.accuracy(&examples);
+        scorer.fit(&examples, 50.0, 200);
+        let after = scorer.accuracy(&examples);
+
+        assert!(after > before);
+        assert_eq!(after, 1.0);
+        let weights = scorer.weights();
+        assert!(weights.iter().all(|&w| w >= 0.0));
+        assert!((weights.iter().sum::<f64>() - 100.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_interaction_contributions_finds_big_early_buy() {
+        let interaction = |amount: f64, early: bool| TokenInteraction {
+            wallet_address: "addr1".to_string(),
+            token_mint: "token1".to_string(),
+            block_time: 1000,
+            sol_amount: amount,
+            is_early_entry: early,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        let interactions = vec![
+            interaction(2.0, false),
+            interaction(3.0, false),
+            interaction(80.0, true),
+            interaction(1.5, false),
+        ];
+
+        let contributions = interaction_contributions(&interactions);
+        assert_eq!(contributions.len(), interactions.len());
+
+        let biggest = contributions
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+            .map(|(i, _)| i);
+        assert_eq!(biggest, Some(2));
+        assert!(contributions[2] > 0.0);
+        assert!(interaction_contributions(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_cross_token_consistency() {
+        let buys = |trades: &[(&str, f64)]| -> Vec<TokenInteraction> {
+            trades
+                .iter()
+                .map(|&(mint, sol_amount)| TokenInteraction {
+                    wallet_address: "addr1".to_string(),
+                    token_mint: mint.to_string(),
+                    block_time: 1000,
+                    sol_amount,
+                    is_early_entry: false,
+                    side: TradeSide::Buy,
+                    source: None,
+                })
+                .collect()
+        };
+
+        // Uneven clips, but every token averages 10 SOL
+        let uniform = buys(&[("t1", 1.0), ("t1", 19.0), ("t2", 10.0), ("t3", 2.0), ("t3", 18.0)]);
+        assert_eq!(cross_token_consistency(&uniform), 100.0);
+        let detector = PatternDetector { min_early_entries: 0, min_avg_buy_size: 0.0, consistency_threshold: 0.0 };
+        assert!(detector.consistency_score(&uniform) < 30.0);
+
+        let uneven = buys(&[("t1", 1.0), ("t2", 50.0), ("t3", 4.0)]);
+        assert!(cross_token_consistency(&uneven) < 10.0);
+
+        assert_eq!(cross_token_consistency(&buys(&[("t1", 5.0), ("t1", 5.0)])), 0.0);
+        assert_eq!(cross_token_consistency(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_rolling_consistency_detects_regime_change() {
+        let sizes = [10.0, 10.5, 9.5, 10.0, 10.2, 1.0, 40.0, 3.0, 25.0];
+        let interactions: Vec<TokenInteraction> = sizes
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, &amount)| TokenInteraction {
+                wallet_address: "addr1".to_string(),
+                token_mint: "token1".to_string(),
+                block_time: 1000 + i as u64,
+                sol_amount: amount,
+                is_early_entry: false,
+                side: TradeSide::Buy,
+                source: None,
+            })
+            .collect();
+
+        let rolling = rolling_consistency(&interactions, 4);
+        assert_eq!(rolling.len(), sizes.len() - 3);
+        assert!(rolling[0] > 90.0);
+        assert!(rolling[rolling.len() - 1] < 50.0);
+
+        assert!(rolling_consistency(&interactions, 20).is_empty());
+        assert_eq!(rolling_consistency(&interactions, sizes.len()).len(), 1);
+    }
+
+    #[test]
+    fn test_scaled_features_make_winrate_matter() {
+        let wallet = |address: &str, volume: f64, winrate: f64| WalletStats {
+            address: address.to_string(),
+            total_volume_sol: volume,
+            interaction_count: 10,
+            average_entry_size: volume / 10.0,
+            early_entry_count: 0,
+            winrate_proxy: winrate,
+            avg_realized_multiple: None,
+            net_volume_sol: volume,
+        };
+        let mut stats_map = HashMap::new();
+        stats_map.insert("a".to_string(), wallet("a", 100.0, 0.50));
+        stats_map.insert("b".to_string(), wallet("b", 100.0, 0.56));
+        stats_map.insert("c".to_string(), wallet("c", 1000.0, 0.53));
+
+        let raw = WalletClusterer::new(0.9);
+        assert_eq!(
+            raw.cluster_wallets(&stats_map),
+            vec![vec!["a".to_string(), "b".to_string()], vec!["c".to_string()]]
+        );
+
+        // Scaled, a and b sit at opposite ends of the cohort's winrate range
+        let scaled = WalletClusterer::new(0.9).with_scale_features(true);
+        assert_eq!(
+            scaled.cluster_wallets(&stats_map),
+            vec![vec!["a".to_string()], vec!["b".to_string()], vec!["c".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_similarity_matrix() {
+        let wallet = |address: &str, volume: f64, winrate: f64| WalletStats {
+            address: address.to_string(),
+            total_volume_sol: volume,
+            interaction_count: 10,
+            average_entry_size: volume / 10.0,
+            early_entry_count: 0,
+            winrate_proxy: winrate,
+            avg_realized_multiple: None,
+            net_volume_sol: volume,
+        };
+        let mut stats_map = HashMap::new();
+        stats_map.insert("c".to_string(), wallet("c", 1000.0, 0.53));
+        stats_map.insert("a".to_string(), wallet("a", 100.0, 0.50));
+        stats_map.insert("b".to_string(), wallet("b", 100.0, 0.56));
+
+        for scale_features in [false, true] {
+            let clusterer = WalletClusterer::new(0.9).with_scale_features(scale_features);
+            let (addresses, matrix) = clusterer.similarity_matrix(&stats_map);
+
+            assert_eq!(addresses, ["a", "b", "c"]);
+            for (i, row) in matrix.iter().enumerate() {
+                assert!((row[i] - 1.0).abs() < 1e-9);
+                for (j, &sim) in row.iter().enumerate() {
+                    assert_eq!(sim, matrix[j][i]);
+                }
+            }
+            assert!(matrix[0][1] > matrix[0][2]);
+        }
+
+        let clusterer = WalletClusterer::new(0.9);
+        assert_eq!(clusterer.similarity_matrix(&HashMap::new()), (Vec::new(), Vec::new()));
+    }
+
+    #[test]
+    fn test_kmeans_separates_two_groups() {
+        let wallet = |address: &str, volume: f64, winrate: f64, early: u64| WalletStats {
+            address: address.to_string(),
+            total_volume_sol: volume,
+            interaction_count: 10,
+            average_entry_size: volume / 10.0,
+            early_entry_count: early,
+            winrate_proxy: winrate,
+            avg_realized_multiple: None,
+            net_volume_sol: volume,
+        };
+        let mut stats_map = HashMap::new();
+        for (address, volume, winrate, early) in [
+            ("big_a", 1000.0, 0.9, 9),
+            ("big_b", 1100.0, 0.85, 8),
+            ("big_c", 950.0, 0.95, 9),
+            ("small_a", 10.0, 0.1, 1),
+            ("small_b", 12.0, 0.15, 0),
+            ("small_c", 8.0, 0.05, 1),
+        ] {
+            stats_map.insert(address.to_string(), wallet(address, volume, winrate, early));
+        }
+
+        let expected = vec![
+            vec!["big_a".to_string(), "big_b".to_string(), "big_c".to_string()],
+            vec!["small_a".to_string(), "small_b".to_string(), "small_c".to_string()],
+        ];
+        for seed in 0..5 {
+            assert_eq!(kmeans_cluster_wallets(&stats_map, 2, seed, 20), expected);
+        }
+    }
+
+    #[test]
+    fn test_kmeans_degenerate_inputs() {
+        let mut stats_map = HashMap::new();
+        stats_map.insert("a".to_string(), WalletStats::empty("a"));
+        stats_map.insert("b".to_string(), WalletStats::empty("b"));
+
+        assert!(kmeans_cluster_wallets(&HashMap::new(), 3, 1, 10).is_empty());
+        assert!(kmeans_cluster_wallets(&stats_map, 0, 1, 10).is_empty());
+        assert_eq!(
+            kmeans_cluster_wallets(&stats_map, 5, 1, 10),
+            vec![vec!["a".to_string()], vec!["b".to_string()]]
+        );
+        // Identical wallets can't be split further
+        assert_eq!(kmeans_cluster_wallets(&stats_map, 1, 1, 10), vec![vec!["a".to_string(), "b".to_string()]]);
+    }
+
+    #[test]
+    fn test_cluster_adjusted_score() {
+        let stats = WalletStats {
+            address: "addr1".to_string(),
+            total_volume_sol: 50.0,
+            interaction_count: 5,
+            average_entry_size: 10.0,
+            early_entry_count: 3,
+            winrate_proxy: 0.7,
+            avg_realized_multiple: None,
+            net_volume_sol: 50.0,
+        };
+        let base = calculate_whale_score(&stats);
+
+        assert_eq!(cluster_adjusted_score(&stats, 1, 5.0, 90), base);
+        assert_eq!(cluster_adjusted_score(&stats, 0, 5.0, 90), base);
+        assert_eq!(cluster_adjusted_score(&stats, 3, 2.0, 90), base + 4);
+        assert_eq!(cluster_adjusted_score(&stats, 50, 5.0, 90), 90);
+        // A cap below the base score never lowers it
+        assert_eq!(cluster_adjusted_score(&stats, 50, 5.0, 10), base);
+        assert_eq!(cluster_adjusted_score(&stats, 3, -2.0, 90), base - 4);
+        assert_eq!(cluster_adjusted_score(&stats, 1000, -5.0, 90), 0);
+    }
+
+    #[test]
+    fn test_exposure_weighted_score() {
+        let clever = |address: &str, total_volume_sol: f64| WalletStats {
+            address: address.to_string(),
+            total_volume_sol,
+            interaction_count: 10,
+            average_entry_size: total_volume_sol / 10.0,
+            early_entry_count: 8,
+            winrate_proxy: 1.0,
+            avg_realized_multiple: None,
+            net_volume_sol: total_volume_sol,
+        };
+        let small = clever("small", 20.0);
+        let large = clever("large", 980.0);
+        let cohort_total = small.total_volume_sol + large.total_volume_sol;
+
+        let small_score = exposure_weighted_score(&small, cohort_total);
+        let large_score = exposure_weighted_score(&large, cohort_total);
+        assert!((small_score - calculate_whale_score(&small) as f64 * 0.02).abs() < 1e-9);
+        assert!((large_score - calculate_whale_score(&large) as f64 * 0.98).abs() < 1e-9);
+        assert!(large_score > 10.0 * small_score);
+
+        assert_eq!(exposure_weighted_score(&large, 0.0), 0.0);
+        assert_eq!(exposure_weighted_score(&large, f64::NAN), 0.0);
+        // A wallet can't hold more than the whole cohort
+        assert_eq!(exposure_weighted_score(&large, 100.0), calculate_whale_score(&large) as f64);
+    }
+
+    #[test]
+    fn test_score_stability() {
+        let interaction = |block_time: u64, sol_amount: f64, is_early_entry: bool| TokenInteraction {
+            wallet_address: "addr1".to_string(),
+            token_mint: "token1".to_string(),
+            block_time,
+            sol_amount,
+            is_early_entry,
+            side: TradeSide::Buy,
+            source: None,
+        };
+
+        // Already saturated on every component within the first quarter
+        let settled: Vec<TokenInteraction> = (0..200).map(|i| interaction(1000 + i, 60.0, true)).collect();
+        let report = score_stability(&settled);
+        assert_eq!(report.scores, [100; 4]);
+        assert_eq!(report.max_swing, 0);
+
+        // Small late entries first, then large early buys
+        let mut moving: Vec<TokenInteraction> = (0..4).map(|i| interaction(1000 + i, 0.5, false)).collect();
+        moving.extend((4..8).map(|i| interaction(1000 + i, 80.0, true)));
+        moving.reverse();
+        let report = score_stability(&moving);
+        assert!(report.scores[0] < report.scores[3]);
+        assert!(report.max_swing >= 15);
+
+        assert_eq!(score_stability(&[]), StabilityReport { scores: [0; 4], max_swing: 0 });
+    }
+
+    #[test]
+    fn test_consensus_clusters() {
+        let wallet = |address: &str, volume: f64| WalletStats {
+            address: address.to_string(),
+            total_volume_sol: volume,
+            interaction_count: 10,
+            average_entry_size: volume / 10.0,
+            early_entry_count: 0,
+            winrate_proxy: 0.5,
+            avg_realized_multiple: None,
+            net_volume_sol: volume,
+        };
+        let stats_map: HashMap<String, WalletStats> =
+            [wallet("a", 100.0), wallet("b", 101.0), wallet("c", 150.0), wallet("d", 5000.0)]
+                .into_iter()
+                .map(|s| (s.address.clone(), s))
+                .collect();
+
+        // a and b are near-identical; c only joins them at the most permissive 0.8
+        let groups = consensus_clusters(&stats_map, &[0.8, 0.9, 0.95]);
+        assert_eq!(
+            groups,
+            vec![
+                vec!["a".to_string(), "b".to_string()],
+                vec!["c".to_string()],
+                vec!["d".to_string()],
+            ]
+        );
+        assert_eq!(consensus_clusters(&stats_map, &[0.8, 0.8, 0.95])[0], ["a", "b", "c"]);
+        assert_eq!(consensus_clusters(&stats_map, &[]).len(), 4);
+    }
+
+    #[test]
+    fn test_isolation_threshold() {
+        let wallet = |address: &str, volume: f64| WalletStats {
+            address: address.to_string(),
+            total_volume_sol: volume,
+            interaction_count: 10,
+            average_entry_size: volume / 10.0,
+            early_entry_count: 0,
+            winrate_proxy: 0.5,
+            avg_realized_multiple: None,
+            net_volume_sol: volume,
+        };
+        let stats_map: HashMap<String, WalletStats> =
+            [wallet("a", 100.0), wallet("twin", 101.0), wallet("unique", 5000.0)]
+                .into_iter()
+                .map(|s| (s.address.clone(), s))
+                .collect();
+
+        let twin = isolation_threshold(&stats_map, "a");
+        let unique = isolation_threshold(&stats_map, "unique");
+        assert!(twin > 0.99);
+        assert!(unique < 0.5);
+
+        // Just above the threshold the wallet is a singleton; at it, it isn't
+        let pair: HashMap<String, WalletStats> = ["a", "unique"]
+            .iter()
+            .map(|address| (address.to_string(), stats_map[*address].clone()))
+            .collect();
+        let threshold = isolation_threshold(&pair, "unique");
+        let clusters_at = |similarity_threshold: f64| {
+            WalletClusterer::new(similarity_threshold).cluster_wallets(&pair).len()
+        };
+        assert_eq!(clusters_at(threshold), 1);
+        assert_eq!(clusters_at(threshold + 1e-9), 2);
+        assert_eq!(isolation_threshold(&stats_map, "missing"), 0.0);
+    }
+
+    #[test]
+    fn test_summarize_clusters() {
+        let wallet = |address: &str, volume: f64, interaction_count: u64| WalletStats {
+            address: address.to_string(),
+            total_volume_sol: volume,
+            interaction_count,
+            average_entry_size: volume / interaction_count as f64,
+            early_entry_count: 0,
+            winrate_proxy: 0.0,
+            avg_realized_multiple: None,
+            net_volume_sol: volume,
+        };
+        let mut stats_map = HashMap::new();
+        stats_map.insert("a".to_string(), wallet("a", 100.0, 10));
+        stats_map.insert("b".to_string(), wallet("b", 250.0, 25));
+
+        let clusters = vec![vec!["a".to_string(), "b".to_string()], vec!["ghost".to_string()]];
+        let summaries = summarize_clusters(&clusters, &stats_map);
+
+        let (score_a, score_b) = (calculate_whale_score(&stats_map["a"]), calculate_whale_score(&stats_map["b"]));
+        assert_eq!(summaries[0].member_count, 2);
+        assert_eq!(summaries[0].total_volume_sol, 350.0);
+        assert!((summaries[0].mean_whale_score - (score_a as f64 + score_b as f64) / 2.0).abs() < 1e-9);
+        assert_eq!(summaries[0].id.len(), 16);
+        assert_eq!(summaries[1].member_count, 1);
+        assert_eq!(summaries[1].mean_whale_score, 0.0);
+
+        let reordered = summarize_clusters(&[vec!["b".to_string(), "a".to_string()]], &stats_map);
+        assert_eq!(reordered[0].id, summaries[0].id);
+        assert_ne!(summaries[0].id, summaries[1].id);
+    }
+
+    #[test]
+    fn test_cluster_centroid() {
+        let wallet = |address: &str, volume: f64, interaction_count: u64, winrate_proxy: f64| WalletStats {
+            address: address.to_string(),
+            total_volume_sol: volume,
+            interaction_count,
+            average_entry_size: volume / interaction_count as f64,
+            early_entry_count: 0,
+            winrate_proxy,
+            avg_realized_multiple: None,
+            net_volume_sol: volume,
+        };
+        let mut stats_map = HashMap::new();
+        stats_map.insert("a".to_string(), wallet("a", 100.0, 10, 0.2));
+        stats_map.insert("b".to_string(), wallet("b", 300.0, 20, 0.6));
+        let cluster = vec!["b".to_string(), "a".to_string(), "ghost".to_string()];
+
+        // Weights 0.25 and 0.75
+        let centroid = cluster_centroid(&cluster, &stats_map);
+        assert!((centroid.total_volume_sol - (0.25 * 100.0 + 0.75 * 300.0)).abs() < 1e-9);
+        assert_eq!(centroid.interaction_count, 18);
+        assert!((centroid.winrate_proxy - 0.5).abs() < 1e-9);
+        assert_eq!(centroid.avg_realized_multiple, None);
+        assert_eq!(centroid.address, summarize_clusters(&[cluster], &stats_map)[0].id);
+
+        let empty = cluster_centroid(&["ghost".to_string()], &stats_map);
+        assert_eq!(empty.interaction_count, 0);
+        assert_eq!(empty.address.len(), 16);
+    }
+
+    #[test]
+    fn test_similarity_zero_volume_handling() {
+        let clusterer = WalletClusterer::new(0.8);
+        let wallet = |volume: f64| WalletStats {
+            address: "test".to_string(),
+            total_volume_sol: volume,
+            interaction_count: 1,
+            average_entry_size: volume,
+            early_entry_count: 0,
+            winrate_proxy: 0.5,
+            avg_realized_multiple: None,
+            net_volume_sol: volume,
+        };
+
+        // Zero/zero: identical on both magnitude dimensions
+        assert!((clusterer.similarity(&wallet(0.0), &wallet(0.0)) - 1.0).abs() < 1e-9);
+        // Zero/non-zero: nothing in common on magnitudes, only winrate matches
+        assert!((clusterer.similarity(&wallet(0.0), &wallet(0.01)) - 1.0 / 3.0).abs() < 1e-9);
+        // Non-zero/non-zero: 0.1 vs 0.3 -> 1 - 0.2 / 0.4 on both magnitudes
+        assert!((clusterer.similarity(&wallet(0.1), &wallet(0.3)) - 2.0 / 3.0).abs() < 1e-9);
+
+        assert_eq!(relative_similarity(0.0, 0.0), 1.0);
+        assert_eq!(relative_similarity(0.0, 5.0), 0.0);
+        assert_eq!(relative_similarity(5.0, 0.0), 0.0);
+        assert!((relative_similarity(10.0, 30.0) - 0.5).abs() < 1e-9);
+    }
+}
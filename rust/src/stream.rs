@@ -0,0 +1,280 @@
+//! Incremental scoring over a live feed of interactions
+//! Per-wallet accumulators that update scores one interaction at a time
+
+use crate::{calculate_whale_score, process_interactions, signed_amount, winrate_proxy, TokenInteraction, WalletStats};
+use std::collections::HashMap;
+#[cfg(feature = "futures")]
+use std::pin::Pin;
+#[cfg(feature = "futures")]
+use std::task::{Context, Poll};
+
+/// Running aggregates for one wallet
+///
+/// `stats()` matches `process_interactions` over the same interactions fed in
+/// the same order.
+#[derive(Debug, Clone, Default)]
+pub struct WalletAccumulator {
+    address: String,
+    total_volume_sol: f64,
+    net_volume_sol: f64,
+    interaction_count: u64,
+    early_entry_count: u64,
+}
+
+impl WalletAccumulator {
+    pub fn new(address: impl Into<String>) -> Self {
+        Self {
+            address: address.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Fold one interaction into the aggregates
+    pub fn add(&mut self, interaction: &TokenInteraction) {
+        self.total_volume_sol += interaction.sol_amount;
+        self.net_volume_sol += signed_amount(interaction);
+        self.interaction_count += 1;
+        if interaction.is_early_entry {
+            self.early_entry_count += 1;
+        }
+    }
+
+    /// Current stats, derived the same way `process_interactions` derives them
+    pub fn stats(&self) -> WalletStats {
+        if self.interaction_count == 0 {
+            return WalletStats::empty(&self.address);
+        }
+
+        WalletStats {
+            address: self.address.clone(),
+            total_volume_sol: self.total_volume_sol,
+            interaction_count: self.interaction_count,
+            average_entry_size: self.total_volume_sol / self.interaction_count as f64,
+            early_entry_count: self.early_entry_count,
+            winrate_proxy: winrate_proxy(self.early_entry_count as f64, self.interaction_count as f64),
+            avg_realized_multiple: None,
+            net_volume_sol: self.net_volume_sol,
+        }
+    }
+}
+
+/// Per-wallet accumulators that re-score a wallet on every new interaction
+#[derive(Debug, Clone, Default)]
+pub struct StreamScorer {
+    accumulators: HashMap<String, WalletAccumulator>,
+}
+
+impl StreamScorer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an interaction and return its wallet's updated `(address, score)`
+    pub fn push(&mut self, interaction: &TokenInteraction) -> (String, u8) {
+        let accumulator = self
+            .accumulators
+            .entry(interaction.wallet_address.clone())
+            .or_insert_with(|| WalletAccumulator::new(interaction.wallet_address.clone()));
+        accumulator.add(interaction);
+        (interaction.wallet_address.clone(), calculate_whale_score(&accumulator.stats()))
+    }
+
+    /// Current stats for a wallet seen so far
+    pub fn stats(&self, address: &str) -> Option<WalletStats> {
+        self.accumulators.get(address).map(WalletAccumulator::stats)
+    }
+}
+
+/// Score change caused by a batch of new interactions
+///
+/// Returns `(before, after, after - before)`, where `after` scores `before`
+/// merged (`WalletStats::merge`) with the stats of `new_interactions`, so a
+/// feed can report "score rose 12 points on these 3 trades".
+pub fn score_delta_from(before: &WalletStats, new_interactions: &[TokenInteraction]) -> (u8, u8, i16) {
+    let before_score = calculate_whale_score(before);
+    let after_score = calculate_whale_score(&before.merge(&process_interactions(new_interactions)));
+    (before_score, after_score, after_score as i16 - before_score as i16)
+}
+
+/// Earliest block seen per token mint in a live feed
+///
+/// Stands in for token creation times when only the trade stream is
+/// available: live `is_early_entry` checks can use `earliest` without
+/// re-scanning history. Out-of-order observations are fine, since only the
+/// running minimum is kept.
+#[derive(Debug, Clone, Default)]
+pub struct TokenCreationTracker {
+    earliest: HashMap<String, u64>,
+}
+
+impl TokenCreationTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that `mint` was seen at `block_time`
+    pub fn observe(&mut self, mint: &str, block_time: u64) {
+        match self.earliest.get_mut(mint) {
+            Some(earliest) => *earliest = (*earliest).min(block_time),
+            None => {
+                self.earliest.insert(mint.to_string(), block_time);
+            }
+        }
+    }
+
+    /// Earliest `block_time` observed for `mint`
+    pub fn earliest(&self, mint: &str) -> Option<u64> {
+        self.earliest.get(mint).copied()
+    }
+}
+
+/// Lazily score a feed, emitting `(address, score)` after every interaction
+///
+/// Each interaction changes its wallet's stats, so one updated score is
+/// emitted per input item. For async feeds use `score_stream` (`futures`
+/// feature) or drive a `StreamScorer` directly.
+pub fn score_iter<I>(interactions: I) -> impl Iterator<Item = (String, u8)>
+where
+    I: IntoIterator<Item = TokenInteraction>,
+{
+    let mut scorer = StreamScorer::new();
+    interactions.into_iter().map(move |interaction| scorer.push(&interaction))
+}
+
+/// `Stream` adapter behind `score_stream`
+#[cfg(feature = "futures")]
+struct ScoreStream<S> {
+    inner: Pin<Box<S>>,
+    scorer: StreamScorer,
+}
+
+#[cfg(feature = "futures")]
+impl<S: futures_core::Stream<Item = TokenInteraction>> futures_core::Stream for ScoreStream<S> {
+    type Item = (String, u8);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        match this.inner.as_mut().poll_next(cx) {
+            Poll::Ready(Some(interaction)) => Poll::Ready(Some(this.scorer.push(&interaction))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Score an async feed, emitting `(address, score)` after every interaction
+///
+/// The async counterpart of `score_iter`: a `StreamScorer` keeps one
+/// accumulator per wallet and each incoming interaction yields its wallet's
+/// updated score. Needs the `futures` feature; the returned `Stream` doesn't
+/// depend on any executor, so it runs under tokio or anything else.
+#[cfg(feature = "futures")]
+pub fn score_stream(
+    stream: impl futures_core::Stream<Item = TokenInteraction>,
+) -> impl futures_core::Stream<Item = (String, u8)> {
+    ScoreStream {
+        inner: Box::pin(stream),
+        scorer: StreamScorer::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TradeSide;
+
+    fn interaction(wallet: &str, amount: f64, early: bool) -> TokenInteraction {
+        TokenInteraction {
+            wallet_address: wallet.to_string(),
+            token_mint: "token1".to_string(),
+            block_time: 1000,
+            sol_amount: amount,
+            is_early_entry: early,
+            side: TradeSide::Buy,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_score_iter_emits_updated_scores() {
+        let feed = vec![
+            interaction("a", 40.0, true),
+            interaction("b", 1.0, false),
+            interaction("a", 60.0, true),
+        ];
+
+        let emitted: Vec<(String, u8)> = score_iter(feed.clone()).collect();
+
+        assert_eq!(emitted.len(), 3);
+        assert_eq!(emitted[0], ("a".to_string(), calculate_whale_score(&process_interactions(&feed[..1]))));
+        assert_eq!(emitted[1], ("b".to_string(), calculate_whale_score(&process_interactions(&feed[1..2]))));
+        let a_all = [feed[0].clone(), feed[2].clone()];
+        assert_eq!(emitted[2], ("a".to_string(), calculate_whale_score(&process_interactions(&a_all))));
+        assert!(emitted[2].1 > emitted[0].1);
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn test_score_stream_emits_updated_scores() {
+        use futures::{executor::block_on, stream, StreamExt};
+
+        let feed = vec![
+            interaction("a", 40.0, true),
+            interaction("b", 1.0, false),
+            interaction("a", 60.0, true),
+        ];
+
+        let emitted: Vec<(String, u8)> = block_on(score_stream(stream::iter(feed.clone())).collect());
+        assert_eq!(emitted, score_iter(feed).collect::<Vec<_>>());
+        assert_eq!(emitted.len(), 3);
+        assert!(emitted[2].1 > emitted[0].1);
+    }
+
+    #[test]
+    fn test_score_delta_from() {
+        let history = vec![interaction("a", 1.0, false), interaction("a", 2.0, false), interaction("a", 1.5, true)];
+        let before = process_interactions(&history);
+        let batch = vec![interaction("a", 60.0, true)];
+
+        let (old, new, delta) = score_delta_from(&before, &batch);
+        let all: Vec<TokenInteraction> = history.iter().chain(&batch).cloned().collect();
+        assert_eq!(old, calculate_whale_score(&before));
+        assert_eq!(new, calculate_whale_score(&process_interactions(&all)));
+        assert_eq!(delta, new as i16 - old as i16);
+        assert!(delta > 10);
+        assert_eq!(score_delta_from(&before, &[]), (old, old, 0));
+    }
+
+    #[test]
+    fn test_token_creation_tracker_keeps_minimum() {
+        let mut tracker = TokenCreationTracker::new();
+        for (mint, block_time) in [("t1", 1050), ("t2", 900), ("t1", 1000), ("t1", 1200), ("t2", 950)] {
+            tracker.observe(mint, block_time);
+        }
+
+        assert_eq!(tracker.earliest("t1"), Some(1000));
+        assert_eq!(tracker.earliest("t2"), Some(900));
+        assert_eq!(tracker.earliest("t3"), None);
+        assert!(crate::is_early_entry(1030, tracker.earliest("t1").unwrap(), 60));
+    }
+
+    #[test]
+    fn test_accumulator_matches_process_interactions() {
+        let interactions = vec![interaction("a", 1.5, true), interaction("a", 2.25, false), interaction("a", 9.0, true)];
+        let mut accumulator = WalletAccumulator::new("a");
+        for i in &interactions {
+            accumulator.add(i);
+        }
+
+        let expected = process_interactions(&interactions);
+        let stats = accumulator.stats();
+        assert_eq!(stats.total_volume_sol, expected.total_volume_sol);
+        assert_eq!(stats.average_entry_size, expected.average_entry_size);
+        assert_eq!(stats.early_entry_count, expected.early_entry_count);
+        assert_eq!(stats.winrate_proxy, expected.winrate_proxy);
+    }
+}
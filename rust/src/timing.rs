@@ -0,0 +1,1080 @@
+//! Temporal analysis of interaction streams
+//! Launch-relative windows and activity timing features
+
+use crate::coordination::pearson;
+use crate::stream::WalletAccumulator;
+use crate::{calculate_whale_score, is_early_entry, process_interactions, TokenInteraction, TradeSide};
+use std::collections::{HashMap, HashSet};
+
+/// Seconds from `earlier` to `later`, or 0 if `later` is actually earlier
+///
+/// Every temporal difference goes through this so out-of-order or future
+/// timestamps can't underflow (a panic in debug builds, a wrap in release).
+pub(crate) fn saturating_elapsed(later: u64, earlier: u64) -> u64 {
+    later.saturating_sub(earlier)
+}
+
+/// Return the interactions sorted by `block_time` (stable for equal times)
+pub(crate) fn sorted_by_time(interactions: &[TokenInteraction]) -> Vec<&TokenInteraction> {
+    let mut sorted: Vec<&TokenInteraction> = interactions.iter().collect();
+    sorted.sort_by_key(|i| i.block_time);
+    sorted
+}
+
+/// Data-quality summary of an interaction stream's timestamps
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChronologyReport {
+    /// Input is already in non-decreasing `block_time` order
+    pub is_sorted: bool,
+    /// Largest gap between consecutive interactions once sorted
+    pub max_gap_seconds: u64,
+    pub min_block_time: Option<u64>,
+    pub max_block_time: Option<u64>,
+    /// Interactions with an earlier `block_time` than the one before them in input order
+    pub out_of_order_count: usize,
+}
+
+/// Check an interaction stream's time ordering and find its largest gap
+///
+/// Empty input reports as sorted with no gap and no time range.
+pub fn chronology_report(interactions: &[TokenInteraction]) -> ChronologyReport {
+    let out_of_order_count = interactions
+        .windows(2)
+        .filter(|pair| pair[1].block_time < pair[0].block_time)
+        .count();
+
+    let sorted = sorted_by_time(interactions);
+    let max_gap_seconds = sorted
+        .windows(2)
+        .map(|pair| saturating_elapsed(pair[1].block_time, pair[0].block_time))
+        .max()
+        .unwrap_or(0);
+
+    ChronologyReport {
+        is_sorted: out_of_order_count == 0,
+        max_gap_seconds,
+        min_block_time: sorted.first().map(|i| i.block_time),
+        max_block_time: sorted.last().map(|i| i.block_time),
+        out_of_order_count,
+    }
+}
+
+/// When a wallet was first and last active, relative to `now`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AgeStats {
+    pub first_seen: u64,
+    pub last_seen: u64,
+    /// `now - first_seen` (0 if `first_seen` is in the future)
+    pub age_seconds: u64,
+    /// `now - last_seen` (0 if `last_seen` is in the future)
+    pub seconds_since_last: u64,
+}
+
+/// Age and freshness of a wallet from its interactions
+///
+/// Returns `None` for a wallet with no interactions, which has no age.
+pub fn wallet_age_stats(interactions: &[TokenInteraction], now: u64) -> Option<AgeStats> {
+    let first_seen = interactions.iter().map(|i| i.block_time).min()?;
+    let last_seen = interactions.iter().map(|i| i.block_time).max()?;
+
+    Some(AgeStats {
+        first_seen,
+        last_seen,
+        age_seconds: saturating_elapsed(now, first_seen),
+        seconds_since_last: saturating_elapsed(now, last_seen),
+    })
+}
+
+/// Shift every `block_time` by `offset` seconds to align clocks across sources
+///
+/// Merging feeds with different epoch bases otherwise makes early-entry
+/// windows meaningless. Shifts saturate at 0 and `u64::MAX` rather than
+/// wrapping.
+pub fn normalize_block_times(interactions: &mut [TokenInteraction], offset: i64) {
+    for interaction in interactions {
+        interaction.block_time = interaction.block_time.saturating_add_signed(offset);
+    }
+}
+
+/// Copy of `interactions` with `block_time` shifted as `normalize_block_times` does
+pub fn normalized_block_times(interactions: &[TokenInteraction], offset: i64) -> Vec<TokenInteraction> {
+    let mut shifted = interactions.to_vec();
+    normalize_block_times(&mut shifted, offset);
+    shifted
+}
+
+/// Compute an early-entry window from a token's volume curve
+///
+/// Returns the number of seconds after the token's first buy within which
+/// `fraction` (clamped to 0-1) of its total volume occurred. The result can be
+/// passed as `window_seconds` to `is_early_entry`. When all of the volume lands
+/// in a single block the window is 0; empty input or zero volume also return 0.
+pub fn adaptive_early_window(interactions_for_token: &[TokenInteraction], fraction: f64) -> u64 {
+    let sorted = sorted_by_time(interactions_for_token);
+    let first_time = match sorted.first() {
+        Some(first) => first.block_time,
+        None => return 0,
+    };
+
+    let total_volume: f64 = sorted.iter().map(|i| i.sol_amount).sum();
+    if total_volume <= 0.0 {
+        return 0;
+    }
+
+    let target = total_volume * fraction.clamp(0.0, 1.0);
+    let mut cumulative = 0.0;
+
+    for interaction in &sorted {
+        cumulative += interaction.sol_amount;
+        if cumulative >= target {
+            return saturating_elapsed(interaction.block_time, first_time);
+        }
+    }
+
+    saturating_elapsed(sorted[sorted.len() - 1].block_time, first_time)
+}
+
+/// Rank every buy by how early it entered its token
+///
+/// Keys are `(token_mint, block_time)`; the value is the 1-based position of
+/// that time among the token's distinct wallets ordered by first buy. Wallets
+/// whose first buys share a block share a rank, and later buys by a wallet
+/// that already entered get the rank the next new wallet would receive.
+pub fn entry_ranks(interactions: &[TokenInteraction]) -> HashMap<(String, u64), usize> {
+    let mut first_buys: HashMap<&str, HashMap<&str, u64>> = HashMap::new();
+    for interaction in interactions {
+        let first = first_buys
+            .entry(interaction.token_mint.as_str())
+            .or_default()
+            .entry(interaction.wallet_address.as_str())
+            .or_insert(interaction.block_time);
+        *first = (*first).min(interaction.block_time);
+    }
+
+    let mut ranks = HashMap::new();
+    for interaction in interactions {
+        let wallets = &first_buys[interaction.token_mint.as_str()];
+        let earlier = wallets.values().filter(|&&t| t < interaction.block_time).count();
+        ranks.insert(
+            (interaction.token_mint.clone(), interaction.block_time),
+            earlier + 1,
+        );
+    }
+
+    ranks
+}
+
+/// Rank of the wallet's earliest buy of each token, for tokens in `entry_ranks`
+fn first_entry_ranks<'a>(
+    wallet_interactions: &'a [TokenInteraction],
+    entry_ranks: &HashMap<(String, u64), usize>,
+) -> HashMap<&'a str, usize> {
+    let mut first_buys: HashMap<&str, u64> = HashMap::new();
+    for interaction in wallet_interactions {
+        let first = first_buys
+            .entry(interaction.token_mint.as_str())
+            .or_insert(interaction.block_time);
+        *first = (*first).min(interaction.block_time);
+    }
+
+    first_buys
+        .into_iter()
+        .filter_map(|(mint, time)| entry_ranks.get(&(mint.to_string(), time)).map(|&rank| (mint, rank)))
+        .collect()
+}
+
+/// Score how consistently a wallet is among the first buyers of its tokens
+///
+/// For each token, the wallet's earliest buy is looked up in the rank map
+/// built by `entry_ranks` and contributes `1 / rank`. The average across tokens is
+/// scaled to 0-100, so a wallet that is always first scores 100. Tokens
+/// missing from the map are skipped; returns 0.0 if none are ranked.
+pub fn first_mover_score(
+    wallet_interactions: &[TokenInteraction],
+    entry_ranks: &HashMap<(String, u64), usize>,
+) -> f64 {
+    let ranks = first_entry_ranks(wallet_interactions, entry_ranks);
+    if ranks.is_empty() {
+        return 0.0;
+    }
+
+    ranks.values().map(|&rank| 1.0 / rank.max(1) as f64).sum::<f64>() / ranks.len() as f64 * 100.0
+}
+
+/// Number of tokens where both wallets were among the first `top_k` buyers
+///
+/// Each wallet's earliest buy of a token is ranked with the map built by
+/// `entry_ranks`. Repeatedly entering the same tokens first, together, is a
+/// much stronger insider-group signal than merely trading the same tokens.
+/// Tokens missing from the map are skipped.
+pub fn shared_first_mover_count(
+    a: &[TokenInteraction],
+    b: &[TokenInteraction],
+    entry_ranks: &HashMap<(String, u64), usize>,
+    top_k: usize,
+) -> usize {
+    let ranks_a = first_entry_ranks(a, entry_ranks);
+    let ranks_b = first_entry_ranks(b, entry_ranks);
+
+    ranks_a
+        .iter()
+        .filter(|&(mint, &rank)| rank <= top_k && ranks_b.get(mint).is_some_and(|&other| other <= top_k))
+        .count()
+}
+
+/// Whether a wallet bought ahead of or after its token's volume peak
+///
+/// The peak is the block with the most token volume (earliest on ties). The
+/// wallet's volume-weighted average buy time is compared to it and the gap
+/// is divided by the token's active span, giving a value in `[-1, 1]`:
+/// negative means the wallet bought before the peak, positive that it chased.
+/// Returns 0.0 when either side has no volume or the token traded in one block.
+pub fn buy_timing_vs_peak(
+    wallet_interactions: &[TokenInteraction],
+    token_interactions: &[TokenInteraction],
+) -> f64 {
+    let mut block_volumes: HashMap<u64, f64> = HashMap::new();
+    for interaction in token_interactions {
+        *block_volumes.entry(interaction.block_time).or_insert(0.0) += interaction.sol_amount;
+    }
+
+    let peak_time = match block_volumes
+        .iter()
+        .max_by(|(ta, va), (tb, vb)| va.total_cmp(vb).then(tb.cmp(ta)))
+    {
+        Some((&time, &volume)) if volume > 0.0 => time,
+        _ => return 0.0,
+    };
+
+    let first = block_volumes.keys().min().copied().unwrap_or(peak_time);
+    let last = block_volumes.keys().max().copied().unwrap_or(peak_time);
+    let span = saturating_elapsed(last, first) as f64;
+
+    let wallet_volume: f64 = wallet_interactions.iter().map(|i| i.sol_amount).sum();
+    if span <= 0.0 || wallet_volume <= 0.0 {
+        return 0.0;
+    }
+
+    let weighted_time: f64 = wallet_interactions
+        .iter()
+        .map(|i| i.block_time as f64 * i.sol_amount)
+        .sum::<f64>()
+        / wallet_volume;
+
+    ((weighted_time - peak_time as f64) / span).clamp(-1.0, 1.0)
+}
+
+/// Mean delay between token launch and the wallet's first buy of each token
+///
+/// For every token in `creation_times`, takes the wallet's earliest buy and
+/// measures `block_time - creation_time` in seconds, clamping pre-creation
+/// buys to 0. Returns `None` if none of the wallet's tokens are in the map.
+pub fn avg_seconds_after_launch(
+    wallet_interactions: &[TokenInteraction],
+    creation_times: &HashMap<String, u64>,
+) -> Option<f64> {
+    let mut first_buys: HashMap<&str, u64> = HashMap::new();
+    for interaction in wallet_interactions {
+        let first = first_buys
+            .entry(interaction.token_mint.as_str())
+            .or_insert(interaction.block_time);
+        *first = (*first).min(interaction.block_time);
+    }
+
+    let delays: Vec<u64> = first_buys
+        .iter()
+        .filter_map(|(mint, &time)| creation_times.get(*mint).map(|&created| saturating_elapsed(time, created)))
+        .collect();
+
+    if delays.is_empty() {
+        return None;
+    }
+    Some(delays.iter().sum::<u64>() as f64 / delays.len() as f64)
+}
+
+/// Pearson correlation between buy size and how soon after launch it landed
+///
+/// Pairs each buy's `sol_amount` with its negated seconds after its token's
+/// creation (pre-creation buys count as 0 seconds), so a positive value
+/// means the wallet's biggest buys were its earliest (conviction) and a
+/// negative one that it sized up late (FOMO). Sells and buys of tokens
+/// missing from `creation_times` are skipped. Returns 0.0 with fewer than
+/// two usable buys or when either sizes or timings don't vary.
+pub fn size_earliness_correlation(
+    interactions: &[TokenInteraction],
+    creation_times: &HashMap<String, u64>,
+) -> f64 {
+    let (sizes, earliness): (Vec<f64>, Vec<f64>) = interactions
+        .iter()
+        .filter(|i| i.side == TradeSide::Buy)
+        .filter_map(|i| {
+            let created = creation_times.get(&i.token_mint)?;
+            Some((i.sol_amount, -(saturating_elapsed(i.block_time, *created) as f64)))
+        })
+        .unzip();
+
+    if sizes.len() < 2 {
+        return 0.0;
+    }
+    pearson(&sizes, &earliness)
+}
+
+/// Whether a buy landed within `block_tolerance` seconds of token creation
+///
+/// A stricter form of `is_early_entry` meant for block-zero buyers: the
+/// tolerance is inclusive, pre-creation timestamps don't count, and sells are
+/// never snipes.
+pub fn is_sniper(interaction: &TokenInteraction, creation_time: u64, block_tolerance: u64) -> bool {
+    interaction.side == TradeSide::Buy && is_early_entry(interaction.block_time, creation_time, block_tolerance)
+}
+
+/// Number of a wallet's buys that were snipes
+///
+/// Interactions with tokens missing from `creation_times` are not counted.
+pub fn sniper_count(
+    wallet_interactions: &[TokenInteraction],
+    creation_times: &HashMap<String, u64>,
+    tolerance: u64,
+) -> u64 {
+    wallet_interactions
+        .iter()
+        .filter(|i| {
+            creation_times
+                .get(&i.token_mint)
+                .is_some_and(|&created| is_sniper(i, created, tolerance))
+        })
+        .count() as u64
+}
+
+/// Fraction of stored `is_early_entry` flags that recomputation agrees with
+///
+/// Each interaction whose token is in `creation_times` has its flag compared
+/// with `is_early_entry(block_time, creation_time, window)`; interactions of
+/// other tokens are left out entirely. A low fraction means the upstream
+/// flagging is stale or uses a different window. With nothing to check the
+/// result is 1.0, since no flag is contradicted.
+pub fn early_entry_consistency(
+    interactions: &[TokenInteraction],
+    creation_times: &HashMap<String, u64>,
+    window: u64,
+) -> f64 {
+    let (checked, consistent) = interactions
+        .iter()
+        .filter_map(|i| {
+            let created = creation_times.get(&i.token_mint)?;
+            Some(i.is_early_entry == is_early_entry(i.block_time, *created, window))
+        })
+        .fold((0usize, 0usize), |(checked, consistent), agrees| (checked + 1, consistent + agrees as usize));
+    if checked == 0 {
+        return 1.0;
+    }
+    consistent as f64 / checked as f64
+}
+
+/// Share of buys `is_pure_sniper` requires to be snipes
+pub const PURE_SNIPER_MIN_SHARE: f64 = 0.95;
+
+/// Whether a wallet does nothing but snipe launches
+///
+/// Only buys of tokens in `creation_times` are judged: at least
+/// `PURE_SNIPER_MIN_SHARE` of them must be snipes (`is_sniper` with
+/// `window`), spread over at least `min_tokens` distinct tokens. Buys of
+/// tokens with unknown creation times are skipped rather than counted
+/// against the wallet, so missing metadata can't hide a sniper, but they
+/// don't count towards `min_tokens` either.
+pub fn is_pure_sniper(
+    wallet_interactions: &[TokenInteraction],
+    creation_times: &HashMap<String, u64>,
+    window: u64,
+    min_tokens: usize,
+) -> bool {
+    let judged: Vec<(&TokenInteraction, u64)> = wallet_interactions
+        .iter()
+        .filter(|i| i.side == TradeSide::Buy)
+        .filter_map(|i| Some((i, *creation_times.get(&i.token_mint)?)))
+        .collect();
+    if judged.is_empty() {
+        return false;
+    }
+
+    let tokens: HashSet<&str> = judged.iter().map(|(i, _)| i.token_mint.as_str()).collect();
+    let snipes = judged.iter().filter(|(i, created)| is_sniper(i, *created, window)).count();
+    tokens.len() >= min_tokens && snipes as f64 / judged.len() as f64 >= PURE_SNIPER_MIN_SHARE
+}
+
+/// How many wallets made their first buy in each time bucket after launch
+///
+/// Bucket `i` counts wallets whose earliest buy of the token landed in
+/// `[i * bucket_seconds, (i + 1) * bucket_seconds)` seconds after
+/// `creation_time` (pre-creation buys fall in bucket 0). The returned vector
+/// has `n_buckets + 1` entries; the last is an overflow bucket for later first
+/// buys. `bucket_seconds` is treated as at least 1.
+pub fn entry_time_histogram(
+    interactions_for_token: &[TokenInteraction],
+    creation_time: u64,
+    bucket_seconds: u64,
+    n_buckets: usize,
+) -> Vec<u64> {
+    let mut first_buys: HashMap<&str, u64> = HashMap::new();
+    for interaction in interactions_for_token {
+        let first = first_buys
+            .entry(interaction.wallet_address.as_str())
+            .or_insert(interaction.block_time);
+        *first = (*first).min(interaction.block_time);
+    }
+
+    let mut counts = vec![0u64; n_buckets + 1];
+    for &time in first_buys.values() {
+        let bucket = saturating_elapsed(time, creation_time) / bucket_seconds.max(1);
+        let index = usize::try_from(bucket).map_or(n_buckets, |b| b.min(n_buckets));
+        counts[index] += 1;
+    }
+    counts
+}
+
+/// Mean gap in seconds between consecutive buys, in `block_time` order
+///
+/// Bots tend to buy on a regular cadence while humans are erratic; the mean
+/// gap is an activity-rate feature that can feed into scoring. Sells are
+/// ignored. Returns `None` for fewer than two buys.
+pub fn buy_cadence(interactions: &[TokenInteraction]) -> Option<f64> {
+    let mut times: Vec<u64> = interactions
+        .iter()
+        .filter(|i| i.side == TradeSide::Buy)
+        .map(|i| i.block_time)
+        .collect();
+    if times.len() < 2 {
+        return None;
+    }
+
+    times.sort_unstable();
+    // Sorted, so the gaps sum to the overall span
+    Some(saturating_elapsed(times[times.len() - 1], times[0]) as f64 / (times.len() - 1) as f64)
+}
+
+/// Normalized Shannon entropy (0-1) of the gaps between consecutive buys
+///
+/// Gaps between buys (in `block_time` order) are binned into buckets of
+/// `bucket_seconds` (at least 1) and the entropy of the bucket distribution
+/// is divided by its maximum, `ln(gap count)`. Clock-regular bots put every
+/// gap in one bucket and score 0; a wallet whose every gap lands in a
+/// different bucket scores 1. Sells are ignored, and fewer than three buys
+/// (at most one gap) return 0.0.
+pub fn timing_entropy(interactions: &[TokenInteraction], bucket_seconds: u64) -> f64 {
+    let mut times: Vec<u64> = interactions
+        .iter()
+        .filter(|i| i.side == TradeSide::Buy)
+        .map(|i| i.block_time)
+        .collect();
+    if times.len() < 3 {
+        return 0.0;
+    }
+    times.sort_unstable();
+
+    let bucket_seconds = bucket_seconds.max(1);
+    let mut counts: HashMap<u64, usize> = HashMap::new();
+    for pair in times.windows(2) {
+        *counts.entry(saturating_elapsed(pair[1], pair[0]) / bucket_seconds).or_insert(0) += 1;
+    }
+
+    let gaps = (times.len() - 1) as f64;
+    let entropy: f64 = counts
+        .values()
+        .map(|&count| count as f64 / gaps)
+        .map(|p| -p * p.ln())
+        .sum();
+    (entropy / gaps.ln()).clamp(0.0, 1.0)
+}
+
+/// 0-100 score for how likely it is that buys are scheduled by a bot
+///
+/// Uses the coefficient of variation (standard deviation over mean) of the
+/// gaps between consecutive buys: perfectly even gaps have a CV of 0 and
+/// score 100, while a CV of 1 or more (as bursty, Poisson-like human
+/// activity tends to have) scores 0. Unlike `timing_entropy` this doesn't
+/// depend on a bucket width, so slow but steady schedules are caught too.
+/// Sells are ignored; fewer than three buys, or all buys in the same
+/// second, return 0.0.
+pub fn uniformity_score(interactions: &[TokenInteraction]) -> f64 {
+    let mut times: Vec<u64> = interactions
+        .iter()
+        .filter(|i| i.side == TradeSide::Buy)
+        .map(|i| i.block_time)
+        .collect();
+    if times.len() < 3 {
+        return 0.0;
+    }
+    times.sort_unstable();
+
+    let gaps: Vec<f64> = times.windows(2).map(|pair| saturating_elapsed(pair[1], pair[0]) as f64).collect();
+    let n = gaps.len() as f64;
+    let mean = gaps.iter().sum::<f64>() / n;
+    if mean <= 0.0 {
+        return 0.0;
+    }
+
+    let std_dev = (gaps.iter().map(|g| (g - mean).powi(2)).sum::<f64>() / n).sqrt();
+    100.0 * (1.0 - (std_dev / mean).min(1.0))
+}
+
+/// Whale score over a sliding time window, for charting its evolution
+///
+/// Windows end at `now`, `now - step`, `now - 2 * step`, ... back to the
+/// wallet's first interaction (`step` is at least 1 second). Each entry is
+/// `(window_end, score)` for the interactions in `(window_end - window,
+/// window_end]`, in ascending time order; windows without interactions score
+/// 0. Interactions after `now` are ignored, and a wallet with none at or
+/// before `now` has an empty timeline.
+pub fn score_timeline(interactions: &[TokenInteraction], window: u64, step: u64, now: u64) -> Vec<(u64, u8)> {
+    let sorted: Vec<&TokenInteraction> = sorted_by_time(interactions).into_iter().filter(|i| i.block_time <= now).collect();
+    let first = match sorted.first() {
+        Some(first) => first.block_time,
+        None => return Vec::new(),
+    };
+
+    let step = step.max(1);
+    let mut timeline = Vec::new();
+    let mut end = now;
+    loop {
+        let in_window: Vec<TokenInteraction> = sorted
+            .iter()
+            .filter(|i| i.block_time <= end && saturating_elapsed(end, i.block_time) < window)
+            .map(|i| (*i).clone())
+            .collect();
+        timeline.push((end, calculate_whale_score(&process_interactions(&in_window))));
+
+        match end.checked_sub(step) {
+            Some(previous) if previous >= first => end = previous,
+            _ => break,
+        }
+    }
+
+    timeline.reverse();
+    timeline
+}
+
+/// `block_time` at which a wallet's cumulative whale score first reached `threshold`
+///
+/// Interactions are replayed in time order and the wallet is rescored after
+/// each block, with every interaction in the same block counted together.
+/// `None` when the score never reaches `threshold` (including when there
+/// are no interactions). Elapsed time since the wallet's first trade is
+/// the result minus its earliest `block_time`.
+pub fn time_to_whale(interactions: &[TokenInteraction], threshold: u8) -> Option<u64> {
+    let sorted = sorted_by_time(interactions);
+    let mut accumulator = WalletAccumulator::new(sorted.first().map_or("", |i| i.wallet_address.as_str()));
+
+    for (i, interaction) in sorted.iter().enumerate() {
+        accumulator.add(interaction);
+        let block_complete = sorted.get(i + 1).is_none_or(|next| next.block_time != interaction.block_time);
+        if block_complete && calculate_whale_score(&accumulator.stats()) >= threshold {
+            return Some(interaction.block_time);
+        }
+    }
+    None
+}
+
+/// Early-entry window that best separates labeled insiders from normal wallets
+///
+/// `labeled` pairs each wallet's interactions with whether it is a known
+/// insider. For every candidate window, `is_early_entry` is recomputed against
+/// `creation_times` (tokens missing from the map are never early), each wallet
+/// is rescored, and the separation is the mean insider score minus the mean
+/// normal score (a class with no wallets averages 0). Returns the candidate
+/// with the largest separation, the first one on ties, or 0 when
+/// `candidate_windows` is empty.
+pub fn optimal_early_window(
+    labeled: &[(Vec<TokenInteraction>, bool)],
+    creation_times: &HashMap<String, u64>,
+    candidate_windows: &[u64],
+) -> u64 {
+    let mean = |scores: &[f64]| {
+        if scores.is_empty() { 0.0 } else { scores.iter().sum::<f64>() / scores.len() as f64 }
+    };
+
+    let mut best: Option<(u64, f64)> = None;
+    for &window in candidate_windows {
+        let mut insider_scores = Vec::new();
+        let mut normal_scores = Vec::new();
+
+        for (interactions, is_insider) in labeled {
+            let relabeled: Vec<TokenInteraction> = interactions
+                .iter()
+                .map(|i| TokenInteraction {
+                    is_early_entry: creation_times
+                        .get(&i.token_mint)
+                        .is_some_and(|&created| is_early_entry(i.block_time, created, window)),
+                    ..i.clone()
+                })
+                .collect();
+            let score = calculate_whale_score(&process_interactions(&relabeled)) as f64;
+            if *is_insider {
+                insider_scores.push(score);
+            } else {
+                normal_scores.push(score);
+            }
+        }
+
+        let separation = mean(&insider_scores) - mean(&normal_scores);
+        if best.is_none_or(|(_, best_separation)| separation > best_separation) {
+            best = Some((window, separation));
+        }
+    }
+
+    best.map_or(0, |(window, _)| window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buy(block_time: u64, sol_amount: f64) -> TokenInteraction {
+        TokenInteraction {
+            wallet_address: "addr1".to_string(),
+            token_mint: "token1".to_string(),
+            block_time,
+            sol_amount,
+            is_early_entry: false,
+            side: TradeSide::Buy,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_saturating_elapsed() {
+        assert_eq!(saturating_elapsed(1060, 1000), 60);
+        assert_eq!(saturating_elapsed(1000, 1000), 0);
+        assert_eq!(saturating_elapsed(1000, 1060), 0);
+        assert_eq!(saturating_elapsed(0, u64::MAX), 0);
+    }
+
+    #[test]
+    fn test_normalize_block_times() {
+        let mut interactions = vec![buy(1000, 1.0), buy(5, 1.0), buy(u64::MAX - 10, 1.0)];
+
+        let forward = normalized_block_times(&interactions, 100);
+        let times: Vec<u64> = forward.iter().map(|i| i.block_time).collect();
+        assert_eq!(times, [1100, 105, u64::MAX]);
+
+        normalize_block_times(&mut interactions, -50);
+        let times: Vec<u64> = interactions.iter().map(|i| i.block_time).collect();
+        assert_eq!(times, [950, 0, u64::MAX - 60]);
+        assert_eq!(normalized_block_times(&interactions, i64::MIN)[0].block_time, 0);
+    }
+
+    #[test]
+    fn test_adaptive_early_window() {
+        // 10 SOL at t=0, 30 at t=10, 40 at t=30, 20 at t=100 (out of order)
+        let interactions = vec![buy(1030, 40.0), buy(1000, 10.0), buy(1100, 20.0), buy(1010, 30.0)];
+
+        assert_eq!(adaptive_early_window(&interactions, 0.1), 0);
+        assert_eq!(adaptive_early_window(&interactions, 0.4), 10);
+        assert_eq!(adaptive_early_window(&interactions, 0.5), 30);
+        assert_eq!(adaptive_early_window(&interactions, 1.0), 100);
+    }
+
+    #[test]
+    fn test_adaptive_early_window_single_block() {
+        let interactions = vec![buy(1000, 10.0), buy(1000, 5.0)];
+
+        assert_eq!(adaptive_early_window(&interactions, 0.5), 0);
+        assert_eq!(adaptive_early_window(&[], 0.5), 0);
+    }
+
+    #[test]
+    fn test_first_mover_score() {
+        let trade = |wallet: &str, mint: &str, block_time: u64| TokenInteraction {
+            wallet_address: wallet.to_string(),
+            token_mint: mint.to_string(),
+            block_time,
+            sol_amount: 1.0,
+            is_early_entry: false,
+            side: TradeSide::Buy,
+            source: None,
+        };
+        let interactions = vec![
+            trade("first", "t1", 1000),
+            trade("mid", "t1", 1005),
+            trade("late", "t1", 1010),
+            trade("first", "t2", 2000),
+            trade("late", "t2", 2050),
+            trade("first", "t2", 2060),
+            trade("mid", "t2", 2030),
+        ];
+        let ranks = entry_ranks(&interactions);
+        assert_eq!(ranks[&("t1".to_string(), 1010)], 3);
+
+        let wallet = |address: &str| -> Vec<TokenInteraction> {
+            interactions.iter().filter(|i| i.wallet_address == address).cloned().collect()
+        };
+
+        assert!((first_mover_score(&wallet("first"), &ranks) - 100.0).abs() < 1e-9);
+        assert!((first_mover_score(&wallet("late"), &ranks) - 100.0 / 3.0).abs() < 1e-9);
+        assert!(first_mover_score(&wallet("first"), &ranks) > first_mover_score(&wallet("mid"), &ranks));
+        assert_eq!(first_mover_score(&wallet("first"), &HashMap::new()), 0.0);
+    }
+
+    #[test]
+    fn test_shared_first_mover_count() {
+        let trade = |wallet: &str, mint: &str, block_time: u64| TokenInteraction {
+            wallet_address: wallet.to_string(),
+            token_mint: mint.to_string(),
+            ..buy(block_time, 1.0)
+        };
+        let mut interactions = Vec::new();
+        for (n, mint) in ["t1", "t2", "t3"].iter().enumerate() {
+            let launch = 1000 * (n as u64 + 1);
+            interactions.push(trade("insider_a", mint, launch));
+            interactions.push(trade("insider_b", mint, launch + 1));
+            for crowd in 0..5 {
+                interactions.push(trade(&format!("crowd{}", crowd), mint, launch + 10 + crowd));
+            }
+            interactions.push(trade("late_a", mint, launch + 100));
+            interactions.push(trade("late_b", mint, launch + 101));
+        }
+        let ranks = entry_ranks(&interactions);
+        let wallet = |address: &str| -> Vec<TokenInteraction> {
+            interactions.iter().filter(|i| i.wallet_address == address).cloned().collect()
+        };
+
+        assert_eq!(shared_first_mover_count(&wallet("insider_a"), &wallet("insider_b"), &ranks, 2), 3);
+        assert_eq!(shared_first_mover_count(&wallet("insider_a"), &wallet("insider_b"), &ranks, 1), 0);
+        // Same three tokens, but never among the first buyers
+        assert_eq!(shared_first_mover_count(&wallet("late_a"), &wallet("late_b"), &ranks, 3), 0);
+        assert_eq!(shared_first_mover_count(&wallet("insider_a"), &wallet("late_a"), &ranks, 3), 0);
+        assert_eq!(shared_first_mover_count(&wallet("insider_a"), &[], &ranks, 3), 0);
+    }
+
+    #[test]
+    fn test_buy_timing_vs_peak() {
+        let trade = |wallet: &str, block_time: u64, sol_amount: f64| TokenInteraction {
+            wallet_address: wallet.to_string(),
+            ..buy(block_time, sol_amount)
+        };
+        // Peak block at t=1050 (60 SOL) in a span of 1000-1100
+        let token = vec![
+            trade("insider", 1000, 5.0),
+            trade("insider", 1010, 5.0),
+            trade("crowd", 1050, 60.0),
+            trade("chaser", 1090, 10.0),
+            trade("chaser", 1100, 10.0),
+        ];
+        let wallet = |address: &str| -> Vec<TokenInteraction> {
+            token.iter().filter(|i| i.wallet_address == address).cloned().collect()
+        };
+
+        let insider = buy_timing_vs_peak(&wallet("insider"), &token);
+        assert!((insider - (-0.45)).abs() < 1e-9);
+        let chaser = buy_timing_vs_peak(&wallet("chaser"), &token);
+        assert!((chaser - 0.45).abs() < 1e-9);
+        assert_eq!(buy_timing_vs_peak(&wallet("crowd"), &token), 0.0);
+        assert_eq!(buy_timing_vs_peak(&wallet("insider"), &[]), 0.0);
+    }
+
+    #[test]
+    fn test_avg_seconds_after_launch() {
+        let trade = |mint: &str, block_time: u64| TokenInteraction {
+            token_mint: mint.to_string(),
+            ..buy(block_time, 1.0)
+        };
+        let interactions = vec![
+            trade("t1", 1030),
+            trade("t1", 1010),
+            trade("t2", 1995),
+            trade("t3", 5000),
+        ];
+        let creation_times: HashMap<String, u64> =
+            [("t1".to_string(), 1000), ("t2".to_string(), 2000)].into_iter().collect();
+
+        // t1: first buy 10s after launch; t2: bought 5s before launch, clamped to 0; t3 unknown
+        assert_eq!(avg_seconds_after_launch(&interactions, &creation_times), Some(5.0));
+        assert_eq!(avg_seconds_after_launch(&interactions[3..], &creation_times), None);
+        assert_eq!(avg_seconds_after_launch(&[], &creation_times), None);
+    }
+
+    #[test]
+    fn test_size_earliness_correlation() {
+        let trade = |mint: &str, block_time: u64, sol_amount: f64| TokenInteraction {
+            token_mint: mint.to_string(),
+            ..buy(block_time, sol_amount)
+        };
+        let creation_times: HashMap<String, u64> =
+            [("t1".to_string(), 1000), ("t2".to_string(), 5000)].into_iter().collect();
+
+        // Seconds after launch: 10, 110, 210
+        let conviction = vec![trade("t1", 1010, 50.0), trade("t2", 5110, 30.0), trade("t1", 1210, 10.0)];
+        let fomo = vec![trade("t1", 1010, 2.0), trade("t2", 5110, 15.0), trade("t1", 1210, 40.0)];
+        assert!((size_earliness_correlation(&conviction, &creation_times) - 1.0).abs() < 1e-9);
+        assert!(size_earliness_correlation(&fomo, &creation_times) < -0.9);
+
+        // Unknown tokens are skipped, leaving too few buys
+        let unknown = vec![trade("t1", 1005, 50.0), trade("t9", 1300, 2.0)];
+        assert_eq!(size_earliness_correlation(&unknown, &creation_times), 0.0);
+        assert_eq!(size_earliness_correlation(&[], &creation_times), 0.0);
+    }
+
+    #[test]
+    fn test_is_sniper_boundary() {
+        assert!(is_sniper(&buy(1000, 1.0), 1000, 2));
+        assert!(is_sniper(&buy(1002, 1.0), 1000, 2));
+        assert!(!is_sniper(&buy(1003, 1.0), 1000, 2));
+        assert!(!is_sniper(&buy(999, 1.0), 1000, 2));
+        let sell = TokenInteraction { side: TradeSide::Sell, ..buy(1000, 1.0) };
+        assert!(!is_sniper(&sell, 1000, 2));
+    }
+
+    #[test]
+    fn test_sniper_count() {
+        let trade = |mint: &str, block_time: u64| TokenInteraction {
+            token_mint: mint.to_string(),
+            ..buy(block_time, 1.0)
+        };
+        let interactions = vec![trade("t1", 1000), trade("t1", 1030), trade("t2", 2001), trade("t3", 3000)];
+        let creation_times: HashMap<String, u64> =
+            [("t1".to_string(), 1000), ("t2".to_string(), 2000)].into_iter().collect();
+
+        // t3 has no known creation time, so its block-zero buy is not counted
+        assert_eq!(sniper_count(&interactions, &creation_times, 1), 2);
+        assert_eq!(sniper_count(&interactions, &creation_times, 0), 1);
+        assert_eq!(sniper_count(&interactions, &HashMap::new(), 1), 0);
+    }
+
+    #[test]
+    fn test_early_entry_consistency() {
+        let trade = |mint: &str, block_time: u64, is_early_entry: bool| TokenInteraction {
+            token_mint: mint.to_string(),
+            is_early_entry,
+            ..buy(block_time, 1.0)
+        };
+        let creation_times: HashMap<String, u64> =
+            [("t1".to_string(), 1000), ("t2".to_string(), 5000)].into_iter().collect();
+
+        let consistent = vec![trade("t1", 1010, true), trade("t1", 2000, false), trade("t2", 5030, true)];
+        assert_eq!(early_entry_consistency(&consistent, &creation_times, 60), 1.0);
+
+        // A stale flag on each token; the unknown token is excluded
+        let stale = vec![
+            trade("t1", 1010, true),
+            trade("t1", 2000, true),
+            trade("t2", 5030, false),
+            trade("t2", 5040, true),
+            trade("unknown", 1, true),
+        ];
+        assert!((early_entry_consistency(&stale, &creation_times, 60) - 0.5).abs() < 1e-9);
+        assert_eq!(early_entry_consistency(&stale, &HashMap::new(), 60), 1.0);
+    }
+
+    #[test]
+    fn test_is_pure_sniper() {
+        let trade = |mint: &str, block_time: u64| TokenInteraction {
+            token_mint: mint.to_string(),
+            ..buy(block_time, 1.0)
+        };
+        let creation_times: HashMap<String, u64> =
+            (1..=4).map(|i| (format!("t{}", i), i * 1000)).collect();
+
+        let sniper = vec![trade("t1", 1001), trade("t2", 2000), trade("t3", 3002), trade("unlisted", 9000)];
+        assert!(is_pure_sniper(&sniper, &creation_times, 2, 3));
+        assert!(!is_pure_sniper(&sniper, &creation_times, 2, 4));
+
+        // Snipes most launches but also buys t4 long after it launched
+        let mixed = vec![trade("t1", 1001), trade("t2", 2000), trade("t3", 3002), trade("t4", 9000)];
+        assert!(!is_pure_sniper(&mixed, &creation_times, 2, 3));
+
+        assert!(!is_pure_sniper(&sniper, &HashMap::new(), 2, 0));
+        assert!(!is_pure_sniper(&[], &creation_times, 2, 0));
+    }
+
+    #[test]
+    fn test_entry_time_histogram() {
+        let trade = |wallet: &str, block_time: u64| TokenInteraction {
+            wallet_address: wallet.to_string(),
+            ..buy(block_time, 1.0)
+        };
+        let interactions = vec![
+            trade("sniper", 1000),
+            trade("sniper", 1100),
+            trade("fast", 1009),
+            trade("early", 1010),
+            trade("early_twin", 1019),
+            trade("late", 1030),
+            trade("much_later", 5000),
+        ];
+
+        // 10s buckets: [0, 10), [10, 20), [20, 30), overflow
+        assert_eq!(entry_time_histogram(&interactions, 1000, 10, 3), vec![2, 2, 0, 2]);
+        assert_eq!(entry_time_histogram(&interactions, 1000, 10, 0), vec![6]);
+        assert_eq!(entry_time_histogram(&[], 1000, 10, 3), vec![0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_chronology_report() {
+        let sorted = vec![buy(1000, 1.0), buy(1010, 1.0), buy(1010, 1.0), buy(1030, 1.0)];
+        let report = chronology_report(&sorted);
+        assert!(report.is_sorted);
+        assert_eq!(report.out_of_order_count, 0);
+        assert_eq!(report.max_gap_seconds, 20);
+        assert_eq!((report.min_block_time, report.max_block_time), (Some(1000), Some(1030)));
+
+        let unsorted = vec![buy(1030, 1.0), buy(1000, 1.0), buy(1020, 1.0), buy(1010, 1.0)];
+        let report = chronology_report(&unsorted);
+        assert!(!report.is_sorted);
+        assert_eq!(report.out_of_order_count, 2);
+        assert_eq!(report.max_gap_seconds, 10);
+
+        let gappy = vec![buy(1000, 1.0), buy(1005, 1.0), buy(90_000, 1.0)];
+        assert_eq!(chronology_report(&gappy).max_gap_seconds, 88_995);
+
+        let empty = chronology_report(&[]);
+        assert!(empty.is_sorted);
+        assert_eq!((empty.max_gap_seconds, empty.min_block_time), (0, None));
+    }
+
+    #[test]
+    fn test_wallet_age_stats() {
+        let interactions = vec![buy(1500, 1.0), buy(1000, 1.0), buy(1200, 1.0)];
+        assert_eq!(
+            wallet_age_stats(&interactions, 2000),
+            Some(AgeStats { first_seen: 1000, last_seen: 1500, age_seconds: 1000, seconds_since_last: 500 })
+        );
+
+        let single = wallet_age_stats(&[buy(1800, 1.0)], 2000).unwrap();
+        assert_eq!(single.first_seen, single.last_seen);
+        assert_eq!(single.age_seconds, single.seconds_since_last);
+        assert_eq!(single.age_seconds, 200);
+
+        assert_eq!(wallet_age_stats(&[], 2000), None);
+    }
+
+    #[test]
+    fn test_buy_cadence() {
+        let regular = vec![buy(1060, 1.0), buy(1000, 1.0), buy(1120, 1.0), buy(1030, 1.0), buy(1090, 1.0)];
+        assert_eq!(buy_cadence(&regular), Some(30.0));
+
+        let mut with_sell = regular.clone();
+        with_sell.push(TokenInteraction { side: TradeSide::Sell, ..buy(5000, 1.0) });
+        assert_eq!(buy_cadence(&with_sell), Some(30.0));
+
+        assert_eq!(buy_cadence(&[buy(1000, 1.0)]), None);
+        assert_eq!(buy_cadence(&[]), None);
+    }
+
+    #[test]
+    fn test_timing_entropy() {
+        let times = |times: &[u64]| -> Vec<TokenInteraction> { times.iter().map(|&t| buy(t, 1.0)).collect() };
+
+        // A buy every 60s, jittered within one 10s bucket
+        let bot = times(&[1000, 1060, 1121, 1183, 1246, 1306]);
+        assert_eq!(timing_entropy(&bot, 10), 0.0);
+
+        // Gaps of 5, 40, 300 and 2000 seconds: every gap in its own bucket
+        let human = times(&[1000, 1005, 1045, 1345, 3345]);
+        assert!((timing_entropy(&human, 10) - 1.0).abs() < 1e-9);
+
+        // Two gaps in one bucket, one in another
+        let mixed = times(&[1000, 1060, 1120, 1500]);
+        assert!(timing_entropy(&mixed, 10) > 0.0 && timing_entropy(&mixed, 10) < 1.0);
+
+        assert_eq!(timing_entropy(&times(&[1000, 1500]), 10), 0.0);
+        assert_eq!(timing_entropy(&[], 10), 0.0);
+    }
+
+    #[test]
+    fn test_uniformity_score() {
+        let times = |times: &[u64]| -> Vec<TokenInteraction> { times.iter().map(|&t| buy(t, 1.0)).collect() };
+
+        let metronome = times(&[1000, 1600, 2200, 2800, 3400, 4000]);
+        assert!((uniformity_score(&metronome) - 100.0).abs() < 1e-9);
+
+        // Slightly jittered hourly schedule still looks automated
+        let jittered = times(&[0, 3600, 7230, 10790, 14400]);
+        assert!(uniformity_score(&jittered) > 95.0);
+
+        // Bursts of quick buys separated by long pauses
+        let human = times(&[1000, 1004, 1011, 9000, 9030, 40000]);
+        assert!(uniformity_score(&human) < 10.0);
+
+        assert_eq!(uniformity_score(&times(&[1000, 1600])), 0.0);
+        assert_eq!(uniformity_score(&times(&[1000, 1000, 1000])), 0.0);
+    }
+
+    #[test]
+    fn test_score_timeline() {
+        let trade = |block_time: u64, sol_amount: f64, is_early_entry: bool| TokenInteraction {
+            is_early_entry,
+            ..buy(block_time, sol_amount)
+        };
+        // A quiet day, a gap, then a day of frequent large early buys
+        let mut interactions = vec![trade(1_000, 0.5, false), trade(20_000, 0.5, false)];
+        interactions.extend((0..30).map(|i| trade(200_000 + i * 1_000, 40.0, true)));
+
+        let day = 86_400;
+        let timeline = score_timeline(&interactions, day, day, 1_000 + 3 * day);
+        let ends: Vec<u64> = timeline.iter().map(|&(end, _)| end).collect();
+        assert_eq!(ends, [1_000, 1_000 + day, 1_000 + 2 * day, 1_000 + 3 * day]);
+
+        let scores: Vec<u8> = timeline.iter().map(|&(_, score)| score).collect();
+        assert!(scores[0] < 10 && scores[1] < 10);
+        assert_eq!(scores[2], 0);
+        assert!(scores[3] > 60);
+
+        // Nothing happened between 120,000 and 190,000
+        assert_eq!(score_timeline(&interactions, 10_000, 70_000, 190_000)[1], (120_000, 0));
+        assert!(score_timeline(&interactions, day, day, 500).is_empty());
+    }
+
+    #[test]
+    fn test_time_to_whale() {
+        let trade = |block_time: u64, sol_amount: f64, is_early_entry: bool| TokenInteraction {
+            is_early_entry,
+            ..buy(block_time, sol_amount)
+        };
+        // A fast ramp: large early buys every minute, fed out of order
+        let mut fast: Vec<TokenInteraction> = (0..12).map(|i| trade(10_000 + i * 60, 40.0, true)).collect();
+        fast.reverse();
+
+        let crossed = time_to_whale(&fast, 60).unwrap();
+        let expected = (1..=fast.len())
+            .map(|n| 10_000 + (n as u64 - 1) * 60)
+            .find(|&end| {
+                let prefix: Vec<TokenInteraction> = fast.iter().filter(|i| i.block_time <= end).cloned().collect();
+                calculate_whale_score(&process_interactions(&prefix)) >= 60
+            })
+            .unwrap();
+        assert_eq!(crossed, expected);
+        assert!(crossed - 10_000 <= 5 * 60);
+
+        // A slow wallet of small late buys never gets there
+        let slow: Vec<TokenInteraction> = (0..12).map(|i| trade(10_000 + i * 86_400, 0.2, false)).collect();
+        assert_eq!(time_to_whale(&slow, 60), None);
+        assert_eq!(time_to_whale(&[], 0), None);
+
+        // Both buys share a block, so the score is only checked once they're both in
+        let same_block = vec![trade(500, 40.0, true), trade(500, 40.0, true)];
+        let both = calculate_whale_score(&process_interactions(&same_block));
+        assert_eq!(time_to_whale(&same_block, both), Some(500));
+    }
+
+    #[test]
+    fn test_optimal_early_window() {
+        let trade = |wallet: &str, mint: &str, block_time: u64| TokenInteraction {
+            wallet_address: wallet.to_string(),
+            token_mint: mint.to_string(),
+            ..buy(block_time, 5.0)
+        };
+        let creation_times: HashMap<String, u64> =
+            [("t1".to_string(), 1000), ("t2".to_string(), 2000)].into_iter().collect();
+        // Insiders buy 5-8s after launch, normal wallets 40-50s after
+        let labeled = vec![
+            (vec![trade("i1", "t1", 1005), trade("i1", "t2", 2008)], true),
+            (vec![trade("i2", "t1", 1006), trade("i2", "t2", 2005)], true),
+            (vec![trade("n1", "t1", 1040), trade("n1", "t2", 2050)], false),
+            (vec![trade("n2", "t1", 1045), trade("n2", "t2", 2042)], false),
+        ];
+
+        // 1s flags nobody, 300s flags everybody; only 10s tells the groups apart
+        assert_eq!(optimal_early_window(&labeled, &creation_times, &[1, 10, 300]), 10);
+        assert_eq!(optimal_early_window(&labeled, &creation_times, &[300, 1]), 300);
+        assert_eq!(optimal_early_window(&labeled, &creation_times, &[]), 0);
+    }
+}
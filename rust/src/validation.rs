@@ -0,0 +1,279 @@
+//! Invariant checks for `WalletStats`
+//! The builder is the safe way to construct stats by hand
+
+use crate::WalletStats;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Relative tolerance when checking `average_entry_size` against `total / count`
+const AVERAGE_TOLERANCE: f64 = 1e-6;
+
+/// A `WalletStats` invariant that doesn't hold
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    /// `early_entry_count` is larger than `interaction_count`
+    EarlyExceedsTotal { early: u64, total: u64 },
+    /// A numeric field is negative or NaN
+    NegativeValue { field: &'static str, value: f64 },
+    /// `winrate_proxy` is outside `[0, 1]`
+    WinrateOutOfRange(f64),
+    /// `average_entry_size` doesn't equal `total_volume_sol / interaction_count`
+    ///
+    /// Only reported by `audit_stats_map`; the builder doesn't check it.
+    AverageMismatch { average: f64, expected: f64 },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::EarlyExceedsTotal { early, total } => {
+                write!(f, "early_entry_count {} exceeds interaction_count {}", early, total)
+            }
+            ValidationError::NegativeValue { field, value } => {
+                write!(f, "{} must be non-negative, got {}", field, value)
+            }
+            ValidationError::WinrateOutOfRange(value) => {
+                write!(f, "winrate_proxy must be within [0, 1], got {}", value)
+            }
+            ValidationError::AverageMismatch { average, expected } => {
+                write!(f, "average_entry_size {} doesn't match total / count {}", average, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Every invariant violation in `stats`, in check order
+fn invariant_violations(stats: &WalletStats) -> Vec<ValidationError> {
+    let mut violations = Vec::new();
+    if stats.early_entry_count > stats.interaction_count {
+        violations.push(ValidationError::EarlyExceedsTotal {
+            early: stats.early_entry_count,
+            total: stats.interaction_count,
+        });
+    }
+
+    let numeric = [
+        ("total_volume_sol", stats.total_volume_sol),
+        ("average_entry_size", stats.average_entry_size),
+        ("avg_realized_multiple", stats.avg_realized_multiple.unwrap_or(0.0)),
+    ];
+    for (field, value) in numeric {
+        if value.is_nan() || value < 0.0 {
+            violations.push(ValidationError::NegativeValue { field, value });
+        }
+    }
+
+    if !(0.0..=1.0).contains(&stats.winrate_proxy) {
+        violations.push(ValidationError::WinrateOutOfRange(stats.winrate_proxy));
+    }
+
+    violations
+}
+
+/// Check the invariants every consistent `WalletStats` satisfies
+pub(crate) fn validate_stats(stats: &WalletStats) -> Result<(), ValidationError> {
+    match invariant_violations(stats).into_iter().next() {
+        Some(violation) => Err(violation),
+        None => Ok(()),
+    }
+}
+
+/// Invariant violations found in one wallet's stats
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsAudit {
+    pub address: String,
+    /// Every violated invariant, in check order
+    pub violations: Vec<ValidationError>,
+}
+
+/// Scan a whole cohort for inconsistent stats
+///
+/// Runs the builder's invariant checks on every wallet, reporting all
+/// violations rather than the first, and additionally checks that
+/// `average_entry_size` equals `total_volume_sol / interaction_count`
+/// (within a relative tolerance; 0 for wallets without interactions). Only
+/// wallets with at least one violation are listed, sorted by address.
+pub fn audit_stats_map(stats_map: &HashMap<String, WalletStats>) -> Vec<StatsAudit> {
+    let mut audits: Vec<StatsAudit> = stats_map
+        .iter()
+        .filter_map(|(address, stats)| {
+            let mut violations = invariant_violations(stats);
+            let expected = if stats.interaction_count == 0 {
+                0.0
+            } else {
+                stats.total_volume_sol / stats.interaction_count as f64
+            };
+            let tolerance = AVERAGE_TOLERANCE * expected.abs().max(1.0);
+            let difference = (stats.average_entry_size - expected).abs();
+            if difference.is_nan() || difference > tolerance {
+                violations.push(ValidationError::AverageMismatch {
+                    average: stats.average_entry_size,
+                    expected,
+                });
+            }
+
+            if violations.is_empty() {
+                None
+            } else {
+                Some(StatsAudit { address: address.clone(), violations })
+            }
+        })
+        .collect();
+    audits.sort_by(|a, b| a.address.cmp(&b.address));
+    audits
+}
+
+/// Builder for `WalletStats` that validates on `build`
+///
+/// Unset fields default to zero / `None`.
+#[derive(Debug, Clone)]
+pub struct WalletStatsBuilder {
+    stats: WalletStats,
+}
+
+impl WalletStats {
+    /// Start building a `WalletStats`
+    ///
+    /// The fields stay public for backward compatibility, but the builder is
+    /// the safe path: `build` rejects inconsistent combinations.
+    pub fn builder() -> WalletStatsBuilder {
+        WalletStatsBuilder {
+            stats: WalletStats::empty(""),
+        }
+    }
+}
+
+impl WalletStatsBuilder {
+    pub fn address(mut self, address: impl Into<String>) -> Self {
+        self.stats.address = address.into();
+        self
+    }
+
+    pub fn total_volume_sol(mut self, total_volume_sol: f64) -> Self {
+        self.stats.total_volume_sol = total_volume_sol;
+        self
+    }
+
+    pub fn interaction_count(mut self, interaction_count: u64) -> Self {
+        self.stats.interaction_count = interaction_count;
+        self
+    }
+
+    pub fn average_entry_size(mut self, average_entry_size: f64) -> Self {
+        self.stats.average_entry_size = average_entry_size;
+        self
+    }
+
+    pub fn early_entry_count(mut self, early_entry_count: u64) -> Self {
+        self.stats.early_entry_count = early_entry_count;
+        self
+    }
+
+    pub fn winrate_proxy(mut self, winrate_proxy: f64) -> Self {
+        self.stats.winrate_proxy = winrate_proxy;
+        self
+    }
+
+    pub fn avg_realized_multiple(mut self, avg_realized_multiple: f64) -> Self {
+        self.stats.avg_realized_multiple = Some(avg_realized_multiple);
+        self
+    }
+
+    /// Validate the invariants and return the stats
+    pub fn build(self) -> Result<WalletStats, ValidationError> {
+        validate_stats(&self.stats)?;
+        Ok(self.stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valid() -> WalletStatsBuilder {
+        WalletStats::builder()
+            .address("addr1")
+            .total_volume_sol(100.0)
+            .interaction_count(10)
+            .average_entry_size(10.0)
+            .early_entry_count(5)
+            .winrate_proxy(0.8)
+    }
+
+    #[test]
+    fn test_builder_builds_valid_stats() {
+        let stats = valid().avg_realized_multiple(2.0).build().unwrap();
+
+        assert_eq!(stats.address, "addr1");
+        assert_eq!(stats.interaction_count, 10);
+        assert_eq!(stats.avg_realized_multiple, Some(2.0));
+    }
+
+    #[test]
+    fn test_builder_rejects_early_above_total() {
+        assert_eq!(
+            valid().early_entry_count(11).build().unwrap_err(),
+            ValidationError::EarlyExceedsTotal { early: 11, total: 10 }
+        );
+    }
+
+    #[test]
+    fn test_builder_rejects_negative_values() {
+        assert_eq!(
+            valid().total_volume_sol(-1.0).build().unwrap_err(),
+            ValidationError::NegativeValue { field: "total_volume_sol", value: -1.0 }
+        );
+        assert!(matches!(
+            valid().average_entry_size(f64::NAN).build(),
+            Err(ValidationError::NegativeValue { field: "average_entry_size", .. })
+        ));
+        assert!(matches!(
+            valid().avg_realized_multiple(-2.0).build(),
+            Err(ValidationError::NegativeValue { field: "avg_realized_multiple", .. })
+        ));
+    }
+
+    #[test]
+    fn test_builder_rejects_winrate_out_of_range() {
+        assert_eq!(
+            valid().winrate_proxy(1.5).build().unwrap_err(),
+            ValidationError::WinrateOutOfRange(1.5)
+        );
+        assert!(valid().winrate_proxy(-0.1).build().is_err());
+    }
+
+    #[test]
+    fn test_audit_stats_map() {
+        let wallet = |address: &str| valid().address(address).build().unwrap();
+        let mut stats_map = HashMap::new();
+        stats_map.insert("clean".to_string(), wallet("clean"));
+        stats_map.insert("empty".to_string(), WalletStats::empty("empty"));
+        stats_map.insert("early".to_string(), WalletStats { early_entry_count: 12, ..wallet("early") });
+        stats_map.insert(
+            "negative".to_string(),
+            WalletStats { total_volume_sol: -100.0, average_entry_size: -10.0, ..wallet("negative") },
+        );
+        stats_map.insert("winrate".to_string(), WalletStats { winrate_proxy: 1.2, ..wallet("winrate") });
+        stats_map.insert("average".to_string(), WalletStats { average_entry_size: 25.0, ..wallet("average") });
+
+        let audits = audit_stats_map(&stats_map);
+        let addresses: Vec<&str> = audits.iter().map(|a| a.address.as_str()).collect();
+        assert_eq!(addresses, ["average", "early", "negative", "winrate"]);
+        assert_eq!(
+            audits[0].violations,
+            vec![ValidationError::AverageMismatch { average: 25.0, expected: 10.0 }]
+        );
+        assert_eq!(audits[1].violations, vec![ValidationError::EarlyExceedsTotal { early: 12, total: 10 }]);
+        assert_eq!(
+            audits[2].violations,
+            vec![
+                ValidationError::NegativeValue { field: "total_volume_sol", value: -100.0 },
+                ValidationError::NegativeValue { field: "average_entry_size", value: -10.0 },
+            ]
+        );
+        assert_eq!(audits[3].violations, vec![ValidationError::WinrateOutOfRange(1.2)]);
+        assert!(audit_stats_map(&HashMap::new()).is_empty());
+    }
+}
@@ -0,0 +1,65 @@
+//! Core scoring math exercised through the public API only
+//!
+//! Nothing here depends on serde, so this target also guards the
+//! `--no-default-features` build: `cargo test --no-default-features --test core_math`
+
+use whale_radar::{
+    calculate_batch_stats, calculate_insider_confidence, calculate_whale_score, is_early_entry,
+    process_interactions, TokenInteraction, TradeSide, WalletStats,
+};
+
+fn interaction(wallet: &str, block_time: u64, sol_amount: f64, is_early_entry: bool) -> TokenInteraction {
+    TokenInteraction {
+        wallet_address: wallet.to_string(),
+        token_mint: "token1".to_string(),
+        block_time,
+        sol_amount,
+        is_early_entry,
+        side: TradeSide::Buy,
+        source: None,
+    }
+}
+
+#[test]
+fn core_scoring_without_serde() {
+    let stats = WalletStats {
+        address: "test".to_string(),
+        total_volume_sol: 100.0,
+        interaction_count: 10,
+        average_entry_size: 10.0,
+        early_entry_count: 5,
+        winrate_proxy: 0.8,
+        avg_realized_multiple: None,
+        net_volume_sol: 100.0,
+    };
+
+    let score = calculate_whale_score(&stats);
+    assert!(score > 0 && score <= 100);
+    assert!(calculate_insider_confidence(5, 10, 10.0, 5.0, 3) > 0);
+    assert!(is_early_entry(1030, 1000, 60));
+}
+
+#[test]
+fn core_batch_processing_without_serde() {
+    let interactions = vec![
+        interaction("addr1", 1000, 10.0, true),
+        interaction("addr1", 2000, 20.0, false),
+        interaction("addr2", 1500, 5.0, true),
+    ];
+
+    let stats = process_interactions(&interactions[..2]);
+    assert_eq!(stats.interaction_count, 2);
+    assert_eq!(stats.average_entry_size, 15.0);
+
+    let batch = calculate_batch_stats(&interactions);
+    assert_eq!(batch.len(), 2);
+    assert_eq!(batch["addr2"].early_entry_count, 1);
+}
+
+#[cfg(not(feature = "serde"))]
+#[test]
+fn serde_feature_is_disabled() {
+    // Only compiled for `--no-default-features`; proves the target built without serde
+    let stats = process_interactions(&[]);
+    assert_eq!(stats.interaction_count, 0);
+}